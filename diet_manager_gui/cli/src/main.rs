@@ -0,0 +1,487 @@
+// Headless access to the diet database for scripts and cron jobs
+use std::path::PathBuf;
+use std::process::ExitCode;
+use chrono::{Duration, Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use diet_manager_core::config::{self, DataPaths};
+use diet_manager_core::crypto;
+use diet_manager_core::database::{self, load_database, save_database};
+use diet_manager_core::models::{Database, Meal, User, plain_food_id};
+use diet_manager_core::service::{catalog, logging, reporting, shopping};
+use diet_manager_core::undo::UndoManager;
+
+// The new passphrase for `encryption enable` and `encryption change-passphrase`
+// in scripts; otherwise it is asked for twice
+const NEW_PASSPHRASE_ENV: &str = "DIET_MANAGER_NEW_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(name = "diet-manager-cli", about = "Log food and check totals without the GUI")]
+struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Directory holding database.json (overrides DIET_MANAGER_DATA_DIR)
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// Use a separate per-profile database (overrides DIET_MANAGER_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add, list or remove food log entries
+    #[command(subcommand)]
+    Log(LogCommand),
+    /// Add, search or share foods in the catalog
+    #[command(subcommand)]
+    Food(FoodCommand),
+    /// Print calorie totals for a day
+    Totals {
+        #[command(flatten)]
+        user: UserArg,
+        /// Date as YYYY-MM-DD (defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Print a shopping list for the meals planned over a date range
+    Shopping {
+        #[command(flatten)]
+        user: UserArg,
+        /// First day as YYYY-MM-DD (defaults to today)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day as YYYY-MM-DD (defaults to a week from the first day)
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Encrypt the database file with a passphrase, change it or turn it off
+    #[command(subcommand)]
+    Encryption(EncryptionCommand),
+}
+
+#[derive(Subcommand)]
+enum EncryptionCommand {
+    /// Encrypt an existing plain database file
+    Enable,
+    /// Re-encrypt the database under a new passphrase
+    ChangePassphrase,
+    /// Store the database as plain JSON again
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum LogCommand {
+    /// Add a food to a user's log
+    Add {
+        #[command(flatten)]
+        user: UserArg,
+        /// Food identifier
+        #[arg(long)]
+        food: String,
+        #[arg(long, default_value_t = 1.0)]
+        servings: f32,
+        /// Date as YYYY-MM-DD (defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+        /// breakfast, lunch, dinner or snack
+        #[arg(long, default_value = "snack")]
+        meal: String,
+        /// Plan the food instead of logging it as eaten
+        #[arg(long)]
+        plan: bool,
+    },
+    /// List a user's entries for a day
+    List {
+        #[command(flatten)]
+        user: UserArg,
+        /// Date as YYYY-MM-DD (defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Remove an entry by its position in `log list`
+    Remove {
+        #[command(flatten)]
+        user: UserArg,
+        /// Date as YYYY-MM-DD (defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+        /// 1-based position as shown by `log list`, or the entry's id
+        entry: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FoodCommand {
+    /// Add a basic food to the catalog
+    Add {
+        /// Account adding the food, which makes it private to them; read-only
+        /// accounts may not
+        #[arg(long)]
+        user: String,
+        /// Share the food with everyone right away
+        #[arg(long)]
+        shared: bool,
+        #[arg(long)]
+        id: String,
+        /// Display name (defaults to the identifier)
+        #[arg(long, default_value = "")]
+        name: String,
+        /// Comma-separated keywords
+        #[arg(long)]
+        keywords: String,
+        #[arg(long)]
+        calories: String,
+    },
+    /// Search foods by name or keyword
+    Search {
+        /// Also search this user's private foods
+        #[arg(long)]
+        user: Option<String>,
+        terms: Vec<String>,
+        /// Require every term to match
+        #[arg(long)]
+        all: bool,
+    },
+    /// Move a private food into the shared catalog
+    Share {
+        /// The food's owner, or an admin
+        #[arg(long)]
+        user: String,
+        /// Food identifier
+        id: String,
+    },
+}
+
+#[derive(Args)]
+struct UserArg {
+    /// Username whose log to use
+    #[arg(long)]
+    user: String,
+}
+
+#[derive(Serialize)]
+struct LogLine {
+    index: usize,
+    id: String,
+    date: String,
+    food_id: String,
+    food_name: String,
+    servings: f32,
+    meal: &'static str,
+    planned: bool,
+    calories: f32,
+}
+
+#[derive(Serialize)]
+struct FoodLine {
+    id: String,
+    name: String,
+    kind: &'static str,
+    private: bool,
+    calories_per_serving: f32,
+    keywords: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let configured = DataPaths::resolve(cli.data_dir.clone(), cli.profile.clone())
+        .and_then(|paths| config::init(paths).map_err(|e| format!("Could not prepare data directory: {}", e)));
+    if let Err(error) = configured {
+        eprintln!("Error: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    let result = match &cli.command {
+        // These work on the file itself rather than on the loaded database
+        Command::Encryption(command) => run_encryption(&cli, command),
+        _ => open_database().and_then(|mut db| {
+            // Each invocation is a single action, so only the latest snapshot is kept
+            let mut undo = UndoManager::new(1);
+            run(&cli, &mut db, &mut undo)
+        }),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            if cli.json {
+                println!("{}", serde_json::json!({ "error": error }));
+            } else {
+                eprintln!("Error: {}", error);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli, db: &mut Database, undo: &mut UndoManager) -> Result<(), String> {
+    match &cli.command {
+        Command::Log(LogCommand::Add { user, food, servings, date, meal, plan }) => {
+            let user_id = find_user(db, &user.user)?.user_id.clone();
+            let date = parse_date(date)?;
+            let meal = Meal::parse(meal).ok_or_else(|| format!("Unknown meal '{}'", meal))?;
+
+            if *plan {
+                logging::plan_entry(db, undo, &user_id, &date, food, *servings, meal)?;
+            } else {
+                logging::add_entry(db, undo, &user_id, &date, food, *servings, meal)?;
+            }
+            save(db)?;
+
+            let lines = log_lines(db, &user_id, &date);
+            if let Some(line) = lines.last() {
+                print(cli.json, line, |line| {
+                    format!("{} {} x{:.1} ({:.0} kcal) to {} on {}", if line.planned { "Planned" } else { "Added" }, line.food_name, line.servings, line.calories, line.meal.to_lowercase(), line.date)
+                });
+            }
+        }
+        Command::Log(LogCommand::List { user, date }) => {
+            let user_id = find_user(db, &user.user)?.user_id.clone();
+            let date = parse_date(date)?;
+            let lines = log_lines(db, &user_id, &date);
+
+            print(cli.json, &lines, |lines| {
+                if lines.is_empty() {
+                    return format!("No entries for {}", date);
+                }
+                lines
+                    .iter()
+                    .map(|l| {
+                        let planned = if l.planned { " [planned]" } else { "" };
+                        format!("{:>3}. {:<9} {} x{:.1} ({:.0} kcal){}", l.index, l.meal, l.food_name, l.servings, l.calories, planned)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        Command::Log(LogCommand::Remove { user, date, entry }) => {
+            let user_id = find_user(db, &user.user)?.user_id.clone();
+            let date = parse_date(date)?;
+            let lines = log_lines(db, &user_id, &date);
+
+            let removed = match entry.trim().parse::<usize>() {
+                Ok(index) => index.checked_sub(1).and_then(|i| lines.get(i)),
+                Err(_) => lines.iter().find(|line| line.id == entry.trim()),
+            };
+            let removed = removed.ok_or_else(|| format!("No entry {} on {}", entry, date))?;
+
+            logging::remove_entry(db, undo, &user_id, &removed.id)?;
+            save(db)?;
+
+            print(cli.json, removed, |line| format!("Removed {} x{:.1} from {}", line.food_name, line.servings, line.date));
+        }
+        Command::Food(FoodCommand::Add { user, shared, id, name, keywords, calories }) => {
+            let user_id = find_user(db, user)?.user_id.clone();
+            let mut food_id = catalog::add_basic_food(db, undo, &user_id, id, name, keywords, calories)?;
+            if *shared {
+                food_id = catalog::share_food(db, undo, &user_id, &food_id)?;
+            }
+            save(db)?;
+
+            let line = find_food_line(db, &user_id, &food_id)?;
+            let scope = if line.private { "private food" } else { "shared food" };
+            print(cli.json, &line, |line| format!("Added {} {} ({:.0} kcal)", scope, line.name, line.calories_per_serving));
+        }
+        Command::Food(FoodCommand::Share { user, id }) => {
+            let user_id = find_user(db, user)?.user_id.clone();
+            let food_id = db.resolve_food_id(&user_id, id).unwrap_or_else(|| id.clone());
+            let food_id = catalog::share_food(db, undo, &user_id, &food_id)?;
+            save(db)?;
+
+            let line = find_food_line(db, &user_id, &food_id)?;
+            print(cli.json, &line, |line| format!("Shared {} with everyone", line.name));
+        }
+        Command::Food(FoodCommand::Search { user, terms, all }) => {
+            let user_id = match user {
+                Some(user) => Some(find_user(db, user)?.user_id.clone()),
+                None => None,
+            };
+            let lines: Vec<FoodLine> = catalog::search_foods(db, user_id.as_deref(), &terms.join(" "), *all)
+                .into_iter()
+                .map(food_line)
+                .collect();
+
+            print(cli.json, &lines, |lines| {
+                if lines.is_empty() {
+                    return "No matching foods found".to_string();
+                }
+                lines
+                    .iter()
+                    .map(|f| format!("{:<20} {:<30} {:>6.0} kcal  [{}{}]", f.id, f.name, f.calories_per_serving, f.kind, if f.private { ", private" } else { "" }))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        Command::Totals { user, date } => {
+            let user_id = find_user(db, &user.user)?.user_id.clone();
+            let date = parse_date(date)?;
+            let totals = reporting::daily_summary(db, &user_id, &date);
+
+            print(cli.json, &totals, |t| {
+                let mut text = format!(
+                    "{}\n  Consumed:  {:.0} kcal\n  Burned:    {:.0} kcal\n  Target:    {:.0} kcal\n  {}: {:.0} kcal",
+                    t.date,
+                    t.consumed,
+                    t.burned,
+                    t.target,
+                    if t.remaining >= 0.0 { "Remaining" } else { "Exceeded " },
+                    t.remaining.abs()
+                );
+                if t.planned > 0.0 {
+                    text.push_str(&format!("\n  Planned:   {:.0} kcal (projected {:.0} kcal left)", t.planned, t.projected_remaining()));
+                }
+                text
+            });
+        }
+        Command::Shopping { user, from, to } => {
+            let user_id = find_user(db, &user.user)?.user_id.clone();
+            let from = parse_date(from)?;
+            let to = match to {
+                Some(_) => parse_date(to)?,
+                None => (NaiveDate::parse_from_str(&from, "%Y-%m-%d").map_err(|e| e.to_string())? + Duration::days(6))
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            };
+            let items = shopping::shopping_list(db, &user_id, &from, &to)?;
+
+            print(cli.json, &items, |items| shopping::shopping_list_to_text(items, &from, &to).trim_end().to_string());
+        }
+        Command::Encryption(command) => run_encryption(cli, command)?,
+    }
+
+    Ok(())
+}
+
+fn run_encryption(cli: &Cli, command: &EncryptionCommand) -> Result<(), String> {
+    let path = config::database_file();
+    let message = match command {
+        EncryptionCommand::Enable => {
+            database::encrypt_database(&path, &read_new_passphrase()?)?;
+            "Encrypted"
+        }
+        EncryptionCommand::ChangePassphrase => {
+            let current = read_passphrase("Current passphrase: ")?;
+            database::change_passphrase(&path, &current, &read_new_passphrase()?)?;
+            "Changed the passphrase of"
+        }
+        EncryptionCommand::Disable => {
+            database::decrypt_database(&path, &read_passphrase("Passphrase: ")?)?;
+            "Decrypted"
+        }
+    };
+
+    let status = serde_json::json!({ "path": path, "encrypted": database::is_encrypted(&path) });
+    print(cli.json, status, |_| format!("{} {}", message, path.display()));
+    Ok(())
+}
+
+// Ask for the passphrase first when the database file is encrypted
+fn open_database() -> Result<Database, String> {
+    let path = config::database_file();
+    if database::is_locked(&path) {
+        database::unlock_database(&path, &read_passphrase("Database passphrase: ")?)?;
+    }
+    Ok(load_database())
+}
+
+// From DIET_MANAGER_PASSPHRASE when set, otherwise typed without echo
+fn read_passphrase(prompt: &str) -> Result<String, String> {
+    match std::env::var(crypto::PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => rpassword::prompt_password(prompt).map_err(|e| format!("Could not read the passphrase: {}", e)),
+    }
+}
+
+fn read_new_passphrase() -> Result<String, String> {
+    if let Ok(passphrase) = std::env::var(NEW_PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+
+    let prompt = |text: &str| rpassword::prompt_password(text).map_err(|e| format!("Could not read the passphrase: {}", e));
+    let passphrase = prompt("New passphrase: ")?;
+    if prompt("Repeat the new passphrase: ")? != passphrase {
+        return Err("The passphrases do not match".to_string());
+    }
+    Ok(passphrase)
+}
+
+fn print<T: Serialize>(json: bool, value: T, text: impl FnOnce(T) -> String) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+    } else {
+        println!("{}", text(value));
+    }
+}
+
+fn save(db: &Database) -> Result<(), String> {
+    save_database(db).map_err(|e| format!("Failed to save database: {}", e))
+}
+
+fn find_user<'a>(db: &'a Database, username: &str) -> Result<&'a User, String> {
+    db.users
+        .get(username)
+        .ok_or_else(|| format!("User '{}' not found", username))
+}
+
+fn parse_date(date: &Option<String>) -> Result<String, String> {
+    match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date)),
+        None => Ok(Local::now().format("%Y-%m-%d").to_string()),
+    }
+}
+
+fn log_lines(db: &Database, user_id: &str, date: &str) -> Vec<LogLine> {
+    db.food_logs
+        .get(user_id)
+        .map_or(Vec::new(), |entries| {
+            entries
+                .iter()
+                .filter(|e| e.date == date)
+                .enumerate()
+                .map(|(i, e)| LogLine {
+                    index: i + 1,
+                    id: e.id.clone(),
+                    date: e.date.clone(),
+                    food_id: plain_food_id(&e.food_id).to_string(),
+                    food_name: db.food_name(&e.food_id),
+                    servings: e.servings,
+                    meal: e.meal.label(),
+                    planned: e.planned,
+                    calories: db.get_food_calories(&e.food_id).unwrap_or(0.0) * e.servings,
+                })
+                .collect()
+        })
+}
+
+fn find_food_line(db: &Database, user_id: &str, food_id: &str) -> Result<FoodLine, String> {
+    let user_id = (!user_id.is_empty()).then_some(user_id);
+    catalog::search_foods(db, user_id, "", false)
+        .into_iter()
+        .find(|f| f.id == food_id)
+        .map(food_line)
+        .ok_or_else(|| format!("Food '{}' was not saved", plain_food_id(food_id)))
+}
+
+// Private foods are shown by the identifier their owner typed
+fn food_line(food: catalog::FoodMatch) -> FoodLine {
+    FoodLine {
+        id: plain_food_id(&food.id).to_string(),
+        name: food.name,
+        kind: if food.is_composite { "composite" } else { "basic" },
+        private: food.is_private,
+        calories_per_serving: food.calories_per_serving,
+        keywords: food.keywords,
+    }
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+pub const DATA_DIR_ENV: &str = "DIET_MANAGER_DATA_DIR";
+pub const PROFILE_ENV: &str = "DIET_MANAGER_PROFILE";
+
+const APP_DIR_NAME: &str = "diet_manager";
+const DATABASE_FILE: &str = "database.json";
+const ACTION_LOG_FILE: &str = "diet_manager_actions.log";
+const RECENT_DATABASES_FILE: &str = "recent_databases.json";
+const MAX_RECENT_DATABASES: usize = 8;
+
+static DATA_PATHS: OnceLock<DataPaths> = OnceLock::new();
+static ACTIVE_DATABASE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+// Where one profile's database and action log live
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataPaths {
+    pub data_dir: PathBuf,
+    pub profile: Option<String>,
+}
+
+impl DataPaths {
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self { data_dir, profile: None }
+    }
+
+    // Pick the data directory: command-line flag, then environment variable, then
+    // the platform data dir (XDG_DATA_HOME on Linux). A profile gets its own
+    // subdirectory so several people or test setups can share a machine.
+    pub fn resolve(dir_flag: Option<PathBuf>, profile_flag: Option<String>) -> Result<Self, String> {
+        let base = dir_flag
+            .or_else(|| non_empty_env(DATA_DIR_ENV).map(PathBuf::from))
+            .unwrap_or_else(default_data_dir);
+        let profile = profile_flag.or_else(|| non_empty_env(PROFILE_ENV));
+
+        Self::for_profile(base, profile.as_deref())
+    }
+
+    pub fn for_profile(base: PathBuf, profile: Option<&str>) -> Result<Self, String> {
+        match profile {
+            Some(name) => {
+                validate_profile_name(name)?;
+                Ok(Self {
+                    data_dir: base.join("profiles").join(name),
+                    profile: Some(name.to_string()),
+                })
+            }
+            None => Ok(Self::new(base)),
+        }
+    }
+
+    pub fn database_file(&self) -> PathBuf {
+        self.data_dir.join(DATABASE_FILE)
+    }
+
+    pub fn action_log_file(&self) -> PathBuf {
+        self.data_dir.join(ACTION_LOG_FILE)
+    }
+
+    // Database files opened recently, most recent first
+    pub fn recent_databases(&self) -> Vec<PathBuf> {
+        fs::read_to_string(self.data_dir.join(RECENT_DATABASES_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn remember_database(&self, path: &Path) -> io::Result<()> {
+        let path = std::path::absolute(path)?;
+        let mut recent = self.recent_databases();
+        recent.retain(|existing| *existing != path);
+        recent.insert(0, path);
+        recent.truncate(MAX_RECENT_DATABASES);
+
+        fs::create_dir_all(&self.data_dir)?;
+        let data = serde_json::to_string_pretty(&recent).map_err(io::Error::other)?;
+        fs::write(self.data_dir.join(RECENT_DATABASES_FILE), data)
+    }
+
+    // Create the directory. A database.json in the working directory (where older
+    // versions kept it) is copied into the default profile the first time so
+    // existing data isn't lost; named profiles always start fresh.
+    pub fn prepare(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.data_dir)?;
+
+        let legacy = Path::new(DATABASE_FILE);
+        let target = self.database_file();
+        if self.profile.is_none() && !target.exists() && legacy.is_file() && !same_file(legacy, &target) {
+            fs::copy(legacy, &target)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Set the paths for this process. Call once at startup, before loading the database.
+pub fn init(paths: DataPaths) -> io::Result<&'static DataPaths> {
+    paths.prepare()?;
+    Ok(DATA_PATHS.get_or_init(|| paths))
+}
+
+// The configured paths, falling back to the environment/default when init wasn't called
+pub fn data_paths() -> &'static DataPaths {
+    DATA_PATHS.get_or_init(|| {
+        DataPaths::resolve(None, None).unwrap_or_else(|_| DataPaths::new(default_data_dir()))
+    })
+}
+
+// The database file in use: one opened at runtime, otherwise the data dir's database.json
+pub fn database_file() -> PathBuf {
+    ACTIVE_DATABASE
+        .read()
+        .ok()
+        .and_then(|active| active.clone())
+        .unwrap_or_else(|| data_paths().database_file())
+}
+
+// Point load_database/save_database at another file
+pub fn set_database_file(path: PathBuf) {
+    if let Ok(mut active) = ACTIVE_DATABASE.write() {
+        *active = Some(path);
+    }
+}
+
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid profile name '{}': use letters, digits, '-' or '_'", name))
+    }
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_live_in_the_data_dir() {
+        let paths = DataPaths::new(PathBuf::from("/data/diet"));
+
+        assert_eq!(paths.database_file(), PathBuf::from("/data/diet/database.json"));
+        assert_eq!(paths.action_log_file(), PathBuf::from("/data/diet/diet_manager_actions.log"));
+    }
+
+    #[test]
+    fn flag_overrides_default() {
+        let paths = DataPaths::resolve(Some(PathBuf::from("/tmp/custom")), None).unwrap();
+
+        assert_eq!(paths.data_dir, PathBuf::from("/tmp/custom"));
+    }
+
+    #[test]
+    fn profiles_get_their_own_directory() {
+        let paths = DataPaths::for_profile(PathBuf::from("/data"), Some("work")).unwrap();
+
+        assert_eq!(paths.data_dir, PathBuf::from("/data/profiles/work"));
+        assert_eq!(paths.profile.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn profile_names_cannot_escape_the_data_dir() {
+        for name in ["", "..", "a/b", "a b"] {
+            assert!(DataPaths::for_profile(PathBuf::from("/data"), Some(name)).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn recent_databases_are_most_recent_first_without_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = DataPaths::new(dir.path().to_path_buf());
+        let a = dir.path().join("a.json");
+        let b = dir.path().join("b.json");
+
+        assert!(paths.recent_databases().is_empty());
+        paths.remember_database(&a).unwrap();
+        paths.remember_database(&b).unwrap();
+        paths.remember_database(&a).unwrap();
+
+        assert_eq!(paths.recent_databases(), vec![a, b]);
+    }
+
+    #[test]
+    fn recent_databases_are_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = DataPaths::new(dir.path().to_path_buf());
+        for i in 0..MAX_RECENT_DATABASES + 3 {
+            paths.remember_database(&dir.path().join(format!("{}.json", i))).unwrap();
+        }
+
+        assert_eq!(paths.recent_databases().len(), MAX_RECENT_DATABASES);
+    }
+
+    #[test]
+    fn prepare_creates_missing_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = DataPaths::new(dir.path().join("nested").join("dir"));
+
+        paths.prepare().unwrap();
+        assert!(paths.data_dir.is_dir());
+    }
+}
@@ -0,0 +1,307 @@
+// Encryption of the database file at rest. The key is derived from a master
+// passphrase with Argon2id and the JSON is sealed with XChaCha20-Poly1305, so a
+// wrong passphrase or a tampered file fails to open instead of loading garbage.
+// Account passwords are hashed with Argon2id too.
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use data_encoding::BASE64;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Scripts and the server can pass the passphrase this way instead of a prompt
+pub const PASSPHRASE_ENV: &str = "DIET_MANAGER_PASSPHRASE";
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+const FORMAT: &str = "diet-manager-encrypted";
+// Version 2 also authenticates the key derivation settings; version 1 files still open
+const VERSION: u32 = 2;
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 24;
+const KEY_BYTES: usize = 32;
+
+// Argon2id cost settings, stored in the file so they can be raised later
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    // The OWASP recommendation for Argon2id
+    fn default() -> Self {
+        Self { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+}
+
+// Limits on settings read from a file, so a crafted one can't make opening it
+// take gigabytes of memory or minutes of work
+const MAX_KDF: KdfParams = KdfParams { memory_kib: 1024 * 1024, iterations: 16, parallelism: 16 };
+
+impl KdfParams {
+    fn check(&self) -> Result<(), String> {
+        if self.memory_kib > MAX_KDF.memory_kib || self.iterations > MAX_KDF.iterations || self.parallelism > MAX_KDF.parallelism {
+            return Err(format!(
+                "The encrypted database asks for key derivation settings above the limit ({} MiB, {} iterations, {} lanes)",
+                MAX_KDF.memory_kib / 1024, MAX_KDF.iterations, MAX_KDF.parallelism
+            ));
+        }
+        Ok(())
+    }
+}
+
+// What an encrypted database file holds; everything but the header is opaque
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// A key derived from the passphrase and one file's salt. The passphrase is kept
+// so a file with a different salt (e.g. after a passphrase change elsewhere) can
+// still be opened.
+#[derive(Clone)]
+pub struct DatabaseKey {
+    passphrase: String,
+    salt: Vec<u8>,
+    params: KdfParams,
+    key: [u8; KEY_BYTES],
+}
+
+impl DatabaseKey {
+    // A key with a fresh random salt, for encrypting a file for the first time
+    // or after changing the passphrase
+    pub fn new(passphrase: &str) -> Result<Self, String> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            return Err(format!("The passphrase must be at least {} characters", MIN_PASSPHRASE_LENGTH));
+        }
+        let salt: [u8; SALT_BYTES] = rand::thread_rng().gen();
+        Self::derive(passphrase, &salt, KdfParams::default())
+    }
+
+    fn derive(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Self, String> {
+        let argon_params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(KEY_BYTES))
+            .map_err(|e| format!("Invalid key derivation settings: {}", e))?;
+        let mut key = [0u8; KEY_BYTES];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Could not derive the key: {}", e))?;
+
+        Ok(Self { passphrase: passphrase.to_string(), salt: salt.to_vec(), params, key })
+    }
+
+    // The same key when the salt matches, otherwise derive one for that salt
+    fn for_salt(&self, salt: &[u8], params: KdfParams) -> Result<Self, String> {
+        if self.salt == salt && self.params == params {
+            Ok(self.clone())
+        } else {
+            Self::derive(&self.passphrase, salt, params)
+        }
+    }
+}
+
+// Whether file contents are an encrypted database rather than plain JSON
+pub fn is_encrypted(data: &str) -> bool {
+    serde_json::from_str::<Envelope>(data).is_ok_and(|envelope| envelope.format == FORMAT)
+}
+
+pub fn encrypt(plaintext: &[u8], key: &DatabaseKey) -> Result<String, String> {
+    let nonce: [u8; NONCE_BYTES] = rand::thread_rng().gen();
+    let ciphertext = XChaCha20Poly1305::new((&key.key).into())
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: &associated_data(VERSION, &key.params) })
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let envelope = Envelope {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        kdf: key.params,
+        salt: BASE64.encode(&key.salt),
+        nonce: BASE64.encode(&nonce),
+        ciphertext: BASE64.encode(&ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| e.to_string())
+}
+
+// Open an encrypted file with a passphrase. Returns the contents and the key,
+// which can be reused to save the file again without another derivation.
+pub fn decrypt(data: &str, passphrase: &str) -> Result<(Vec<u8>, DatabaseKey), String> {
+    let envelope = parse_envelope(data)?;
+    let key = DatabaseKey::derive(passphrase, &decode(&envelope.salt)?, envelope.kdf)?;
+    let plaintext = open(&envelope, &key)?;
+    Ok((plaintext, key))
+}
+
+// Open an encrypted file with an already unlocked key
+pub fn decrypt_with(data: &str, key: &DatabaseKey) -> Result<Vec<u8>, String> {
+    let envelope = parse_envelope(data)?;
+    let key = key.for_salt(&decode(&envelope.salt)?, envelope.kdf)?;
+    open(&envelope, &key)
+}
+
+fn parse_envelope(data: &str) -> Result<Envelope, String> {
+    let envelope: Envelope = serde_json::from_str(data)
+        .ok()
+        .filter(|envelope: &Envelope| envelope.format == FORMAT)
+        .ok_or("The file is not an encrypted database")?;
+    if !(1..=VERSION).contains(&envelope.version) {
+        return Err(format!("Unsupported encrypted database version {}", envelope.version));
+    }
+    envelope.kdf.check()?;
+    Ok(envelope)
+}
+
+// Authenticated along with the ciphertext, so the header can't be edited unnoticed
+fn associated_data(version: u32, params: &KdfParams) -> Vec<u8> {
+    if version == 1 {
+        return FORMAT.as_bytes().to_vec();
+    }
+    let params = serde_json::to_string(params).unwrap_or_default();
+    format!("{}\n{}\n{}", FORMAT, version, params).into_bytes()
+}
+
+fn open(envelope: &Envelope, key: &DatabaseKey) -> Result<Vec<u8>, String> {
+    let nonce = decode(&envelope.nonce)?;
+    if nonce.len() != NONCE_BYTES {
+        return Err("The encrypted database is damaged".to_string());
+    }
+    let ciphertext = decode(&envelope.ciphertext)?;
+
+    XChaCha20Poly1305::new((&key.key).into())
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &associated_data(envelope.version, &envelope.kdf) })
+        .map_err(|_| "Wrong passphrase, or the file is damaged".to_string())
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(text.as_bytes())
+        .map_err(|_| "The encrypted database is damaged".to_string())
+}
+
+// An account password as an Argon2id PHC string ("$argon2id$v=19$..."), which
+// carries its own random salt and cost settings
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt: [u8; SALT_BYTES] = rand::thread_rng().gen();
+    let salt = SaltString::encode_b64(&salt).map_err(|e| format!("Could not hash the password: {}", e))?;
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Could not hash the password: {}", e))
+}
+
+// Whether a password matches a hash from hash_password. Anything else stored,
+// e.g. a plaintext password from an old file, never matches.
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    PasswordHash::new(stored)
+        .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+// Whether a stored password is already hashed rather than plaintext from before hashing
+pub fn is_password_hash(stored: &str) -> bool {
+    PasswordHash::new(stored).is_ok_and(|hash| hash.algorithm == argon2::ARGON2ID_IDENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwords_are_stored_as_salted_hashes() {
+        let hash = hash_password("hunter2").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!hash.contains("hunter2"));
+        assert!(is_password_hash(&hash));
+        assert_ne!(hash, hash_password("hunter2").unwrap());
+
+        assert!(verify_password("hunter2", &hash));
+        assert!(!verify_password("hunter3", &hash));
+        // A plaintext password left in an old file is not a hash and never matches
+        assert!(!is_password_hash("hunter2"));
+        assert!(!verify_password("hunter2", "hunter2"));
+    }
+
+    #[test]
+    fn encrypt_then_decrypt_round_trips() {
+        let key = DatabaseKey::new("correct horse").unwrap();
+        let sealed = encrypt(b"{\"users\":{}}", &key).unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("users"));
+        assert_eq!(decrypt(&sealed, "correct horse").unwrap().0, b"{\"users\":{}}");
+        assert_eq!(decrypt_with(&sealed, &key).unwrap(), b"{\"users\":{}}");
+    }
+
+    #[test]
+    fn wrong_passphrase_and_tampering_are_rejected() {
+        let key = DatabaseKey::new("correct horse").unwrap();
+        let sealed = encrypt(b"secret", &key).unwrap();
+        assert!(decrypt(&sealed, "wrong horse").is_err());
+
+        let mut envelope: Envelope = serde_json::from_str(&sealed).unwrap();
+        let mut ciphertext = BASE64.decode(envelope.ciphertext.as_bytes()).unwrap();
+        ciphertext[0] ^= 1;
+        envelope.ciphertext = BASE64.encode(&ciphertext);
+        let tampered = serde_json::to_string(&envelope).unwrap();
+        assert!(decrypt(&tampered, "correct horse").is_err());
+    }
+
+    #[test]
+    fn key_derivation_settings_are_authenticated_and_limited() {
+        let key = DatabaseKey::new("correct horse").unwrap();
+        let sealed = encrypt(b"secret", &key).unwrap();
+        let mut envelope: Envelope = serde_json::from_str(&sealed).unwrap();
+        assert_eq!(envelope.version, VERSION);
+
+        // Weaker settings with the same key still fail to open
+        envelope.kdf.iterations = 1;
+        let downgraded = serde_json::to_string(&envelope).unwrap();
+        let weak_key = DatabaseKey::derive("correct horse", &key.salt, envelope.kdf).unwrap();
+        assert!(open(&serde_json::from_str(&downgraded).unwrap(), &weak_key).is_err());
+
+        envelope.kdf.memory_kib = u32::MAX;
+        let costly = serde_json::to_string(&envelope).unwrap();
+        assert!(decrypt(&costly, "correct horse").err().unwrap().contains("above the limit"));
+    }
+
+    #[test]
+    fn version_1_files_still_open() {
+        let key = DatabaseKey::new("correct horse").unwrap();
+        let nonce = [7u8; NONCE_BYTES];
+        let ciphertext = XChaCha20Poly1305::new((&key.key).into())
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: b"old", aad: FORMAT.as_bytes() })
+            .unwrap();
+        let envelope = Envelope {
+            format: FORMAT.to_string(),
+            version: 1,
+            kdf: key.params,
+            salt: BASE64.encode(&key.salt),
+            nonce: BASE64.encode(&nonce),
+            ciphertext: BASE64.encode(&ciphertext),
+        };
+        let sealed = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(decrypt(&sealed, "correct horse").unwrap().0, b"old");
+    }
+
+    #[test]
+    fn each_key_and_save_gets_fresh_randomness() {
+        let a = DatabaseKey::new("correct horse").unwrap();
+        let b = DatabaseKey::new("correct horse").unwrap();
+        assert_ne!(a.salt, b.salt);
+        assert_ne!(encrypt(b"same", &a).unwrap(), encrypt(b"same", &a).unwrap());
+
+        // A key for another salt still opens the file through its passphrase
+        assert_eq!(decrypt_with(&encrypt(b"same", &b).unwrap(), &a).unwrap(), b"same");
+    }
+
+    #[test]
+    fn short_passphrases_and_plain_json_are_refused() {
+        assert!(DatabaseKey::new("short").is_err());
+        assert!(!is_encrypted("{\"users\":{}}"));
+        assert!(!is_encrypted("not json"));
+    }
+}
@@ -0,0 +1,515 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+use crate::config;
+use crate::crypto::{self, DatabaseKey};
+use crate::models::Database;
+
+// Keys of the encrypted database files unlocked in this process. Other files,
+// e.g. a new database created from the login screen, are saved unencrypted.
+static UNLOCKED: RwLock<Vec<(PathBuf, DatabaseKey)>> = RwLock::new(Vec::new());
+
+// What each database file looked like when this process last read or wrote it
+static SYNCED: Mutex<Vec<(PathBuf, FileStamp)>> = Mutex::new(Vec::new());
+
+// Returned by save_database_to when the app, CLI or server changed the file since
+// this process last read or wrote it; the caller should reload instead of overwriting
+pub const DATABASE_CHANGED: &str = "The database was changed by another program; please retry";
+
+// Modification time and size of a file, or None when it doesn't exist
+pub type FileStamp = Option<(SystemTime, u64)>;
+
+// Load the active database file (see config::database_file)
+pub fn load_database() -> Database {
+    let path = config::database_file();
+    let mut db = load_database_from(&path);
+
+    // Save an upgraded file right away so the new entry ids stay the same and no
+    // plaintext password is left in it
+    if upgrade(&mut db) > 0 {
+        if let Err(e) = save_database_to(&db, &path) {
+            eprintln!("Failed to save the upgraded database: {}", e);
+        }
+    }
+    db
+}
+
+pub fn save_database(db: &Database) -> std::io::Result<()> {
+    save_database_to(db, &config::database_file())
+}
+
+// Like load_database_from, but reports a missing or unreadable file instead of
+// quietly starting over. Used before switching to another database.
+pub fn read_database(path: &Path) -> Result<Database, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    record_stamp(path);
+    let mut db: Database = if crypto::is_encrypted(&data) {
+        parse(&data, path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?
+    } else {
+        serde_json::from_str(&data)
+            .map_err(|e| format!("{} is not a Diet Manager database: {}", path.display(), e))?
+    };
+    // The upgrade is saved with the next change
+    upgrade(&mut db);
+    Ok(db)
+}
+
+// Bring a file from an older version up to date: food log entries without an
+// id get one, plaintext passwords are hashed, and an account is made admin if
+// the file is from before roles. Returns how many changes were made.
+fn upgrade(db: &mut Database) -> usize {
+    db.assign_entry_ids() + db.hash_plain_passwords() + usize::from(db.ensure_admin())
+}
+
+// Start a new database file with the default food catalog
+pub fn create_database(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+
+    save_database_to(&Database::default(), path)
+        .map_err(|e| format!("Could not create {}: {}", path.display(), e))
+}
+
+pub fn load_database_from(path: &Path) -> Database {
+    let data = fs::read_to_string(path);
+    record_stamp(path);
+    if let Ok(data) = data {
+        parse(&data, path).unwrap_or_else(|_| Database::default())
+    } else {
+        Database::default()
+    }
+}
+
+pub fn save_database_to(db: &Database, path: &Path) -> std::io::Result<()> {
+    // A locked file would be replaced by whatever is in memory, most likely an
+    // empty database loaded in its place, so refuse instead
+    if is_locked(path) {
+        return Err(io::Error::other(format!("{} is encrypted and has not been unlocked", path.display())));
+    }
+    if changed_on_disk(path) {
+        return Err(io::Error::other(DATABASE_CHANGED));
+    }
+
+    // Try to load the existing database to preserve foods if file exists
+    let mut final_db = if let Ok(data) = fs::read_to_string(path) {
+        if let Ok(existing_db) = parse(&data, path) {
+            // If we have foods in the db parameter, use those
+            // Otherwise, keep the foods from the existing database
+            let basic_foods = if !db.basic_foods.is_empty() {
+                db.basic_foods.clone()
+            } else {
+                existing_db.basic_foods
+            };
+
+            let composite_foods = if !db.composite_foods.is_empty() {
+                db.composite_foods.clone()
+            } else {
+                existing_db.composite_foods
+            };
+
+            // Always update users, logs and current_user
+            Database {
+                basic_foods,
+                composite_foods,
+                ..db.clone()
+            }
+        } else {
+            db.clone()
+        }
+    } else {
+        db.clone()
+    };
+
+    // If we still have empty food maps, use the default hardcoded values
+    if final_db.basic_foods.is_empty() {
+        final_db.basic_foods = Database::default().basic_foods;
+    }
+
+    if final_db.composite_foods.is_empty() {
+        final_db.composite_foods = Database::default().composite_foods;
+    }
+
+    write_database(&final_db, path, unlocked_key(path).as_ref())
+}
+
+pub fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// Whether another program wrote the file since this process last read or wrote
+// it. Files this process hasn't touched yet count as unchanged.
+pub fn changed_on_disk(path: &Path) -> bool {
+    let synced = SYNCED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    synced
+        .iter()
+        .find(|(synced_path, _)| synced_path == path)
+        .is_some_and(|(_, stamp)| *stamp != file_stamp(path))
+}
+
+fn record_stamp(path: &Path) {
+    let mut synced = SYNCED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    synced.retain(|(synced_path, _)| synced_path != path);
+    synced.push((path.to_path_buf(), file_stamp(path)));
+}
+
+// Whether the file holds an encrypted database
+pub fn is_encrypted(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|data| crypto::is_encrypted(&data))
+}
+
+// Whether the file is encrypted and still needs its passphrase (see unlock_database)
+pub fn is_locked(path: &Path) -> bool {
+    unlocked_key(path).is_none() && is_encrypted(path)
+}
+
+// Check the passphrase and keep the key so the file can be loaded and saved
+pub fn unlock_database(path: &Path, passphrase: &str) -> Result<(), String> {
+    let (_, key) = open_encrypted(path, passphrase)?;
+    set_unlocked_key(path, Some(key));
+    Ok(())
+}
+
+// Encrypt a plain database file in place
+pub fn encrypt_database(path: &Path, passphrase: &str) -> Result<(), String> {
+    if is_encrypted(path) {
+        return Err(format!("{} is already encrypted", path.display()));
+    }
+    let db = read_database(path)?;
+    let key = DatabaseKey::new(passphrase)?;
+
+    write_database(&db, path, Some(&key)).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    set_unlocked_key(path, Some(key));
+    Ok(())
+}
+
+// Re-encrypt the file under a new passphrase (and a new salt)
+pub fn change_passphrase(path: &Path, current: &str, new: &str) -> Result<(), String> {
+    let (db, _) = open_encrypted(path, current)?;
+    let key = DatabaseKey::new(new)?;
+
+    write_database(&db, path, Some(&key)).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    set_unlocked_key(path, Some(key));
+    Ok(())
+}
+
+// Turn encryption off again, leaving plain JSON on disk
+pub fn decrypt_database(path: &Path, passphrase: &str) -> Result<(), String> {
+    let (db, _) = open_encrypted(path, passphrase)?;
+
+    write_database(&db, path, None).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    set_unlocked_key(path, None);
+    Ok(())
+}
+
+fn open_encrypted(path: &Path, passphrase: &str) -> Result<(Database, DatabaseKey), String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    if !crypto::is_encrypted(&data) {
+        return Err(format!("{} is not encrypted", path.display()));
+    }
+
+    let (plaintext, key) = crypto::decrypt(&data, passphrase)?;
+    let db = serde_json::from_slice(&plaintext)
+        .map_err(|e| format!("{} is not a Diet Manager database: {}", path.display(), e))?;
+    Ok((db, key))
+}
+
+// Parse file contents, decrypting them first if the file is encrypted
+fn parse(data: &str, path: &Path) -> Result<Database, String> {
+    if !crypto::is_encrypted(data) {
+        return serde_json::from_str(data).map_err(|e| e.to_string());
+    }
+
+    let key = unlocked_key(path).ok_or("The database is encrypted; unlock it with its passphrase first")?;
+    let plaintext = crypto::decrypt_with(data, &key)?;
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+fn write_database(db: &Database, path: &Path, key: Option<&DatabaseKey>) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let data = serde_json::to_string_pretty(db).unwrap();
+    let data = match key {
+        Some(key) => crypto::encrypt(data.as_bytes(), key).map_err(io::Error::other)?,
+        None => data,
+    };
+    // Write a temporary file next to it and rename it into place, so a crash or a
+    // full disk leaves the old file intact rather than a half-written one
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    let written = fs::File::create(&temp_path)
+        .and_then(|mut file| file.write_all(data.as_bytes()).and_then(|()| file.sync_all()))
+        .and_then(|()| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written?;
+    record_stamp(path);
+    Ok(())
+}
+
+fn unlocked_key(path: &Path) -> Option<DatabaseKey> {
+    UNLOCKED
+        .read()
+        .ok()
+        .and_then(|unlocked| unlocked.iter().find(|(unlocked_path, _)| unlocked_path == path).map(|(_, key)| key.clone()))
+}
+
+fn set_unlocked_key(path: &Path, key: Option<DatabaseKey>) {
+    if let Ok(mut unlocked) = UNLOCKED.write() {
+        unlocked.retain(|(unlocked_path, _)| unlocked_path != path);
+        if let Some(key) = key {
+            unlocked.push((path.to_path_buf(), key));
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FoodLogEntry, Meal, Role};
+
+    fn as_json(db: &Database) -> serde_json::Value {
+        serde_json::to_value(db).unwrap()
+    }
+
+    #[test]
+    fn missing_file_loads_default_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = load_database_from(&dir.path().join("database.json"));
+
+        assert_eq!(as_json(&db), as_json(&Database::default()));
+    }
+
+    #[test]
+    fn corrupt_file_loads_default_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        assert_eq!(as_json(&load_database_from(&path)), as_json(&Database::default()));
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+
+        let mut db = Database {
+            current_user: "u1".to_string(),
+            ..Database::default()
+        };
+        db.food_logs.insert("u1".to_string(), vec![FoodLogEntry::new("u1", "2025-01-01", "apple", 1.5, Meal::Lunch, false)]);
+        db.ensure_default_trackers("u1");
+
+        save_database_to(&db, &path).unwrap();
+        let loaded = load_database_from(&path);
+
+        assert_eq!(as_json(&loaded), as_json(&db));
+    }
+
+    #[test]
+    fn save_keeps_existing_foods_when_none_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+
+        let mut original = Database::default();
+        original.basic_foods.retain(|id, _| id == "apple");
+        save_database_to(&original, &path).unwrap();
+
+        let mut without_foods = original.clone();
+        without_foods.basic_foods.clear();
+        save_database_to(&without_foods, &path).unwrap();
+
+        let loaded = load_database_from(&path);
+        assert_eq!(loaded.basic_foods.keys().collect::<Vec<_>>(), vec!["apple"]);
+    }
+
+    #[test]
+    fn read_database_reports_missing_and_corrupt_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.json");
+        assert!(read_database(&path).is_err());
+
+        fs::write(&path, "[]").unwrap();
+        assert!(read_database(&path).is_err());
+    }
+
+    #[test]
+    fn create_database_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("family").join("mum.json");
+
+        create_database(&path).unwrap();
+        assert!(read_database(&path).is_ok());
+        assert!(create_database(&path).is_err());
+    }
+
+    #[test]
+    fn old_files_without_new_fields_still_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        fs::write(&path, r#"{
+            "users": {},
+            "basic_foods": {},
+            "composite_foods": {},
+            "food_logs": {},
+            "current_user": ""
+        }"#).unwrap();
+
+        let db = load_database_from(&path);
+        assert!(!db.exercises.is_empty());
+        assert!(db.trackers.is_empty());
+    }
+
+    #[test]
+    fn saving_replaces_the_file_without_leaving_temporary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        save_database_to(&Database::default(), &path).unwrap();
+        let mut db = load_database_from(&path);
+        db.current_user = "ann".to_string();
+        save_database_to(&db, &path).unwrap();
+
+        assert_eq!(load_database_from(&path).current_user, "ann");
+        let files: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(files, vec![std::ffi::OsString::from("database.json")]);
+    }
+
+    #[test]
+    fn changes_saved_by_another_program_are_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        save_database_to(&Database::default(), &path).unwrap();
+        let mut db = load_database_from(&path);
+
+        // Another program saves a different database in the meantime
+        let other = Database { current_user: "someone else".to_string(), ..Database::default() };
+        fs::write(&path, serde_json::to_string(&other).unwrap()).unwrap();
+        assert!(changed_on_disk(&path));
+        assert_eq!(save_database_to(&db, &path).unwrap_err().to_string(), DATABASE_CHANGED);
+
+        db = read_database(&path).unwrap();
+        assert!(!changed_on_disk(&path));
+        assert_eq!(db.current_user, "someone else");
+        save_database_to(&db, &path).unwrap();
+    }
+
+    #[test]
+    fn plaintext_passwords_from_old_files_are_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        fs::write(&path, r#"{
+            "users": {
+                "paarth": {
+                    "user_id": "u1",
+                    "username": "paarth",
+                    "password": "12345",
+                    "profile": {"gender": "Male", "height_cm": 169.7, "age": 20, "calorie_method": "HarrisBenedict", "weight_kg": 70.0, "activity_level": "Moderate"}
+                }
+            },
+            "basic_foods": {},
+            "composite_foods": {},
+            "food_logs": {},
+            "current_user": ""
+        }"#).unwrap();
+
+        let mut db = read_database(&path).unwrap();
+        let stored = &db.users["paarth"].password;
+        assert!(crypto::is_password_hash(stored));
+        assert!(crypto::verify_password("12345", stored));
+        assert_eq!(db.hash_plain_passwords(), 0);
+    }
+
+    #[test]
+    fn only_one_account_from_before_roles_becomes_admin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        let profile = r#"{"gender": "Male", "height_cm": 169.7, "age": 20, "calorie_method": "HarrisBenedict", "weight_kg": 70.0, "activity_level": "Moderate"}"#;
+        let legacy = |current_user: &str| format!(r#"{{
+            "users": {{
+                "alice": {{"user_id": "u1", "username": "alice", "password": "a", "profile": {profile}}},
+                "bob": {{"user_id": "u2", "username": "bob", "password": "b", "profile": {profile}}}
+            }},
+            "basic_foods": {{}},
+            "composite_foods": {{}},
+            "food_logs": {{}},
+            "current_user": "{current_user}"
+        }}"#);
+
+        // The account signed in last is the one that ran this copy of the app
+        fs::write(&path, legacy("u2")).unwrap();
+        let db = read_database(&path).unwrap();
+        assert_eq!(db.users["bob"].role, Role::Admin);
+        assert_eq!(db.users["alice"].role, Role::Member);
+
+        fs::write(&path, legacy("")).unwrap();
+        let mut db = read_database(&path).unwrap();
+        assert_eq!(db.users["alice"].role, Role::Admin);
+        assert_eq!(db.users["bob"].role, Role::Member);
+        assert!(!db.ensure_admin());
+    }
+
+    #[test]
+    fn encrypted_database_round_trips_once_unlocked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        let db = Database { current_user: "u1".to_string(), ..Database::default() };
+        save_database_to(&db, &path).unwrap();
+
+        encrypt_database(&path, "correct horse").unwrap();
+        assert!(is_encrypted(&path));
+        assert!(!fs::read_to_string(&path).unwrap().contains("\"u1\""));
+
+        // Saving keeps the file encrypted
+        save_database_to(&db, &path).unwrap();
+        assert!(is_encrypted(&path));
+        assert_eq!(load_database_from(&path).current_user, "u1");
+        assert!(encrypt_database(&path, "correct horse").is_err());
+    }
+
+    #[test]
+    fn locked_database_is_never_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        save_database_to(&Database::default(), &path).unwrap();
+        encrypt_database(&path, "correct horse").unwrap();
+        let sealed = fs::read_to_string(&path).unwrap();
+
+        // What a fresh process sees before the passphrase is entered
+        set_unlocked_key(&path, None);
+        assert!(is_locked(&path));
+        assert!(read_database(&path).is_err());
+        assert!(save_database_to(&Database::default(), &path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), sealed);
+
+        assert!(unlock_database(&path, "wrong horse").is_err());
+        unlock_database(&path, "correct horse").unwrap();
+        assert!(!is_locked(&path));
+        assert!(read_database(&path).is_ok());
+    }
+
+    #[test]
+    fn passphrase_can_be_changed_and_encryption_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        save_database_to(&Database::default(), &path).unwrap();
+        encrypt_database(&path, "correct horse").unwrap();
+
+        assert!(change_passphrase(&path, "wrong horse", "battery staple").is_err());
+        assert!(change_passphrase(&path, "correct horse", "short").is_err());
+        change_passphrase(&path, "correct horse", "battery staple").unwrap();
+
+        set_unlocked_key(&path, None);
+        assert!(unlock_database(&path, "correct horse").is_err());
+        decrypt_database(&path, "battery staple").unwrap();
+        assert!(!is_encrypted(&path));
+        assert!(read_database(&path).is_ok());
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::models::{Database, BasicFood, CompositeFood};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+// A food log entry with the food resolved, so the export is readable on its own
+#[derive(Serialize, Debug, Clone)]
+pub struct ExportedLogEntry {
+    pub date: String,
+    pub food_id: String,
+    pub food_name: String,
+    pub servings: f32,
+    pub calories_per_serving: f32,
+    pub total_calories: f32,
+}
+
+// Catalog file layout, shared with the importer so exports can be re-imported
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FoodCatalog {
+    #[serde(default)]
+    pub basic_foods: Vec<BasicFood>,
+    #[serde(default)]
+    pub composite_foods: Vec<CompositeFood>,
+}
+
+pub const CATALOG_CSV_HEADER: &str = "id,name,keywords,calories";
+
+// Entries for a user between two ISO dates (inclusive), oldest first
+pub fn collect_food_log(db: &Database, user_id: &str, from: &str, to: &str) -> Vec<ExportedLogEntry> {
+    let mut entries: Vec<ExportedLogEntry> = db.food_logs
+        .get(user_id)
+        .map_or(Vec::new(), |entries| {
+            entries
+                .iter()
+                .filter(|entry| !entry.planned && entry.date.as_str() >= from && entry.date.as_str() <= to)
+                .map(|entry| {
+                    let calories_per_serving = db.get_food_calories(&entry.food_id).unwrap_or(0.0);
+                    ExportedLogEntry {
+                        date: entry.date.clone(),
+                        food_id: entry.food_id.clone(),
+                        food_name: db.food_name(&entry.food_id),
+                        servings: entry.servings,
+                        calories_per_serving,
+                        total_calories: calories_per_serving * entry.servings,
+                    }
+                })
+                .collect()
+        });
+
+    entries.sort_by(|a, b| a.date.cmp(&b.date));
+    entries
+}
+
+pub fn food_log_to_csv(entries: &[ExportedLogEntry]) -> String {
+    let mut csv = String::from("date,food_id,food_name,servings,calories_per_serving,total_calories\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&entry.date),
+            csv_field(&entry.food_id),
+            csv_field(&entry.food_name),
+            entry.servings,
+            entry.calories_per_serving,
+            entry.total_calories
+        ));
+    }
+    csv
+}
+
+pub fn food_log_to_json(entries: &[ExportedLogEntry]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(entries)
+}
+
+// Only the shared catalog; private foods stay with their owners
+pub fn catalog_from_db(db: &Database) -> FoodCatalog {
+    let mut basic_foods: Vec<BasicFood> = db.basic_foods.values().filter(|food| food.owner.is_none()).cloned().collect();
+    basic_foods.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut composite_foods: Vec<CompositeFood> = db.composite_foods.values().filter(|food| food.owner.is_none()).cloned().collect();
+    composite_foods.sort_by(|a, b| a.id.cmp(&b.id));
+
+    FoodCatalog { basic_foods, composite_foods }
+}
+
+// Only basic foods fit the flat CSV layout; composite foods need JSON
+pub fn catalog_to_csv(catalog: &FoodCatalog) -> String {
+    let mut csv = format!("{}\n", CATALOG_CSV_HEADER);
+    for food in &catalog.basic_foods {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&food.id),
+            csv_field(&food.name),
+            csv_field(&food.keywords.join(";")),
+            food.calories_per_serving
+        ));
+    }
+    csv
+}
+
+pub fn catalog_to_json(catalog: &FoodCatalog) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(catalog)
+}
+
+// Returns the number of entries written
+pub fn export_food_log(
+    db: &Database,
+    user_id: &str,
+    from: &str,
+    to: &str,
+    format: ExportFormat,
+    path: &Path
+) -> io::Result<usize> {
+    let entries = collect_food_log(db, user_id, from, to);
+    let data = match format {
+        ExportFormat::Csv => food_log_to_csv(&entries),
+        ExportFormat::Json => food_log_to_json(&entries)?,
+    };
+    fs::write(path, data)?;
+    Ok(entries.len())
+}
+
+// Returns the number of foods written
+pub fn export_catalog(db: &Database, format: ExportFormat, path: &Path) -> io::Result<usize> {
+    let catalog = catalog_from_db(db);
+    let (data, count) = match format {
+        ExportFormat::Csv => (catalog_to_csv(&catalog), catalog.basic_foods.len()),
+        ExportFormat::Json => (
+            catalog_to_json(&catalog)?,
+            catalog.basic_foods.len() + catalog.composite_foods.len(),
+        ),
+    };
+    fs::write(path, data)?;
+    Ok(count)
+}
+
+// Quote a CSV field when it contains a separator, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::models::{Database, BasicFood, CompositeFood, plain_food_id, private_food_key, validate_food_id};
+use crate::export::FoodCatalog;
+
+// What to do when an imported food's id is already in the catalog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictStrategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportedFood {
+    Basic(BasicFood),
+    Composite(CompositeFood),
+}
+
+impl ImportedFood {
+    pub fn id(&self) -> &str {
+        match self {
+            ImportedFood::Basic(food) => &food.id,
+            ImportedFood::Composite(food) => &food.id,
+        }
+    }
+
+    fn set_key(&mut self, id: String, owner: Option<&str>) {
+        let owner = owner.map(str::to_string);
+        match self {
+            ImportedFood::Basic(food) => {
+                food.id = id;
+                food.owner = owner;
+            }
+            ImportedFood::Composite(food) => {
+                food.id = id;
+                food.owner = owner;
+            }
+        }
+    }
+}
+
+// Result of reading a file: the rows that passed validation and a message per rejected row
+#[derive(Debug, Clone, Default)]
+pub struct ImportPreview {
+    pub foods: Vec<ImportedFood>,
+    pub errors: Vec<String>,
+}
+
+impl ImportPreview {
+    // Ids of valid rows that clash with a food already in the catalog, for an
+    // import into the shared catalog (no owner) or into one user's private foods
+    pub fn conflicts(&self, db: &Database, owner: Option<&str>) -> Vec<String> {
+        self.foods
+            .iter()
+            .map(|food| food.id())
+            .filter(|id| existing_key(db, owner, id).is_some())
+            .map(|id| id.to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub overwritten: usize,
+    pub renamed: Vec<(String, String)>, // (original id, new id)
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>, // Rows refused while merging, e.g. recipes that contain themselves
+}
+
+impl ImportReport {
+    pub fn changed(&self) -> bool {
+        self.added > 0 || self.overwritten > 0 || !self.renamed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Added {}, overwrote {}", self.added, self.overwritten);
+        if !self.renamed.is_empty() {
+            summary.push_str(&format!(", renamed {}", self.renamed.len()));
+        }
+        if !self.skipped.is_empty() {
+            summary.push_str(&format!(", skipped {}", self.skipped.len()));
+        }
+        if !self.errors.is_empty() {
+            summary.push_str(&format!(", rejected {}", self.errors.len()));
+        }
+        summary
+    }
+}
+
+// Read a catalog file, choosing the parser from the extension
+pub fn read_file(path: &Path, db: &Database) -> io::Result<ImportPreview> {
+    let data = fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    Ok(if is_json { parse_json(&data, db) } else { parse_csv(&data) })
+}
+
+// CSV rows are: id, name, keywords, calories. Keywords may be separated by ';' or ','.
+pub fn parse_csv(data: &str) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+    let mut seen = HashSet::new();
+
+    for (line, fields) in parse_csv_records(data) {
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        if line == 1 && fields.first().is_some_and(|f| f.trim().eq_ignore_ascii_case("id")) {
+            continue;
+        }
+
+        if fields.len() != 4 {
+            preview.errors.push(format!("Line {}: expected 4 columns (id, name, keywords, calories), found {}", line, fields.len()));
+            continue;
+        }
+
+        let keywords = fields[2].replace(';', ",");
+        match BasicFood::from_input(&fields[0], &fields[1], &keywords, &fields[3]) {
+            Ok(food) if !seen.insert(food.id.clone()) => {
+                preview.errors.push(format!("Line {}: duplicate identifier '{}' in file", line, food.id));
+            }
+            Ok(food) => preview.foods.push(ImportedFood::Basic(food)),
+            Err(error) => preview.errors.push(format!("Line {}: {}", line, error)),
+        }
+    }
+
+    preview
+}
+
+// JSON uses the same layout as a catalog export
+pub fn parse_json(data: &str, db: &Database) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+
+    let catalog: FoodCatalog = match serde_json::from_str(data) {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            preview.errors.push(format!("Invalid JSON: {}", e));
+            return preview;
+        }
+    };
+
+    let mut seen = HashSet::new();
+
+    for food in catalog.basic_foods {
+        let keywords = food.keywords.join(",");
+        let calories = food.calories_per_serving.to_string();
+        match BasicFood::from_input(&food.id, &food.name, &keywords, &calories) {
+            Ok(food) if !seen.insert(food.id.clone()) => {
+                preview.errors.push(format!("Basic food '{}': duplicate identifier in file", food.id));
+            }
+            Ok(food) => preview.foods.push(ImportedFood::Basic(food)),
+            Err(error) => preview.errors.push(format!("Basic food '{}': {}", food.id, error)),
+        }
+    }
+
+    // Components may refer to foods already in the catalog or anywhere in this file
+    let mut known: HashSet<String> = seen.clone();
+    known.extend(catalog.composite_foods.iter().map(|f| f.id.trim().to_string()));
+
+    for food in catalog.composite_foods {
+        match validate_composite(food, &known, db) {
+            Ok(food) if !seen.insert(food.id.clone()) => {
+                preview.errors.push(format!("Composite food '{}': duplicate identifier in file", food.id));
+            }
+            Ok(food) => preview.foods.push(ImportedFood::Composite(food)),
+            Err((id, error)) => preview.errors.push(format!("Composite food '{}': {}", id, error)),
+        }
+    }
+
+    preview
+}
+
+// Same rules the composite food form applies
+fn validate_composite(
+    mut food: CompositeFood,
+    known_in_file: &HashSet<String>,
+    db: &Database
+) -> Result<CompositeFood, (String, String)> {
+    food.id = food.id.trim().to_string();
+    food.owner = None;
+    let id = food.id.clone();
+
+    validate_food_id(&food.id).map_err(|error| (id.clone(), error))?;
+    if food.name.trim().is_empty() {
+        return Err((id, "Food Name cannot be empty".to_string()));
+    }
+
+    food.keywords = food.keywords
+        .iter()
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if food.keywords.is_empty() {
+        return Err((id, "Please provide at least one valid keyword".to_string()));
+    }
+
+    if food.components.is_empty() {
+        return Err((id, "At least one component is required".to_string()));
+    }
+    for component in &food.components {
+        if component.food_id == food.id {
+            return Err((id, "A food cannot contain itself".to_string()));
+        }
+        if !(component.servings > 0.0 && component.servings.is_finite()) {
+            return Err((id, format!("Component '{}' needs a positive number of servings", component.food_id)));
+        }
+        // Whose private foods an import may use isn't known yet, so only shared ones count
+        if !known_in_file.contains(&component.food_id) && !db.food_visible_to(&component.food_id, "") {
+            return Err((id, format!("Unknown component food '{}'", component.food_id)));
+        }
+    }
+
+    Ok(food)
+}
+
+// Merge imported foods into the catalog: the shared one without an owner,
+// otherwise as private foods of that user. Frontends go through
+// service::catalog::import_foods so the whole batch is one undoable action.
+pub fn apply_import(db: &mut Database, foods: &[ImportedFood], strategy: ConflictStrategy, owner: Option<&str>) -> ImportReport {
+    let mut report = ImportReport::default();
+    let mut renames: HashMap<String, String> = HashMap::new();
+    let mut keys: HashMap<String, String> = HashMap::new(); // Row id in the file -> key it is stored under
+    let incoming: HashSet<&str> = foods.iter().map(|f| f.id()).collect();
+
+    // Settle every row's final id first; nothing changes until cycles are ruled out
+    let mut planned: Vec<PlannedFood> = Vec::new();
+    for food in foods {
+        let mut food = food.clone();
+        let original_id = food.id().to_string();
+        let mut id = original_id.clone();
+        let mut replaces = false;
+
+        if existing_key(db, owner, &original_id).is_some() {
+            match strategy {
+                ConflictStrategy::Skip => {
+                    report.skipped.push(original_id);
+                    continue;
+                }
+                ConflictStrategy::Overwrite => replaces = true,
+                ConflictStrategy::Rename => {
+                    id = unique_id(db, owner, &original_id, &incoming, &renames);
+                    renames.insert(original_id.clone(), id.clone());
+                }
+            }
+        }
+
+        let key = match owner {
+            Some(owner) => private_food_key(owner, &id),
+            None => id,
+        };
+        food.set_key(key.clone(), owner);
+        keys.insert(original_id.clone(), key);
+        planned.push(PlannedFood { food, original_id, replaces });
+    }
+
+    // Point components at the keys of foods from the same batch, or else at
+    // the food the importing user would get for that id
+    for row in &mut planned {
+        if let ImportedFood::Composite(food) = &mut row.food {
+            for component in &mut food.components {
+                let key = keys.get(&component.food_id).cloned().or_else(|| existing_key(db, owner, &component.food_id));
+                if let Some(key) = key {
+                    component.food_id = key;
+                }
+            }
+        }
+    }
+
+    let rejected = reject_cycles(db, &planned);
+
+    for row in planned {
+        let id = row.food.id().to_string();
+        if let Some(error) = rejected.get(&id) {
+            report.errors.push(error.clone());
+            continue;
+        }
+
+        if row.replaces {
+            db.basic_foods.remove(&id);
+            db.composite_foods.remove(&id);
+            report.overwritten += 1;
+        } else {
+            report.added += 1;
+        }
+        if plain_food_id(&id) != row.original_id {
+            report.renamed.push((row.original_id, plain_food_id(&id).to_string()));
+        }
+
+        match row.food {
+            ImportedFood::Basic(food) => {
+                db.basic_foods.insert(food.id.clone(), food);
+            }
+            ImportedFood::Composite(food) => {
+                db.composite_foods.insert(food.id.clone(), food);
+            }
+        }
+    }
+
+    report
+}
+
+// An imported row with the id it will be stored under
+struct PlannedFood {
+    food: ImportedFood,
+    original_id: String,
+    replaces: bool, // Overwrites a food already in the catalog
+}
+
+// Composite rows whose components lead back to themselves, through this batch or
+// the catalog as it would be after the import, plus rows that use a rejected row.
+// Such a recipe would have no calorie total. Returns an error message per row id.
+fn reject_cycles(db: &Database, planned: &[PlannedFood]) -> HashMap<String, String> {
+    let rows: HashMap<&str, &ImportedFood> = planned.iter().map(|row| (row.food.id(), &row.food)).collect();
+    let components = |id: &str| -> Vec<String> {
+        let components = match rows.get(id) {
+            Some(ImportedFood::Composite(food)) => &food.components,
+            Some(ImportedFood::Basic(_)) => return Vec::new(),
+            None => match db.composite_foods.get(id) {
+                Some(food) => &food.components,
+                None => return Vec::new(),
+            },
+        };
+        components.iter().map(|component| component.food_id.clone()).collect()
+    };
+
+    let mut rejected = HashMap::new();
+    for row in planned {
+        let id = row.food.id();
+        if reaches(id, &components) {
+            rejected.insert(id.to_string(), format!("Composite food '{}': its components lead back to itself", id));
+        }
+    }
+
+    // Recipes built on a rejected row would refer to a food that never arrives
+    loop {
+        let missing: Vec<(String, String)> = planned
+            .iter()
+            .filter(|row| !rejected.contains_key(row.food.id()))
+            .filter_map(|row| match &row.food {
+                ImportedFood::Composite(food) => food.components
+                    .iter()
+                    .find(|c| rejected.contains_key(&c.food_id) && !db.food_exists(&c.food_id))
+                    .map(|c| (food.id.clone(), format!("Composite food '{}': uses rejected food '{}'", food.id, c.food_id))),
+                ImportedFood::Basic(_) => None,
+            })
+            .collect();
+        if missing.is_empty() {
+            return rejected;
+        }
+        rejected.extend(missing);
+    }
+}
+
+// Whether following components from a food leads back to it
+fn reaches(start: &str, components: &dyn Fn(&str) -> Vec<String>) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = components(start);
+    while let Some(id) = pending.pop() {
+        if id == start {
+            return true;
+        }
+        if visited.insert(id.clone()) {
+            pending.extend(components(&id));
+        }
+    }
+    false
+}
+
+// The key of the food an imported id clashes with: the shared food with that id,
+// or for a private import also the owner's own food
+fn existing_key(db: &Database, owner: Option<&str>, id: &str) -> Option<String> {
+    match owner {
+        Some(owner) => db.resolve_food_id(owner, id),
+        None => db.food_exists(id).then(|| id.to_string()),
+    }
+}
+
+fn unique_id(db: &Database, owner: Option<&str>, id: &str, incoming: &HashSet<&str>, renames: &HashMap<String, String>) -> String {
+    let taken = |candidate: &str| {
+        existing_key(db, owner, candidate).is_some()
+            || incoming.contains(candidate)
+            || renames.values().any(|new_id| new_id == candidate)
+    };
+
+    (2..)
+        .map(|n| format!("{}_{}", id, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| id.to_string())
+}
+
+// Split CSV text into records, honouring quoted fields (which may span lines).
+// Each record carries the line number it starts on.
+fn parse_csv_records(data: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composite_row(id: &str, components: &[&str]) -> String {
+        let components: Vec<String> = components
+            .iter()
+            .map(|food_id| format!(r#"{{"food_id": "{}", "servings": 1.0}}"#, food_id))
+            .collect();
+        format!(r#"{{"id": "{}", "name": "{}", "keywords": ["test"], "components": [{}]}}"#, id, id, components.join(", "))
+    }
+
+    fn catalog_json(composites: &[String]) -> String {
+        format!(r#"{{"basic_foods": [], "composite_foods": [{}]}}"#, composites.join(", "))
+    }
+
+    #[test]
+    fn recipes_that_contain_each_other_are_rejected() {
+        let mut db = Database::default();
+        let data = catalog_json(&[composite_row("a", &["b"]), composite_row("b", &["a"]), composite_row("c", &["a", "apple"])]);
+        let preview = parse_json(&data, &db);
+        assert!(preview.errors.is_empty());
+
+        let report = apply_import(&mut db, &preview.foods, ConflictStrategy::Skip, None);
+        assert_eq!(report.added, 0);
+        assert_eq!(report.errors.len(), 3);
+        assert!(report.errors[0].contains("lead back to itself"));
+        assert!(report.errors[2].contains("uses rejected food 'a'"));
+        assert!(!db.food_exists("a") && !db.food_exists("b") && !db.food_exists("c"));
+    }
+
+    #[test]
+    fn overwriting_cannot_close_a_cycle_through_the_catalog() {
+        let mut db = Database::default();
+        // The catalog's sandwich contains bread; a recipe called bread that contains sandwich would loop
+        let preview = parse_json(&catalog_json(&[composite_row("bread", &["sandwich"])]), &db);
+
+        let report = apply_import(&mut db, &preview.foods, ConflictStrategy::Overwrite, None);
+        assert_eq!((report.overwritten, report.errors.len()), (0, 1));
+        assert!(db.basic_foods.contains_key("bread"));
+        assert!(db.get_food_calories("sandwich").is_some());
+
+        // Renaming keeps both, so there is no cycle
+        let report = apply_import(&mut db, &preview.foods, ConflictStrategy::Rename, None);
+        assert_eq!(report.renamed, vec![("bread".to_string(), "bread_2".to_string())]);
+        assert!(report.errors.is_empty());
+    }
+}
@@ -0,0 +1,12 @@
+// Models, persistence and business rules shared by the GUI, the CLI and the API server
+pub mod config;
+pub mod models;
+pub mod database;
+pub mod export;
+pub mod import;
+pub mod undo;
+pub mod search;
+pub mod totp;
+pub mod crypto;
+pub mod units;
+pub mod service;
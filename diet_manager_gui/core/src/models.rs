@@ -0,0 +1,1123 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDate};
+use uuid::Uuid;
+use crate::crypto;
+use crate::units::Units;
+
+// Roughly 7700 kcal of surplus or deficit per kilogram of body weight
+pub const KCAL_PER_KG: f32 = 7700.0;
+// Fastest weekly change we will plan for, in either direction
+pub const MAX_WEEKLY_RATE_KG: f32 = 1.0;
+// Never suggest eating below these, whatever the goal asks for
+pub const MIN_CALORIES_MALE: f32 = 1500.0;
+pub const MIN_CALORIES_FEMALE: f32 = 1200.0;
+// Number of days of logged intake used for the goal-date projection
+pub const PROJECTION_WINDOW_DAYS: i64 = 14;
+// Every user gets a water tracker; it can be reconfigured but not deleted
+pub const WATER_TRACKER_ID: &str = "water";
+// Oldest audit entries are dropped beyond this many
+pub const MAX_AUDIT_ENTRIES: usize = 1000;
+// Recipes nest recipes; stop expanding past this depth in case of a cycle
+pub const MAX_RECIPE_DEPTH: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BasicFood {
+    pub id: String,
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub calories_per_serving: f32,
+    #[serde(default)]
+    pub owner: Option<String>, // user_id of a private food; None for the shared catalog
+}
+
+impl BasicFood {
+    // Build a basic food from raw form input, applying the catalog's validation rules.
+    // Checking for an existing food with the same id is left to the caller.
+    pub fn from_input(id: &str, name: &str, keywords: &str, calories: &str) -> Result<BasicFood, String> {
+        let id = id.trim();
+        validate_food_id(id)?;
+
+        if keywords.trim().is_empty() {
+            return Err("Keywords cannot be empty".to_string());
+        }
+
+        let calories_per_serving = match calories.trim().parse::<f32>() {
+            Ok(cal) if cal > 0.0 && cal.is_finite() => cal,
+            Ok(_) => return Err("Calories must be a positive number".to_string()),
+            Err(_) => return Err("Invalid calories value. Please enter a number".to_string()),
+        };
+
+        let keywords = parse_keywords(keywords);
+        if keywords.is_empty() {
+            return Err("Please provide at least one valid keyword".to_string());
+        }
+
+        let name = name.trim();
+        Ok(BasicFood {
+            id: id.to_string(),
+            name: if name.is_empty() { id.to_string() } else { name.to_string() },
+            keywords,
+            calories_per_serving,
+            owner: None,
+        })
+    }
+}
+
+// Food identifiers are typed by users, so '/' is kept for private food keys
+pub fn validate_food_id(id: &str) -> Result<(), String> {
+    if id.is_empty() {
+        return Err("Food Identifier cannot be empty".to_string());
+    }
+    if id.contains('/') {
+        return Err("Food Identifier cannot contain '/'".to_string());
+    }
+    Ok(())
+}
+
+// Private foods are stored under "<user_id>/<id>" so users can't collide with
+// each other or with the shared catalog
+pub fn private_food_key(user_id: &str, id: &str) -> String {
+    format!("{}/{}", user_id, id)
+}
+
+// The identifier a user typed for a food, without any private prefix
+pub fn plain_food_id(food_id: &str) -> &str {
+    food_id.rsplit('/').next().unwrap_or(food_id)
+}
+
+// Split a comma-separated keyword list, dropping blanks
+pub fn parse_keywords(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompositeFood {
+    pub id: String,
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub components: Vec<FoodComponent>,
+    #[serde(default)]
+    pub owner: Option<String>, // user_id of a private food; None for the shared catalog
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FoodComponent {
+    pub food_id: String,
+    pub servings: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FoodLogEntry {
+    #[serde(default)]
+    pub id: String, // UUID; entries saved before ids existed get one when the database is loaded
+    pub date: String, // ISO 8601 date format (e.g., "2023-10-01")
+    pub food_id: String,
+    pub servings: f32,
+    pub user_id: String, // Add user_id to associate with a user
+    #[serde(default)]
+    pub meal: Meal,
+    #[serde(default)]
+    pub planned: bool, // Planned but not eaten yet; left out of consumed totals
+}
+
+impl FoodLogEntry {
+    pub fn new(user_id: &str, date: &str, food_id: &str, servings: f32, meal: Meal, planned: bool) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            food_id: food_id.to_string(),
+            servings,
+            user_id: user_id.to_string(),
+            meal,
+            planned,
+        }
+    }
+}
+
+// Entries logged before meals existed count as snacks
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Meal {
+    Breakfast,
+    Lunch,
+    Dinner,
+    #[default]
+    Snack,
+}
+
+impl Meal {
+    pub const ALL: [Meal; 4] = [Meal::Breakfast, Meal::Lunch, Meal::Dinner, Meal::Snack];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Meal::Breakfast => "Breakfast",
+            Meal::Lunch => "Lunch",
+            Meal::Dinner => "Dinner",
+            Meal::Snack => "Snack",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Meal> {
+        Meal::ALL.into_iter().find(|meal| meal.label().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+// A named set of foods logged together, e.g. "Standard workday lunch"
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MealTemplate {
+    pub name: String,
+    pub items: Vec<FoodComponent>,
+}
+
+// Failed sign-ins since the last successful one
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FailedLogins {
+    pub count: u32,
+    pub locked_until: Option<String>, // RFC 3339 timestamp
+    #[serde(default)]
+    pub last_failed: Option<String>, // RFC 3339 timestamp; None in older files
+}
+
+// A security-relevant event, e.g. a failed login
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub timestamp: String, // "YYYY-MM-DD HH:MM:SS", local time
+    pub username: String,  // As entered, so it may not belong to an account
+    pub event: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exercise {
+    pub id: String,
+    pub name: String,
+    pub met: f32, // Metabolic equivalent of task
+}
+
+impl Exercise {
+    pub fn calories_burned(&self, weight_kg: f32, duration_minutes: f32) -> f32 {
+        self.met * weight_kg * (duration_minutes / 60.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExerciseLogEntry {
+    pub date: String, // ISO 8601 date format (e.g., "2023-10-01")
+    pub exercise_id: String,
+    pub duration_minutes: f32,
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HabitTracker {
+    pub id: String,
+    pub name: String,
+    pub unit: String,            // e.g., "ml", "servings", "steps"
+    pub daily_goal: Option<f32>,
+    pub increment: f32,          // Step used by the quick +/- buttons
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackerEntry {
+    pub date: String, // ISO 8601 date format (e.g., "2023-10-01")
+    pub tracker_id: String,
+    pub value: f32,   // Running total for the day
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Gender {
+    Male,
+    Female,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActivityLevel {
+    Sedentary,
+    Light,
+    Moderate,
+    VeryActive,
+    ExtraActive,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CalorieCalculationMethod {
+    HarrisBenedict,
+    MifflinStJeor,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GoalPace {
+    WeeklyRate(f32),    // kg per week, always positive
+    TargetDate(String), // ISO 8601 date by which to reach the target weight
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeightGoal {
+    pub target_weight_kg: f32,
+    pub pace: GoalPace,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserProfile {
+    pub gender: Gender,
+    pub height_cm: f32,
+    pub age: u32,
+    pub calorie_method: CalorieCalculationMethod,
+    pub weight_kg: f32,
+    pub activity_level: ActivityLevel,
+    #[serde(default)]
+    pub weight_goal: Option<WeightGoal>,
+    #[serde(default = "default_earn_back_exercise")]
+    pub earn_back_exercise: bool, // Add exercise calories back onto the daily allowance
+    #[serde(default)]
+    pub units: Units, // How heights and weights are entered and shown; stored values are metric
+}
+
+fn default_earn_back_exercise() -> bool {
+    true
+}
+
+// Breakdown of how the daily target was derived from maintenance
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalorieTarget {
+    pub maintenance: f32,
+    pub adjustment: f32, // Negative for a deficit, positive for a surplus
+    pub target: f32,
+    pub floor_applied: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoalProjection {
+    Reached,
+    OnTrack(NaiveDate),
+    OffTrack, // Recent intake is moving away from (or not towards) the goal
+    NoData,
+}
+
+impl UserProfile {
+    // Maintenance calories (TDEE) for the current weight and activity level
+    pub fn calculate_maintenance_calories(&self) -> f32 {
+        let bmr = match self.gender {
+            Gender::Male => 88.362 + (13.397 * self.weight_kg) + (4.799 * self.height_cm) - (5.677 * self.age as f32),
+            Gender::Female => 447.593 + (9.247 * self.weight_kg) + (3.098 * self.height_cm) - (4.330 * self.age as f32),
+        };
+        bmr * match self.activity_level {
+            ActivityLevel::Sedentary => 1.2,
+            ActivityLevel::Light => 1.375,
+            ActivityLevel::Moderate => 1.55,
+            ActivityLevel::VeryActive => 1.725,
+            ActivityLevel::ExtraActive => 1.9,
+        }
+    }
+
+    pub fn calculate_target_calories(&self) -> f32 {
+        self.calorie_target_on(chrono::Local::now().date_naive()).target
+    }
+
+    pub fn minimum_calories(&self) -> f32 {
+        match self.gender {
+            Gender::Male => MIN_CALORIES_MALE,
+            Gender::Female => MIN_CALORIES_FEMALE,
+        }
+    }
+
+    // Planned weight change per week in kg (negative when losing), capped at MAX_WEEKLY_RATE_KG
+    pub fn planned_weekly_change_kg(&self, today: NaiveDate) -> f32 {
+        let goal = match &self.weight_goal {
+            Some(goal) => goal,
+            None => return 0.0,
+        };
+
+        let remaining_kg = goal.target_weight_kg - self.weight_kg;
+        if remaining_kg.abs() < 0.05 {
+            return 0.0;
+        }
+
+        let rate = match &goal.pace {
+            GoalPace::WeeklyRate(rate) => rate.abs(),
+            GoalPace::TargetDate(date) => {
+                match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) if date > today => {
+                        let weeks_left = (date - today).num_days() as f32 / 7.0;
+                        remaining_kg.abs() / weeks_left
+                    }
+                    // A past or unparsable date means "as fast as is safe"
+                    _ => MAX_WEEKLY_RATE_KG,
+                }
+            }
+        };
+
+        rate.min(MAX_WEEKLY_RATE_KG).copysign(remaining_kg)
+    }
+
+    pub fn calorie_target_on(&self, today: NaiveDate) -> CalorieTarget {
+        let maintenance = self.calculate_maintenance_calories();
+        let adjustment = self.planned_weekly_change_kg(today) * KCAL_PER_KG / 7.0;
+
+        let minimum = self.minimum_calories();
+        let floor_applied = maintenance + adjustment < minimum;
+        let target = if floor_applied { minimum } else { maintenance + adjustment };
+
+        CalorieTarget {
+            maintenance,
+            adjustment,
+            target,
+            floor_applied,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+    pub user_id: String,
+    pub username: String,
+    pub password: String, // Argon2id hash (see crypto::hash_password)
+    pub profile: UserProfile,
+    #[serde(default)]
+    pub two_factor: Option<TwoFactor>, // None until the user turns it on
+    #[serde(default = "Role::legacy")]
+    pub role: Role,
+    #[serde(default)]
+    pub disabled: bool, // Set by an admin; the account can't log in
+}
+
+// What an account may do. Admins manage other accounts and the shared catalog;
+// read-only accounts can look at their logs but not change anything.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Admin,
+    Member,
+    ReadOnly,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Admin, Role::Member, Role::ReadOnly];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::Admin => "Admin",
+            Role::Member => "Member",
+            Role::ReadOnly => "Read-only",
+        }
+    }
+
+    // Accounts from before roles existed become members; Database::ensure_admin
+    // then makes one of them the admin
+    fn legacy() -> Role {
+        Role::Member
+    }
+}
+
+// Authenticator app (TOTP) settings for a user
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TwoFactor {
+    pub secret: String,              // Base32, as entered into the authenticator app
+    pub recovery_codes: Vec<String>, // Unused one-time codes
+    #[serde(default)]
+    pub last_used_step: u64,         // Codes from this time step or earlier are not accepted again
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Database {
+    pub users: HashMap<String, User>, // Key: username, Value: User
+    pub basic_foods: HashMap<String, BasicFood>,
+    pub composite_foods: HashMap<String, CompositeFood>,
+    pub food_logs: HashMap<String, Vec<FoodLogEntry>>, // Key: user_id, Value: logs
+    pub current_user: String, // Track the currently logged-in user
+    #[serde(default = "default_exercises")]
+    pub exercises: HashMap<String, Exercise>,
+    #[serde(default)]
+    pub exercise_logs: HashMap<String, Vec<ExerciseLogEntry>>, // Key: user_id, Value: exercise entries
+    #[serde(default)]
+    pub trackers: HashMap<String, Vec<HabitTracker>>, // Key: user_id, Value: tracker definitions
+    #[serde(default)]
+    pub tracker_logs: HashMap<String, Vec<TrackerEntry>>, // Key: user_id, Value: one entry per tracker per date
+    #[serde(default)]
+    pub meal_templates: HashMap<String, Vec<MealTemplate>>, // Key: user_id, Value: saved meals
+    #[serde(default)]
+    pub shopping_checked: HashMap<String, Vec<String>>, // Key: user_id, Value: basic food ids ticked off the shopping list
+    #[serde(default)]
+    pub failed_logins: HashMap<String, FailedLogins>, // Key: user_id, or "unknown:<username>" for names without an account
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>, // Oldest first
+}
+
+impl Database {
+    // Give every food log entry without an id a fresh one. Returns how many were assigned.
+    pub fn assign_entry_ids(&mut self) -> usize {
+        let mut assigned = 0;
+        for entry in self.food_logs.values_mut().flatten().filter(|entry| entry.id.is_empty()) {
+            entry.id = Uuid::new_v4().to_string();
+            assigned += 1;
+        }
+        assigned
+    }
+
+    // Hash the passwords of accounts saved before passwords were hashed.
+    // Returns how many were hashed.
+    pub fn hash_plain_passwords(&mut self) -> usize {
+        let mut hashed = 0;
+        for user in self.users.values_mut().filter(|user| !crypto::is_password_hash(&user.password)) {
+            match crypto::hash_password(&user.password) {
+                Ok(hash) => {
+                    user.password = hash;
+                    hashed += 1;
+                }
+                Err(e) => eprintln!("Failed to hash the password of {}: {}", user.username, e),
+            }
+        }
+        hashed
+    }
+
+    // A database with accounts always has an admin, since the last one can't be
+    // demoted or deleted. Files from before roles existed have none, so one
+    // account is promoted: the one signed in last, or else the first by username.
+    // Returns whether an account was promoted.
+    pub fn ensure_admin(&mut self) -> bool {
+        if self.users.is_empty() || self.users.values().any(|user| user.role == Role::Admin) {
+            return false;
+        }
+        let username = self.users
+            .values()
+            .find(|user| user.user_id == self.current_user)
+            .map(|user| user.username.clone())
+            .or_else(|| self.users.keys().min().cloned());
+        match username.and_then(|username| self.users.get_mut(&username)) {
+            Some(user) => {
+                user.role = Role::Admin;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Drop everything stored for a user: the account and all per-user logs and settings
+    pub fn remove_user_data(&mut self, user_id: &str) {
+        self.users.retain(|_, user| user.user_id != user_id);
+        self.food_logs.remove(user_id);
+        self.exercise_logs.remove(user_id);
+        self.trackers.remove(user_id);
+        self.tracker_logs.remove(user_id);
+        self.meal_templates.remove(user_id);
+        self.shopping_checked.remove(user_id);
+        self.failed_logins.remove(user_id);
+        self.basic_foods.retain(|_, food| food.owner.as_deref() != Some(user_id));
+        self.composite_foods.retain(|_, food| food.owner.as_deref() != Some(user_id));
+        if self.current_user == user_id {
+            self.current_user.clear();
+        }
+    }
+
+    pub fn record_audit(&mut self, username: &str, event: &str) {
+        self.audit_log.push(AuditEntry {
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            username: username.to_string(),
+            event: event.to_string(),
+        });
+        if self.audit_log.len() > MAX_AUDIT_ENTRIES {
+            let excess = self.audit_log.len() - MAX_AUDIT_ENTRIES;
+            self.audit_log.drain(..excess);
+        }
+    }
+
+    pub fn find_user_by_id(&self, user_id: &str) -> Option<&User> {
+        self.users.values().find(|u| u.user_id == user_id)
+    }
+
+    pub fn calories_consumed(&self, user_id: &str, date: &str) -> f32 {
+        self.food_logs.get(user_id).map_or(0.0, |entries| {
+            entries
+                .iter()
+                .filter(|entry| entry.date == date && !entry.planned)
+                .map(|entry| self.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
+                // fold from +0.0 so an emptied day doesn't show as "-0 kcal"
+                .fold(0.0, |total, kcal| total + kcal)
+        })
+    }
+
+    // Calories of entries planned for a date but not marked as eaten
+    pub fn calories_planned(&self, user_id: &str, date: &str) -> f32 {
+        self.food_logs.get(user_id).map_or(0.0, |entries| {
+            entries
+                .iter()
+                .filter(|entry| entry.date == date && entry.planned)
+                .map(|entry| self.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
+                .fold(0.0, |total, kcal| total + kcal)
+        })
+    }
+
+    pub fn calories_burned(&self, user_id: &str, date: &str) -> f32 {
+        let weight_kg = match self.find_user_by_id(user_id) {
+            Some(user) => user.profile.weight_kg,
+            None => return 0.0,
+        };
+
+        self.exercise_logs.get(user_id).map_or(0.0, |entries| {
+            entries
+                .iter()
+                .filter(|entry| entry.date == date)
+                .filter_map(|entry| {
+                    self.exercises
+                        .get(&entry.exercise_id)
+                        .map(|exercise| exercise.calories_burned(weight_kg, entry.duration_minutes))
+                })
+                .fold(0.0, |total, kcal| total + kcal)
+        })
+    }
+
+    // Calories that exercise adds back onto the daily allowance, if the user opted in
+    pub fn exercise_credit(&self, user_id: &str, date: &str) -> f32 {
+        match self.find_user_by_id(user_id) {
+            Some(user) if user.profile.earn_back_exercise => self.calories_burned(user_id, date),
+            _ => 0.0,
+        }
+    }
+
+    // Make sure the user has the built-in water tracker
+    pub fn ensure_default_trackers(&mut self, user_id: &str) {
+        let trackers = self.trackers.entry(user_id.to_string()).or_default();
+        if !trackers.iter().any(|t| t.id == WATER_TRACKER_ID) {
+            trackers.insert(0, HabitTracker {
+                id: WATER_TRACKER_ID.to_string(),
+                name: "Water".to_string(),
+                unit: "ml".to_string(),
+                daily_goal: Some(2000.0),
+                increment: 250.0,
+            });
+        }
+    }
+
+    pub fn tracker_value(&self, user_id: &str, tracker_id: &str, date: &str) -> f32 {
+        self.tracker_logs
+            .get(user_id)
+            .and_then(|entries| entries.iter().find(|e| e.tracker_id == tracker_id && e.date == date))
+            .map_or(0.0, |entry| entry.value)
+    }
+
+    // Add delta to the day's total for a tracker, never going below zero
+    pub fn adjust_tracker(&mut self, user_id: &str, tracker_id: &str, date: &str, delta: f32) {
+        let entries = self.tracker_logs.entry(user_id.to_string()).or_default();
+        match entries.iter_mut().find(|e| e.tracker_id == tracker_id && e.date == date) {
+            Some(entry) => entry.value = (entry.value + delta).max(0.0),
+            None => entries.push(TrackerEntry {
+                date: date.to_string(),
+                tracker_id: tracker_id.to_string(),
+                value: delta.max(0.0),
+                user_id: user_id.to_string(),
+            }),
+        }
+    }
+
+    // Estimate when the user reaches their goal weight if they keep eating
+    // what they have actually logged over the last PROJECTION_WINDOW_DAYS days
+    pub fn project_goal_date(&self, user_id: &str, today: NaiveDate) -> GoalProjection {
+        let user = match self.find_user_by_id(user_id) {
+            Some(user) => user,
+            None => return GoalProjection::NoData,
+        };
+        let goal = match &user.profile.weight_goal {
+            Some(goal) => goal,
+            None => return GoalProjection::NoData,
+        };
+
+        let remaining_kg = goal.target_weight_kg - user.profile.weight_kg;
+        if remaining_kg.abs() < 0.05 {
+            return GoalProjection::Reached;
+        }
+
+        // Only days with at least one entry count, so unlogged days don't look like fasting
+        let logged_days: Vec<f32> = (1..=PROJECTION_WINDOW_DAYS)
+            .map(|offset| (today - Duration::days(offset)).format("%Y-%m-%d").to_string())
+            .filter(|date| {
+                self.food_logs
+                    .get(user_id)
+                    .is_some_and(|entries| entries.iter().any(|e| &e.date == date && !e.planned))
+            })
+            .map(|date| self.calories_consumed(user_id, &date))
+            .collect();
+
+        if logged_days.is_empty() {
+            return GoalProjection::NoData;
+        }
+
+        let average_intake = logged_days.iter().sum::<f32>() / logged_days.len() as f32;
+        let daily_balance = average_intake - user.profile.calculate_maintenance_calories();
+        let kg_per_day = daily_balance / KCAL_PER_KG;
+
+        if kg_per_day == 0.0 || kg_per_day.signum() != remaining_kg.signum() {
+            return GoalProjection::OffTrack;
+        }
+
+        let days = (remaining_kg / kg_per_day).ceil() as i64;
+        match today.checked_add_signed(Duration::days(days)) {
+            Some(date) => GoalProjection::OnTrack(date),
+            None => GoalProjection::OffTrack,
+        }
+    }
+
+    pub fn get_food_calories(&self, food_id: &str) -> Option<f32> {
+        self.food_calories_at_depth(food_id, 0)
+    }
+
+    // Components nested deeper than MAX_RECIPE_DEPTH (only possible through a
+    // cycle) count as zero instead of recursing forever
+    fn food_calories_at_depth(&self, food_id: &str, depth: usize) -> Option<f32> {
+        if let Some(basic_food) = self.basic_foods.get(food_id) {
+            Some(basic_food.calories_per_serving)
+        } else if let Some(composite_food) = self.composite_foods.get(food_id) {
+            if depth >= MAX_RECIPE_DEPTH {
+                return Some(0.0);
+            }
+            let mut total_calories = 0.0;
+            for component in &composite_food.components {
+                if let Some(calories) = self.food_calories_at_depth(&component.food_id, depth + 1) {
+                    total_calories += calories * component.servings;
+                }
+            }
+            Some(total_calories)
+        } else {
+            None
+        }
+    }
+
+    // (consumed, target, remaining) for a user on a date. Remaining includes any
+    // exercise calories the user has chosen to earn back.
+    pub fn calculate_calories(&self, user_id: &str, date: &str) -> (f32, f32, f32) {
+        let total_calories = self.calories_consumed(user_id, date);
+
+        let target_calories = self
+            .find_user_by_id(user_id)
+            .map(|user| user.profile.calculate_target_calories())
+            .unwrap_or(0.0);
+
+        let difference = target_calories + self.exercise_credit(user_id, date) - total_calories;
+        (total_calories, target_calories, difference)
+    }
+
+    pub fn food_name(&self, food_id: &str) -> String {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            food.name.clone()
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            food.name.clone()
+        } else {
+            food_id.to_string()
+        }
+    }
+
+    pub fn food_exists(&self, food_id: &str) -> bool {
+        self.basic_foods.contains_key(food_id) || self.composite_foods.contains_key(food_id)
+    }
+
+    // Some(None) for a shared food, Some(Some(user_id)) for a private one
+    pub fn food_owner(&self, food_id: &str) -> Option<Option<&str>> {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            Some(food.owner.as_deref())
+        } else {
+            self.composite_foods.get(food_id).map(|food| food.owner.as_deref())
+        }
+    }
+
+    // Shared foods are visible to everyone, private ones only to their owner.
+    // An empty user_id sees the shared catalog only.
+    pub fn food_visible_to(&self, food_id: &str, user_id: &str) -> bool {
+        match self.food_owner(food_id) {
+            Some(None) => true,
+            Some(Some(owner)) => owner == user_id,
+            None => false,
+        }
+    }
+
+    // Turn an identifier a user typed into a food key: their own private food
+    // first, then the shared one. Full keys are accepted as they are.
+    pub fn resolve_food_id(&self, user_id: &str, food_id: &str) -> Option<String> {
+        let food_id = food_id.trim();
+        [private_food_key(user_id, food_id), food_id.to_string()]
+            .into_iter()
+            .find(|id| self.food_visible_to(id, user_id))
+    }
+}
+
+// ...existing code...
+
+// ...existing code...
+
+impl Default for Database {
+    fn default() -> Self {
+        let mut basic_foods = std::collections::HashMap::new();
+        let mut composite_foods = std::collections::HashMap::new();
+
+        // Add hardcoded basic foods
+        basic_foods.insert("apple".to_string(), BasicFood {
+            id: "apple".to_string(),
+            name: "Apple".to_string(),
+            keywords: vec!["fruit".to_string(), "fresh".to_string(), "snack".to_string()],
+            calories_per_serving: 95.0,
+            owner: None,
+        });
+
+        basic_foods.insert("banana".to_string(), BasicFood {
+            id: "banana".to_string(),
+            name: "Banana".to_string(),
+            keywords: vec!["fruit".to_string(), "fresh".to_string(), "potassium".to_string()],
+            calories_per_serving: 105.0,
+            owner: None,
+        });
+
+        basic_foods.insert("chicken_breast".to_string(), BasicFood {
+            id: "chicken_breast".to_string(),
+            name: "Chicken Breast".to_string(),
+            keywords: vec!["meat".to_string(), "protein".to_string(), "lean".to_string()],
+            calories_per_serving: 165.0,
+            owner: None,
+        });
+
+        basic_foods.insert("brown_rice".to_string(), BasicFood {
+            id: "brown_rice".to_string(),
+            name: "Brown Rice".to_string(),
+            keywords: vec!["grain".to_string(), "carbs".to_string(), "whole grain".to_string()],
+            calories_per_serving: 215.0,
+            owner: None,
+        });
+
+        basic_foods.insert("egg".to_string(), BasicFood {
+            id: "egg".to_string(),
+            name: "Egg".to_string(),
+            keywords: vec!["protein".to_string(), "breakfast".to_string()],
+            calories_per_serving: 78.0,
+            owner: None,
+        });
+
+        // Add bread as well for the sandwich
+        basic_foods.insert("bread".to_string(), BasicFood {
+            id: "bread".to_string(),
+            name: "Bread Slice".to_string(),
+            keywords: vec!["grain".to_string(), "carbs".to_string()],
+            calories_per_serving: 80.0,
+            owner: None,
+        });
+
+        // Add a hardcoded composite food - using Vec<FoodComponent> instead of HashMap
+        let sandwich_components = vec![
+            FoodComponent {
+                food_id: "chicken_breast".to_string(),
+                servings: 0.5
+            },
+            FoodComponent {
+                food_id: "bread".to_string(),
+                servings: 2.0
+            },
+        ];
+
+        composite_foods.insert("sandwich".to_string(), CompositeFood {
+            id: "sandwich".to_string(),
+            name: "Basic Sandwich".to_string(),
+            keywords: vec!["lunch".to_string(), "quick".to_string(), "easy".to_string()],
+            components: sandwich_components,
+            owner: None,
+        });
+
+        // Add another hardcoded composite food
+        let oatmeal_components = vec![
+            FoodComponent {
+                food_id: "banana".to_string(),
+                servings: 1.0
+            },
+        ];
+
+        composite_foods.insert("banana_oatmeal".to_string(), CompositeFood {
+            id: "banana_oatmeal".to_string(),
+            name: "Banana Oatmeal".to_string(),
+            keywords: vec!["breakfast".to_string(), "healthy".to_string()],
+            components: oatmeal_components,
+            owner: None,
+        });
+
+        Self {
+            users: std::collections::HashMap::new(),
+            basic_foods,
+            composite_foods,
+            food_logs: std::collections::HashMap::new(),
+            current_user: String::new(),
+            exercises: default_exercises(),
+            exercise_logs: std::collections::HashMap::new(),
+            trackers: std::collections::HashMap::new(),
+            tracker_logs: std::collections::HashMap::new(),
+            meal_templates: std::collections::HashMap::new(),
+            shopping_checked: std::collections::HashMap::new(),
+            failed_logins: std::collections::HashMap::new(),
+            audit_log: Vec::new(),
+        }
+    }
+}
+
+pub fn default_exercises() -> HashMap<String, Exercise> {
+    // MET values from the Compendium of Physical Activities
+    [
+        ("walking", "Walking (brisk)", 3.5),
+        ("running", "Running (6 mph)", 9.8),
+        ("cycling", "Cycling (moderate)", 7.5),
+        ("swimming", "Swimming (laps)", 6.0),
+        ("weight_training", "Weight Training", 5.0),
+        ("hiit", "HIIT / Circuit Training", 8.0),
+        ("yoga", "Yoga", 2.5),
+        ("hiking", "Hiking", 6.0),
+    ]
+    .into_iter()
+    .map(|(id, name, met)| {
+        (id.to_string(), Exercise {
+            id: id.to_string(),
+            name: name.to_string(),
+            met,
+        })
+    })
+    .collect()
+}
+
+// A member account with an average profile, for tests in other modules that
+// need a user to exist
+#[cfg(test)]
+pub(crate) fn test_user(user_id: &str, username: &str) -> User {
+    User {
+        user_id: user_id.to_string(),
+        username: username.to_string(),
+        password: String::new(),
+        profile: UserProfile {
+            gender: Gender::Female,
+            height_cm: 165.0,
+            age: 35,
+            calorie_method: CalorieCalculationMethod::MifflinStJeor,
+            weight_kg: 60.0,
+            activity_level: ActivityLevel::Light,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        },
+        two_factor: None,
+        role: Role::Member,
+        disabled: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(gender: Gender, weight_kg: f32, height_cm: f32, age: u32, activity_level: ActivityLevel) -> UserProfile {
+        UserProfile {
+            gender,
+            height_cm,
+            age,
+            calorie_method: CalorieCalculationMethod::HarrisBenedict,
+            weight_kg,
+            activity_level,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        }
+    }
+
+    fn basic(id: &str, calories: f32) -> BasicFood {
+        BasicFood {
+            id: id.to_string(),
+            name: id.to_string(),
+            keywords: vec![id.to_string()],
+            calories_per_serving: calories,
+            owner: None,
+        }
+    }
+
+    fn composite(id: &str, components: &[(&str, f32)]) -> CompositeFood {
+        CompositeFood {
+            id: id.to_string(),
+            name: id.to_string(),
+            keywords: vec![id.to_string()],
+            components: components
+                .iter()
+                .map(|(food_id, servings)| FoodComponent { food_id: food_id.to_string(), servings: *servings })
+                .collect(),
+            owner: None,
+        }
+    }
+
+    // An empty database with one user and a few foods
+    fn test_db(profile: UserProfile) -> Database {
+        let mut db = Database {
+            users: HashMap::new(),
+            basic_foods: HashMap::new(),
+            composite_foods: HashMap::new(),
+            food_logs: HashMap::new(),
+            current_user: String::new(),
+            exercises: default_exercises(),
+            exercise_logs: HashMap::new(),
+            trackers: HashMap::new(),
+            tracker_logs: HashMap::new(),
+            meal_templates: HashMap::new(),
+            shopping_checked: HashMap::new(),
+            failed_logins: HashMap::new(),
+            audit_log: Vec::new(),
+        };
+        db.users.insert("alice".to_string(), User {
+            user_id: "u1".to_string(),
+            username: "alice".to_string(),
+            password: "secret".to_string(),
+            profile,
+            two_factor: None,
+            role: Role::Member,
+            disabled: false,
+        });
+        for food in [basic("apple", 95.0), basic("bread", 80.0), basic("cheese", 110.0)] {
+            db.basic_foods.insert(food.id.clone(), food);
+        }
+        db.composite_foods.insert("toast".to_string(), composite("toast", &[("bread", 2.0), ("cheese", 0.5)]));
+        db
+    }
+
+    fn log(db: &mut Database, date: &str, food_id: &str, servings: f32) {
+        db.food_logs.entry("u1".to_string()).or_default().push(FoodLogEntry::new("u1", date, food_id, servings, Meal::Snack, false));
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.5, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn target_without_goal_is_maintenance() {
+        let profile = profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate);
+        let bmr = 88.362 + 13.397 * 70.0 + 4.799 * 170.0 - 5.677 * 30.0;
+
+        assert_close(profile.calculate_target_calories(), bmr * 1.55);
+    }
+
+    #[test]
+    fn target_scales_with_activity_level() {
+        let sedentary = profile(Gender::Female, 60.0, 165.0, 25, ActivityLevel::Sedentary);
+        let active = profile(Gender::Female, 60.0, 165.0, 25, ActivityLevel::ExtraActive);
+
+        assert_close(active.calculate_target_calories() / sedentary.calculate_target_calories(), 1.9 / 1.2);
+    }
+
+    #[test]
+    fn target_subtracts_weekly_deficit() {
+        let mut profile = profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate);
+        let maintenance = profile.calculate_target_calories();
+        profile.weight_goal = Some(WeightGoal { target_weight_kg: 65.0, pace: GoalPace::WeeklyRate(0.5) });
+
+        assert_close(profile.calculate_target_calories(), maintenance - 0.5 * KCAL_PER_KG / 7.0);
+    }
+
+    #[test]
+    fn target_caps_weekly_rate() {
+        let mut profile = profile(Gender::Male, 90.0, 180.0, 40, ActivityLevel::VeryActive);
+        let maintenance = profile.calculate_target_calories();
+        profile.weight_goal = Some(WeightGoal { target_weight_kg: 70.0, pace: GoalPace::WeeklyRate(3.0) });
+
+        assert_close(profile.calculate_target_calories(), maintenance - MAX_WEEKLY_RATE_KG * KCAL_PER_KG / 7.0);
+    }
+
+    #[test]
+    fn target_never_drops_below_floor() {
+        let mut profile = profile(Gender::Female, 45.0, 150.0, 60, ActivityLevel::Sedentary);
+        profile.weight_goal = Some(WeightGoal { target_weight_kg: 40.0, pace: GoalPace::WeeklyRate(1.0) });
+
+        assert_eq!(profile.calculate_target_calories(), MIN_CALORIES_FEMALE);
+    }
+
+    #[test]
+    fn food_calories_for_basic_and_composite() {
+        let db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+
+        assert_eq!(db.get_food_calories("apple"), Some(95.0));
+        assert_eq!(db.get_food_calories("toast"), Some(2.0 * 80.0 + 0.5 * 110.0));
+        assert_eq!(db.get_food_calories("missing"), None);
+    }
+
+    #[test]
+    fn food_calories_for_nested_composite() {
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        db.composite_foods.insert("breakfast".to_string(), composite("breakfast", &[("toast", 2.0), ("apple", 1.0)]));
+
+        assert_eq!(db.get_food_calories("breakfast"), Some(2.0 * 215.0 + 95.0));
+    }
+
+    #[test]
+    fn food_calories_skip_missing_components() {
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        db.composite_foods.insert("snack".to_string(), composite("snack", &[("apple", 1.0), ("deleted", 3.0)]));
+
+        assert_eq!(db.get_food_calories("snack"), Some(95.0));
+    }
+
+    #[test]
+    fn food_calories_stop_at_a_cycle() {
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        db.composite_foods.insert("a".to_string(), composite("a", &[("apple", 1.0), ("b", 1.0)]));
+        db.composite_foods.insert("b".to_string(), composite("b", &[("a", 1.0)]));
+
+        // Each trip round the cycle adds one apple until the depth limit
+        assert_eq!(db.get_food_calories("a"), Some(95.0 * (MAX_RECIPE_DEPTH / 2) as f32));
+    }
+
+    #[test]
+    fn calculate_calories_counts_only_that_day() {
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        log(&mut db, "2025-01-01", "apple", 2.0);
+        log(&mut db, "2025-01-01", "toast", 1.0);
+        log(&mut db, "2025-01-02", "bread", 5.0);
+
+        let target = db.users["alice"].profile.calculate_target_calories();
+        let (consumed, day_target, remaining) = db.calculate_calories("u1", "2025-01-01");
+
+        assert_eq!(consumed, 2.0 * 95.0 + 215.0);
+        assert_eq!(day_target, target);
+        assert_close(remaining, target - consumed);
+    }
+
+    #[test]
+    fn calculate_calories_adds_exercise_only_when_earned_back() {
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        db.exercise_logs.insert("u1".to_string(), vec![ExerciseLogEntry {
+            date: "2025-01-01".to_string(),
+            exercise_id: "running".to_string(),
+            duration_minutes: 30.0,
+            user_id: "u1".to_string(),
+        }]);
+        let burned = db.calories_burned("u1", "2025-01-01");
+        assert!(burned > 0.0);
+
+        let (_, target, remaining) = db.calculate_calories("u1", "2025-01-01");
+        assert_close(remaining, target + burned);
+
+        db.users.get_mut("alice").unwrap().profile.earn_back_exercise = false;
+        let (_, target, remaining) = db.calculate_calories("u1", "2025-01-01");
+        assert_close(remaining, target);
+    }
+
+    #[test]
+    fn calculate_calories_for_unknown_user_is_zero() {
+        let db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+
+        assert_eq!(db.calculate_calories("nobody", "2025-01-01"), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn entries_saved_without_ids_get_unique_ones() {
+        let legacy = r#"{"date": "2025-01-01", "food_id": "apple", "servings": 1.0, "user_id": "u1"}"#;
+        let entry: FoodLogEntry = serde_json::from_str(legacy).unwrap();
+        assert!(entry.id.is_empty());
+
+        let mut db = test_db(profile(Gender::Male, 70.0, 170.0, 30, ActivityLevel::Moderate));
+        db.food_logs.insert("u1".to_string(), vec![entry.clone(), entry]);
+        log(&mut db, "2025-01-01", "apple", 1.0);
+        let existing = db.food_logs["u1"][2].id.clone();
+
+        assert_eq!(db.assign_entry_ids(), 2);
+        assert_eq!(db.assign_entry_ids(), 0);
+        let ids: Vec<&str> = db.food_logs["u1"].iter().map(|entry| entry.id.as_str()).collect();
+        assert!(ids.iter().all(|id| !id.is_empty()));
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[2], existing);
+    }
+}
@@ -0,0 +1,223 @@
+// Fuzzy, ranked matching of search queries against foods. Matching works on
+// words, so "chick" finds "Chicken Breast" and "chiken" still finds it.
+
+// Words matched in the name count for more than words matched in keywords or the id
+const NAME_WEIGHT: f32 = 2.0;
+const KEYWORD_WEIGHT: f32 = 1.0;
+
+const EXACT_SCORE: f32 = 1.0;
+const PREFIX_SCORE: f32 = 0.8;
+const TYPO_SCORE: f32 = 0.6;
+const TYPO_PREFIX_SCORE: f32 = 0.4;
+const SUBSTRING_SCORE: f32 = 0.3;
+
+// Usage boosts stay below the gap between a name hit and a keyword hit
+const MAX_FREQUENCY_BOOST: f32 = 0.6;
+const RECENT_BOOST: f32 = 0.3;
+const RECENT_DAYS: i64 = 7;
+
+// The text a food can be found by
+pub struct Document<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub keywords: &'a [String],
+}
+
+// Lowercase alphanumeric words, e.g. "Whole-wheat Bread (1 slice)" -> whole, wheat, bread, 1, slice
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+// How well one query term matches one word, if at all
+pub fn term_score(term: &str, word: &str) -> Option<f32> {
+    if term == word {
+        return Some(EXACT_SCORE);
+    }
+    if word.starts_with(term) {
+        return Some(PREFIX_SCORE);
+    }
+
+    let allowed = allowed_typos(term);
+    if allowed > 0 {
+        if edit_distance(term, word) <= allowed {
+            return Some(TYPO_SCORE);
+        }
+
+        // A misspelled start of a longer word, e.g. "chik" for "chicken"
+        let prefix: String = word.chars().take(term.chars().count()).collect();
+        if prefix.len() < word.len() && edit_distance(term, &prefix) <= allowed {
+            return Some(TYPO_PREFIX_SCORE);
+        }
+    }
+
+    if term.chars().count() >= 3 && word.contains(term) {
+        return Some(SUBSTRING_SCORE);
+    }
+
+    None
+}
+
+// Relevance of a document for the query terms, or None when it doesn't match.
+// Every term has to match when match_all is set, otherwise at least one.
+// An empty query matches everything with a score of zero.
+pub fn score(terms: &[String], document: &Document, match_all: bool) -> Option<f32> {
+    if terms.is_empty() {
+        return Some(0.0);
+    }
+
+    let name_words = tokenize(document.name);
+    let other_words: Vec<String> = document.keywords
+        .iter()
+        .flat_map(|keyword| tokenize(keyword))
+        .chain(tokenize(document.id))
+        .collect();
+
+    let mut total = 0.0;
+    let mut matched_any = false;
+    for term in terms {
+        let best_in = |words: &[String], weight: f32| {
+            words
+                .iter()
+                .filter_map(|word| term_score(term, word))
+                .fold(None, |best: Option<f32>, score| Some(best.map_or(score, |b| b.max(score))))
+                .map(|score| score * weight)
+        };
+
+        let best = match (best_in(&name_words, NAME_WEIGHT), best_in(&other_words, KEYWORD_WEIGHT)) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+
+        match best {
+            Some(score) => {
+                total += score;
+                matched_any = true;
+            }
+            None if match_all => return None,
+            None => {}
+        }
+    }
+
+    matched_any.then_some(total)
+}
+
+// Extra relevance for foods the user logs often or logged lately
+pub fn usage_boost(times_logged: usize, days_since_last_use: Option<i64>) -> f32 {
+    let frequency = ((1.0 + times_logged as f32).ln() * 0.2).min(MAX_FREQUENCY_BOOST);
+    let recent = match days_since_last_use {
+        Some(days) if (0..=RECENT_DAYS).contains(&days) => RECENT_BOOST,
+        _ => 0.0,
+    };
+
+    frequency + recent
+}
+
+// Short words must match exactly; longer ones tolerate one or two typos
+fn allowed_typos(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+// Levenshtein distance, counting an adjacent swap ("chikcen") as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut table = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut best = (table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1)
+                .min(table[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(table[i - 2][j - 2] + 1);
+            }
+            table[i][j] = best;
+        }
+    }
+
+    table[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn score_of(query: &str, name: &str, words: &[&str]) -> Option<f32> {
+        let keywords = keywords(words);
+        let document = Document { id: "food", name, keywords: &keywords };
+        score(&tokenize(query), &document, false)
+    }
+
+    #[test]
+    fn tokenize_splits_on_punctuation_and_lowercases() {
+        assert_eq!(tokenize("Whole-wheat Bread (1 slice)"), vec!["whole", "wheat", "bread", "1", "slice"]);
+        assert!(tokenize("  ,; ").is_empty());
+    }
+
+    #[test]
+    fn prefixes_and_typos_match() {
+        assert_eq!(term_score("chick", "chicken"), Some(PREFIX_SCORE));
+        assert_eq!(term_score("chiken", "chicken"), Some(TYPO_SCORE));
+        assert_eq!(term_score("chikcen", "chicken"), Some(TYPO_SCORE));
+        assert_eq!(term_score("chik", "chicken"), Some(TYPO_PREFIX_SCORE));
+        assert_eq!(term_score("egg", "eggs"), Some(PREFIX_SCORE));
+    }
+
+    #[test]
+    fn short_terms_do_not_fuzzy_match() {
+        assert_eq!(term_score("egg", "ham"), None);
+        assert_eq!(term_score("tea", "pea"), None);
+    }
+
+    #[test]
+    fn name_hits_rank_above_keyword_hits() {
+        let by_name = score_of("apple", "Apple", &["fruit"]).unwrap();
+        let in_longer_name = score_of("apple", "Apple Pie", &["dessert"]).unwrap();
+        let by_keyword = score_of("apple", "Cider", &["apple", "drink"]).unwrap();
+
+        assert!(by_name >= in_longer_name);
+        assert!(in_longer_name > by_keyword);
+    }
+
+    #[test]
+    fn match_all_requires_every_term() {
+        let keywords = keywords(&["bread", "breakfast"]);
+        let document = Document { id: "toast", name: "Toast", keywords: &keywords };
+
+        assert!(score(&tokenize("toast jam"), &document, false).is_some());
+        assert!(score(&tokenize("toast jam"), &document, true).is_none());
+        assert!(score(&tokenize("toast bread"), &document, true).is_some());
+    }
+
+    #[test]
+    fn unrelated_queries_do_not_match() {
+        assert!(score_of("pizza", "Apple", &["fruit"]).is_none());
+    }
+
+    #[test]
+    fn usage_boost_favours_frequent_and_recent_foods() {
+        assert_eq!(usage_boost(0, None), 0.0);
+        assert!(usage_boost(10, None) > usage_boost(1, None));
+        assert!(usage_boost(1, Some(2)) > usage_boost(1, Some(30)));
+        assert!(usage_boost(1000, Some(0)) < NAME_WEIGHT - KEYWORD_WEIGHT);
+    }
+}
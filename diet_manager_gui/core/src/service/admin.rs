@@ -0,0 +1,351 @@
+// Managing other accounts and the shared catalog. Every call checks that the
+// acting user is an admin and is written to the audit log. Account changes
+// start the undo history over, like the account changes in auth.
+use std::collections::HashSet;
+use serde::Serialize;
+use crate::crypto;
+use crate::models::{AuditEntry, Database, Role, User};
+use crate::service::auth;
+use crate::undo::UndoManager;
+
+pub const ADMIN_ONLY: &str = "Only admins can do this";
+
+// One row of the admin console's user list
+#[derive(Serialize, Debug, Clone)]
+pub struct UserStats {
+    pub user_id: String,
+    pub username: String,
+    pub role: Role,
+    pub disabled: bool,
+    pub locked_out: bool,
+    pub two_factor: bool,
+    pub food_entries: usize,
+    pub days_logged: usize,
+    pub last_logged: Option<String>, // YYYY-MM-DD of the latest eaten entry
+    pub exercise_entries: usize,
+}
+
+// Every account with how much it has logged, sorted by username
+pub fn user_stats(db: &Database, admin_id: &str) -> Result<Vec<UserStats>, String> {
+    require_admin(db, admin_id)?;
+
+    let mut stats: Vec<UserStats> = db.users
+        .values()
+        .map(|user| {
+            let eaten: Vec<_> = db.food_logs
+                .get(&user.user_id)
+                .into_iter()
+                .flatten()
+                .filter(|entry| !entry.planned)
+                .collect();
+            let days: HashSet<&str> = eaten.iter().map(|entry| entry.date.as_str()).collect();
+
+            UserStats {
+                user_id: user.user_id.clone(),
+                username: user.username.clone(),
+                role: user.role,
+                disabled: user.disabled,
+                locked_out: auth::is_locked_out(db, &user.user_id),
+                two_factor: user.two_factor.is_some(),
+                food_entries: eaten.len(),
+                days_logged: days.len(),
+                last_logged: days.iter().max().map(|date| date.to_string()),
+                exercise_entries: db.exercise_logs.get(&user.user_id).map_or(0, Vec::len),
+            }
+        })
+        .collect();
+
+    stats.sort_by_key(|user| user.username.to_lowercase());
+    Ok(stats)
+}
+
+// The most recent audit entries, newest first
+pub fn audit_log(db: &Database, admin_id: &str, limit: usize) -> Result<Vec<AuditEntry>, String> {
+    require_admin(db, admin_id)?;
+    Ok(db.audit_log.iter().rev().take(limit).cloned().collect())
+}
+
+pub fn set_role(db: &mut Database, undo: &mut UndoManager, admin_id: &str, user_id: &str, role: Role) -> Result<(), String> {
+    let admin = require_admin(db, admin_id)?;
+    let user = find_user(db, user_id)?;
+    if user.role == role {
+        return Ok(());
+    }
+    if user.role == Role::Admin && !user.disabled && auth::active_admins(db) == 1 {
+        return Err("There must be at least one admin".to_string());
+    }
+
+    let username = user.username.clone();
+    user_mut(db, user_id)?.role = role;
+    db.record_audit(&admin, &format!("Changed the role of {} to {}", username, role.label()));
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// A disabled account can't log in; its data is kept
+pub fn set_disabled(db: &mut Database, undo: &mut UndoManager, admin_id: &str, user_id: &str, disabled: bool) -> Result<(), String> {
+    let admin = require_admin(db, admin_id)?;
+    if user_id == admin_id && disabled {
+        return Err("You cannot disable your own account".to_string());
+    }
+
+    let user = user_mut(db, user_id)?;
+    if user.disabled == disabled {
+        return Ok(());
+    }
+    user.disabled = disabled;
+    let username = user.username.clone();
+    let action = if disabled { "Disabled" } else { "Enabled" };
+    db.record_audit(&admin, &format!("{} the account {}", action, username));
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Set a new password for someone who forgot theirs. This also lifts a lockout
+// after failed logins.
+pub fn reset_password(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    admin_id: &str,
+    user_id: &str,
+    new_password: &str
+) -> Result<(), String> {
+    let admin = require_admin(db, admin_id)?;
+    if new_password.is_empty() {
+        return Err("New password cannot be empty".to_string());
+    }
+
+    let hash = crypto::hash_password(new_password)?;
+    let user = user_mut(db, user_id)?;
+    user.password = hash;
+    let username = user.username.clone();
+    db.failed_logins.remove(user_id);
+    db.record_audit(&admin, &format!("Reset the password of {}", username));
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Remove a food from the shared catalog. Foods still used by a composite food,
+// a saved meal or someone's log are kept, since removing them would change past
+// totals or leave a meal that can't be applied. Ticks on shopping lists are dropped.
+pub fn delete_food(db: &mut Database, undo: &mut UndoManager, admin_id: &str, food_id: &str) -> Result<(), String> {
+    let admin = require_admin(db, admin_id)?;
+    if !db.food_exists(food_id) {
+        return Err(format!("Food '{}' not found", food_id));
+    }
+
+    let used_in: Vec<&str> = db.composite_foods
+        .values()
+        .filter(|food| food.components.iter().any(|component| component.food_id == food_id))
+        .map(|food| food.name.as_str())
+        .collect();
+    if !used_in.is_empty() {
+        return Err(format!("{} is part of {}", db.food_name(food_id), used_in.join(", ")));
+    }
+
+    let times_logged = db.food_logs.values().flatten().filter(|entry| entry.food_id == food_id).count();
+    if times_logged > 0 {
+        return Err(format!("{} has been logged {} times and can't be deleted", db.food_name(food_id), times_logged));
+    }
+
+    let in_templates = db.meal_templates
+        .values()
+        .flatten()
+        .filter(|template| template.items.iter().any(|item| item.food_id == food_id))
+        .count();
+    if in_templates > 0 {
+        return Err(format!("{} is in {} saved meals and can't be deleted", db.food_name(food_id), in_templates));
+    }
+
+    let name = db.food_name(food_id);
+    db.basic_foods.remove(food_id);
+    db.composite_foods.remove(food_id);
+    db.shopping_checked.values_mut().for_each(|checked| checked.retain(|id| id != food_id));
+    db.record_audit(&admin, &format!("Deleted the food {}", name));
+
+    // Undo would also take back the audit entry
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Returns the admin's username for the audit log
+pub(crate) fn require_admin(db: &Database, admin_id: &str) -> Result<String, String> {
+    match db.find_user_by_id(admin_id) {
+        Some(user) if auth::is_admin(db, admin_id) => Ok(user.username.clone()),
+        _ => Err(ADMIN_ONLY.to_string()),
+    }
+}
+
+fn find_user<'a>(db: &'a Database, user_id: &str) -> Result<&'a User, String> {
+    db.find_user_by_id(user_id).ok_or_else(|| "User not found".to_string())
+}
+
+fn user_mut<'a>(db: &'a mut Database, user_id: &str) -> Result<&'a mut User, String> {
+    db.users
+        .values_mut()
+        .find(|user| user.user_id == user_id)
+        .ok_or_else(|| "User not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActivityLevel, CalorieCalculationMethod, Gender, Meal, UserProfile};
+    use crate::service::{catalog, logging, templates};
+    use crate::units::Units;
+
+    fn profile() -> UserProfile {
+        UserProfile {
+            gender: Gender::Female,
+            height_cm: 165.0,
+            age: 35,
+            calorie_method: CalorieCalculationMethod::MifflinStJeor,
+            weight_kg: 60.0,
+            activity_level: ActivityLevel::Light,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        }
+    }
+
+    // An admin (the first account) and a member
+    fn setup() -> (Database, UndoManager, String, String) {
+        let mut db = Database::default();
+        let admin_id = auth::register(&mut db, "ada", "admin-pass", profile()).unwrap();
+        let member_id = auth::register(&mut db, "max", "member-pass", profile()).unwrap();
+        let mut undo = UndoManager::with_log_path(10, None);
+        undo.initialize(db.clone());
+        (db, undo, admin_id, member_id)
+    }
+
+    #[test]
+    fn first_account_is_admin_and_old_accounts_become_members() {
+        let (db, _, admin_id, member_id) = setup();
+        assert!(auth::is_admin(&db, &admin_id));
+        assert!(!auth::is_admin(&db, &member_id));
+
+        let mut json = serde_json::to_value(db.find_user_by_id(&admin_id).unwrap()).unwrap();
+        json.as_object_mut().unwrap().remove("role");
+        let old: User = serde_json::from_value(json).unwrap();
+        assert_eq!(old.role, Role::Member);
+    }
+
+    #[test]
+    fn members_cannot_use_the_console() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+
+        assert_eq!(user_stats(&db, &member_id).unwrap_err(), ADMIN_ONLY);
+        assert!(reset_password(&mut db, &mut undo, &member_id, &admin_id, "mine now").is_err());
+        assert!(set_role(&mut db, &mut undo, &member_id, &member_id, Role::Admin).is_err());
+        assert_eq!(logging::add_custom_exercise(&mut db, &mut undo, &member_id, "Climbing", "8").unwrap_err(), ADMIN_ONLY);
+        logging::add_custom_exercise(&mut db, &mut undo, &admin_id, "Climbing", "8").unwrap();
+        assert!(delete_food(&mut db, &mut undo, &member_id, "apple").is_err());
+    }
+
+    #[test]
+    fn read_only_accounts_cannot_change_data() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        set_role(&mut db, &mut undo, &admin_id, &member_id, Role::ReadOnly).unwrap();
+
+        let added = logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-01", "apple", 1.0, Meal::Lunch);
+        assert_eq!(added.unwrap_err(), auth::READ_ONLY_ACCOUNT);
+        assert!(catalog::add_basic_food(&mut db, &mut undo, &member_id, "kiwi", "Kiwi", "fruit", "42").is_err());
+        // Logging in and looking still works
+        assert_eq!(auth::authenticate(&mut db, "max", "member-pass").unwrap(), member_id);
+    }
+
+    #[test]
+    fn disabled_accounts_cannot_log_in() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        assert!(set_disabled(&mut db, &mut undo, &admin_id, &admin_id, true).is_err());
+        set_disabled(&mut db, &mut undo, &admin_id, &member_id, true).unwrap();
+
+        assert_eq!(auth::authenticate(&mut db, "max", "member-pass").unwrap_err(), auth::ACCOUNT_DISABLED);
+        set_disabled(&mut db, &mut undo, &admin_id, &member_id, false).unwrap();
+        assert!(auth::authenticate(&mut db, "max", "member-pass").is_ok());
+        assert!(db.audit_log.iter().any(|entry| entry.username == "ada" && entry.event == "Disabled the account max"));
+    }
+
+    #[test]
+    fn the_last_admin_stays_an_admin() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        assert!(set_role(&mut db, &mut undo, &admin_id, &admin_id, Role::Member).is_err());
+        assert!(auth::delete_account(&mut db, &mut undo, &admin_id, "admin-pass").is_err());
+
+        set_role(&mut db, &mut undo, &admin_id, &member_id, Role::Admin).unwrap();
+        set_role(&mut db, &mut undo, &admin_id, &admin_id, Role::Member).unwrap();
+        assert!(!auth::is_admin(&db, &admin_id));
+    }
+
+    #[test]
+    fn reset_password_replaces_it_and_lifts_the_lockout() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        for _ in 0..5 {
+            let _ = auth::authenticate(&mut db, "max", "guess");
+        }
+        assert!(auth::is_locked_out(&db, &member_id));
+
+        reset_password(&mut db, &mut undo, &admin_id, &member_id, "fresh-start").unwrap();
+        assert!(!auth::is_locked_out(&db, &member_id));
+        assert_eq!(auth::authenticate(&mut db, "max", "fresh-start").unwrap(), member_id);
+    }
+
+    #[test]
+    fn user_stats_count_eaten_food_per_day() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+        logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-01", "banana", 1.0, Meal::Snack).unwrap();
+        logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-04", "apple", 1.0, Meal::Lunch).unwrap();
+
+        let stats = user_stats(&db, &admin_id).unwrap();
+        assert_eq!(stats.iter().map(|user| user.username.as_str()).collect::<Vec<_>>(), vec!["ada", "max"]);
+        assert_eq!((stats[1].food_entries, stats[1].days_logged), (3, 2));
+        assert_eq!(stats[1].last_logged.as_deref(), Some("2025-03-04"));
+        assert_eq!(stats[0].last_logged, None);
+    }
+
+    #[test]
+    fn foods_in_use_cannot_be_deleted() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        let kiwi = catalog::add_basic_food(&mut db, &mut undo, &member_id, "kiwi", "Kiwi", "fruit", "42").unwrap();
+        catalog::share_food(&mut db, &mut undo, &member_id, &kiwi).unwrap();
+        logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+
+        assert!(delete_food(&mut db, &mut undo, &admin_id, "apple").is_err());
+        delete_food(&mut db, &mut undo, &admin_id, "kiwi").unwrap();
+        assert!(!db.food_exists("kiwi"));
+        assert!(delete_food(&mut db, &mut undo, &admin_id, "kiwi").is_err());
+    }
+
+    #[test]
+    fn foods_in_saved_meals_cannot_be_deleted() {
+        let (mut db, mut undo, admin_id, member_id) = setup();
+        let entry_id = logging::add_entry(&mut db, &mut undo, &member_id, "2025-03-01", "egg", 2.0, Meal::Breakfast).unwrap();
+        templates::save_template(&mut db, &mut undo, &member_id, "Eggs", "2025-03-01", None).unwrap();
+        logging::remove_entry(&mut db, &mut undo, &member_id, &entry_id).unwrap();
+        db.shopping_checked.insert(member_id.clone(), vec!["egg".to_string(), "milk".to_string()]);
+
+        assert!(delete_food(&mut db, &mut undo, &admin_id, "egg").unwrap_err().contains("saved meals"));
+        templates::delete_template(&mut db, &mut undo, &member_id, "Eggs").unwrap();
+        delete_food(&mut db, &mut undo, &admin_id, "egg").unwrap();
+        assert_eq!(db.shopping_checked[&member_id], vec!["milk".to_string()]);
+    }
+
+    #[test]
+    fn undo_cannot_take_back_the_audit_log() {
+        let (mut db, mut undo, admin_id, _) = setup();
+        let kiwi = catalog::add_basic_food(&mut db, &mut undo, &admin_id, "kiwi", "Kiwi", "fruit", "42").unwrap();
+        catalog::share_food(&mut db, &mut undo, &admin_id, &kiwi).unwrap();
+
+        delete_food(&mut db, &mut undo, &admin_id, "kiwi").unwrap();
+        assert!(undo.undo().is_none());
+        assert_eq!(db.audit_log.last().unwrap().event, "Deleted the food Kiwi");
+
+        logging::add_custom_exercise(&mut db, &mut undo, &admin_id, "Climbing", "8").unwrap();
+        assert!(undo.undo().is_none());
+        assert_eq!(db.audit_log.last().unwrap().event, "Added the activity Climbing");
+    }
+}
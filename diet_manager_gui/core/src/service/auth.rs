@@ -0,0 +1,752 @@
+use std::sync::OnceLock;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use uuid::Uuid;
+use crate::crypto;
+use crate::models::{Database, FailedLogins, GoalPace, Role, TwoFactor, User, UserProfile, WeightGoal};
+use crate::totp;
+use crate::units;
+use crate::undo::UndoManager;
+
+// Failed logins allowed before an account is locked
+const FREE_LOGIN_ATTEMPTS: u32 = 3;
+// The first lockout lasts this long; each further failure doubles it
+const BASE_LOCKOUT_SECS: i64 = 30;
+const MAX_LOCKOUT_SECS: i64 = 15 * 60;
+// Failures are forgotten once a name has had none for this long
+const FORGET_FAILURES_SECS: i64 = 24 * 60 * 60;
+// Names without an account that are tracked at once; the stalest go first
+const MAX_UNKNOWN_USER_ENTRIES: usize = 1000;
+// The same for unknown users and wrong passwords, so usernames can't be probed
+const INVALID_CREDENTIALS: &str = "Invalid username or password";
+pub const RECOVERY_CODE_COUNT: usize = 8;
+pub const ACCOUNT_DISABLED: &str = "This account has been disabled by an administrator";
+pub const READ_ONLY_ACCOUNT: &str = "This account is read-only";
+const TOTP_ISSUER: &str = "Diet Manager";
+
+// Create an account and log it in. Returns the new user's id. The first
+// account in a database becomes its admin.
+pub fn register(db: &mut Database, username: &str, password: &str, profile: UserProfile) -> Result<String, String> {
+    let username = username.trim();
+    if username.is_empty() || password.is_empty() {
+        return Err("Username and password are required.".to_string());
+    }
+
+    if db.users.contains_key(username) {
+        return Err("Username already exists.".to_string());
+    }
+
+    validate_profile(&profile)?;
+
+    let user_id = Uuid::new_v4().to_string();
+    let user = User {
+        user_id: user_id.clone(),
+        username: username.to_string(),
+        password: crypto::hash_password(password)?,
+        profile,
+        two_factor: None,
+        role: if db.users.is_empty() { Role::Admin } else { Role::Member },
+        disabled: false,
+    };
+
+    db.users.insert(username.to_string(), user);
+    db.failed_logins.remove(&unknown_user_key(username));
+    db.ensure_default_trackers(&user_id);
+    db.current_user = user_id.clone();
+
+    Ok(user_id)
+}
+
+// Make an authenticated user current. Accounts with two-factor authentication
+// must pass verify_second_factor first.
+pub fn start_session(db: &mut Database, user_id: &str) {
+    db.ensure_default_trackers(user_id);
+    db.current_user = user_id.to_string();
+}
+
+// Check the password without touching the session. Returns the user's id.
+// Failures are counted per account and written to the audit log; past the
+// first few, each one locks the account for twice as long as the last.
+pub fn authenticate(db: &mut Database, username: &str, password: &str) -> Result<String, String> {
+    authenticate_at(db, username, password, Local::now())
+}
+
+fn authenticate_at(db: &mut Database, username: &str, password: &str, now: DateTime<Local>) -> Result<String, String> {
+    if username.is_empty() || password.is_empty() {
+        return Err("Username and password are required".to_string());
+    }
+
+    // Unknown names are counted and locked like accounts, so a lockout doesn't
+    // reveal that an account exists
+    let (user_id, password_matches, disabled) = match db.users.get(username) {
+        Some(user) => (user.user_id.clone(), crypto::verify_password(password, &user.password), user.disabled),
+        None => {
+            // Take as long as checking a real password would
+            crypto::verify_password(password, dummy_password_hash());
+            (unknown_user_key(username), false, false)
+        }
+    };
+    let reason = if db.users.contains_key(username) { "wrong password" } else { "unknown username" };
+    prune_failed_logins(db, now);
+
+    if let Some(seconds) = lockout_remaining(db, &user_id, now) {
+        db.record_audit(username, "Login refused: account is locked");
+        return Err(format!("Too many failed attempts. Try again in {}.", describe_wait(seconds)));
+    }
+
+    if !password_matches {
+        record_failed_login(db, &user_id, username, reason, now);
+        return Err(INVALID_CREDENTIALS.to_string());
+    }
+
+    if disabled {
+        db.record_audit(username, "Login refused: account is disabled");
+        return Err(ACCOUNT_DISABLED.to_string());
+    }
+
+    // With two-factor authentication the count is only reset once the code is right too
+    if !two_factor_enabled(db, &user_id) {
+        clear_failed_logins(db, &user_id, username);
+    }
+    Ok(user_id)
+}
+
+pub fn is_admin(db: &Database, user_id: &str) -> bool {
+    db.find_user_by_id(user_id).is_some_and(|user| user.role == Role::Admin && !user.disabled)
+}
+
+// Read-only and disabled accounts can look at their data but not change it.
+// Checked by every call that changes a user's logs or the catalog.
+pub fn require_write_access(db: &Database, user_id: &str) -> Result<(), String> {
+    match db.find_user_by_id(user_id) {
+        Some(user) if user.disabled => Err(ACCOUNT_DISABLED.to_string()),
+        Some(user) if user.role == Role::ReadOnly => Err(READ_ONLY_ACCOUNT.to_string()),
+        Some(_) => Ok(()),
+        None => Err("User not found".to_string()),
+    }
+}
+
+// Whether repeated failed logins have locked the account right now
+pub fn is_locked_out(db: &Database, user_id: &str) -> bool {
+    lockout_remaining(db, user_id, Local::now()).is_some()
+}
+
+pub fn two_factor_enabled(db: &Database, user_id: &str) -> bool {
+    db.find_user_by_id(user_id).is_some_and(|user| user.two_factor.is_some())
+}
+
+// Second login step: a code from the authenticator app, or one of the recovery
+// codes, which then can't be used again. Wrong codes count as failed logins.
+pub fn verify_second_factor(db: &mut Database, user_id: &str, code: &str) -> Result<(), String> {
+    verify_second_factor_at(db, user_id, code, Local::now())
+}
+
+fn verify_second_factor_at(db: &mut Database, user_id: &str, code: &str, now: DateTime<Local>) -> Result<(), String> {
+    let (username, two_factor) = db.find_user_by_id(user_id)
+        .and_then(|user| Some((user.username.clone(), user.two_factor.clone()?)))
+        .ok_or_else(|| "Two-factor authentication is not turned on".to_string())?;
+
+    if let Some(seconds) = lockout_remaining(db, user_id, now) {
+        db.record_audit(&username, "Login refused: account is locked");
+        return Err(format!("Too many failed attempts. Try again in {}.", describe_wait(seconds)));
+    }
+
+    let code = code.trim();
+    if code.is_empty() {
+        return Err("Enter the code from your authenticator app or a recovery code".to_string());
+    }
+
+    let step = totp::verify(&two_factor.secret, code, now.timestamp().max(0) as u64);
+    let recovery_code = two_factor.recovery_codes
+        .iter()
+        .position(|recovery| totp::normalize_recovery_code(recovery) == totp::normalize_recovery_code(code));
+
+    match (step, recovery_code) {
+        (Some(step), _) if step > two_factor.last_used_step => {
+            if let Some(two_factor) = two_factor_mut(db, user_id) {
+                two_factor.last_used_step = step;
+            }
+        }
+        (_, Some(index)) => {
+            let left = two_factor_mut(db, user_id).map_or(0, |two_factor| {
+                two_factor.recovery_codes.remove(index);
+                two_factor.recovery_codes.len()
+            });
+            db.record_audit(&username, &format!("Logged in with a recovery code ({} left)", left));
+        }
+        _ => {
+            record_failed_login(db, user_id, &username, "wrong verification code", now);
+            return Err("Invalid verification code".to_string());
+        }
+    }
+
+    clear_failed_logins(db, user_id, &username);
+    Ok(())
+}
+
+pub fn logout(db: &mut Database) {
+    db.current_user.clear();
+}
+
+// The account changes below need the current password again. Every older undo
+// snapshot still holds the account as it was, so the undo history starts over
+// afterwards; undoing anything else would quietly revert the change.
+
+pub fn change_password(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    current_password: &str,
+    new_password: &str
+) -> Result<(), String> {
+    let username = reauthenticate(db, user_id, current_password)?;
+    if new_password.is_empty() {
+        return Err("New password cannot be empty".to_string());
+    }
+    if new_password == current_password {
+        return Err("New password must be different from the current one".to_string());
+    }
+
+    let hash = crypto::hash_password(new_password)?;
+    if let Some(user) = db.users.get_mut(&username) {
+        user.password = hash;
+    }
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Users are keyed by username, so the record moves to the new key.
+// Everything else refers to the user by id and is left alone.
+pub fn rename_user(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    password: &str,
+    new_username: &str
+) -> Result<(), String> {
+    let username = reauthenticate(db, user_id, password)?;
+    let new_username = new_username.trim();
+    if new_username.is_empty() {
+        return Err("New username cannot be empty".to_string());
+    }
+    if new_username == username {
+        return Err("That is already your username".to_string());
+    }
+    if db.users.contains_key(new_username) {
+        return Err("Username already exists.".to_string());
+    }
+
+    let mut user = db.users
+        .remove(&username)
+        .ok_or_else(|| "User not found".to_string())?;
+    user.username = new_username.to_string();
+    db.users.insert(new_username.to_string(), user);
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Turn on two-factor authentication with a secret from new_two_factor_secret.
+// The code shows the user has added the secret to their authenticator app.
+// Returns the recovery codes, which are only shown this once.
+pub fn enable_two_factor(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    password: &str,
+    secret: &str,
+    code: &str
+) -> Result<Vec<String>, String> {
+    let username = reauthenticate(db, user_id, password)?;
+    if two_factor_enabled(db, user_id) {
+        return Err("Two-factor authentication is already turned on".to_string());
+    }
+    let step = totp::verify(secret, code, Local::now().timestamp().max(0) as u64)
+        .ok_or_else(|| "That code doesn't match. Check the secret in your authenticator app and try again".to_string())?;
+
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    if let Some(user) = db.users.get_mut(&username) {
+        user.two_factor = Some(TwoFactor {
+            secret: secret.to_string(),
+            recovery_codes: recovery_codes.clone(),
+            last_used_step: step,
+        });
+    }
+    db.record_audit(&username, "Turned on two-factor authentication");
+
+    undo.initialize(db.clone());
+    Ok(recovery_codes)
+}
+
+pub fn disable_two_factor(db: &mut Database, undo: &mut UndoManager, user_id: &str, password: &str) -> Result<(), String> {
+    let username = reauthenticate(db, user_id, password)?;
+    if !two_factor_enabled(db, user_id) {
+        return Err("Two-factor authentication is not turned on".to_string());
+    }
+
+    if let Some(user) = db.users.get_mut(&username) {
+        user.two_factor = None;
+    }
+    db.record_audit(&username, "Turned off two-factor authentication");
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+// Replace all recovery codes, e.g. after using most of them. Returns the new ones.
+pub fn regenerate_recovery_codes(db: &mut Database, undo: &mut UndoManager, user_id: &str, password: &str) -> Result<Vec<String>, String> {
+    let username = reauthenticate(db, user_id, password)?;
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let two_factor = two_factor_mut(db, user_id)
+        .ok_or_else(|| "Two-factor authentication is not turned on".to_string())?;
+    two_factor.recovery_codes = recovery_codes.clone();
+    db.record_audit(&username, "Generated new recovery codes");
+
+    undo.initialize(db.clone());
+    Ok(recovery_codes)
+}
+
+// A fresh secret to enroll, with the otpauth:// link authenticator apps import
+pub fn new_two_factor_secret(db: &Database, user_id: &str) -> (String, String) {
+    let account = db.find_user_by_id(user_id).map_or(String::new(), |user| user.username.clone());
+    let secret = totp::generate_secret();
+    let uri = totp::otpauth_uri(&secret, &account, TOTP_ISSUER);
+    (secret, uri)
+}
+
+// Remove the account with all of its logs, trackers and templates, and log it out
+pub fn delete_account(db: &mut Database, undo: &mut UndoManager, user_id: &str, password: &str) -> Result<(), String> {
+    reauthenticate(db, user_id, password)?;
+    if is_admin(db, user_id) && db.users.len() > 1 && active_admins(db) == 1 {
+        return Err("Make another account an admin before deleting the last admin account".to_string());
+    }
+
+    db.remove_user_data(user_id);
+
+    undo.initialize(db.clone());
+    Ok(())
+}
+
+pub fn update_profile(db: &mut Database, undo: &mut UndoManager, user_id: &str, profile: UserProfile) -> Result<(), String> {
+    require_write_access(db, user_id)?;
+    validate_profile(&profile)?;
+
+    let user = db.users
+        .values_mut()
+        .find(|u| u.user_id == user_id)
+        .ok_or_else(|| "Failed to update profile".to_string())?;
+    user.profile = profile;
+
+    undo.record_action(db.clone(), "Updated profile");
+    Ok(())
+}
+
+// Admins who can still log in
+pub(crate) fn active_admins(db: &Database) -> usize {
+    db.users.values().filter(|user| user.role == Role::Admin && !user.disabled).count()
+}
+
+// Count a failed attempt, locking the account once there have been too many
+fn record_failed_login(db: &mut Database, user_id: &str, username: &str, reason: &str, now: DateTime<Local>) {
+    let failures = db.failed_logins.entry(user_id.to_string()).or_default();
+    failures.count += 1;
+    failures.last_failed = Some(now.to_rfc3339());
+    let mut event = format!("Failed login: {} ({} in a row)", reason, failures.count);
+    if failures.count > FREE_LOGIN_ATTEMPTS {
+        let seconds = lockout_seconds(failures.count);
+        failures.locked_until = Some((now + Duration::seconds(seconds)).to_rfc3339());
+        event.push_str(&format!(", locked for {}", describe_wait(seconds)));
+    }
+    db.record_audit(username, &event);
+}
+
+// Forget failures that are a day old for every name alike, so an account can't be
+// told apart by a count that outlives an unknown name's, then cap the unknown names
+fn prune_failed_logins(db: &mut Database, now: DateTime<Local>) {
+    let since_last = |failures: &FailedLogins| {
+        failures
+            .last_failed
+            .as_deref()
+            .and_then(|last| DateTime::parse_from_rfc3339(last).ok())
+            .map(|last| (now - last.with_timezone(&Local)).num_seconds())
+    };
+    db.failed_logins.retain(|_, failures| since_last(failures).is_none_or(|seconds| seconds < FORGET_FAILURES_SECS));
+
+    let mut unknown: Vec<(String, Option<i64>)> = db.failed_logins
+        .iter()
+        .filter(|(key, _)| key.starts_with(UNKNOWN_USER_PREFIX))
+        .map(|(key, failures)| (key.clone(), since_last(failures)))
+        .collect();
+    if unknown.len() > MAX_UNKNOWN_USER_ENTRIES {
+        // Entries from older files have no timestamp and count as the stalest
+        unknown.sort_by_key(|(_, seconds)| std::cmp::Reverse(seconds.unwrap_or(i64::MAX)));
+        for (key, _) in unknown.drain(..unknown.len() - MAX_UNKNOWN_USER_ENTRIES) {
+            db.failed_logins.remove(&key);
+        }
+    }
+}
+
+// Checked against for names without an account, so they aren't answered faster
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| crypto::hash_password("no such account").unwrap_or_default())
+}
+
+// Where failures for a name without an account are counted
+const UNKNOWN_USER_PREFIX: &str = "unknown:";
+
+fn unknown_user_key(username: &str) -> String {
+    format!("{}{}", UNKNOWN_USER_PREFIX, username)
+}
+
+fn clear_failed_logins(db: &mut Database, user_id: &str, username: &str) {
+    if let Some(failures) = db.failed_logins.remove(user_id) {
+        db.record_audit(username, &format!("Logged in after {} failed attempts", failures.count));
+    }
+}
+
+fn two_factor_mut<'a>(db: &'a mut Database, user_id: &str) -> Option<&'a mut TwoFactor> {
+    db.users
+        .values_mut()
+        .find(|user| user.user_id == user_id)
+        .and_then(|user| user.two_factor.as_mut())
+}
+
+// Seconds left on an account's lockout, if it is locked
+fn lockout_remaining(db: &Database, user_id: &str, now: DateTime<Local>) -> Option<i64> {
+    let locked_until = db.failed_logins.get(user_id)?.locked_until.as_ref()?;
+    let locked_until = DateTime::parse_from_rfc3339(locked_until).ok()?;
+    let seconds = (locked_until.with_timezone(&Local) - now).num_seconds();
+    (seconds > 0).then_some(seconds)
+}
+
+fn lockout_seconds(failures: u32) -> i64 {
+    let doublings = (failures - FREE_LOGIN_ATTEMPTS - 1).min(16);
+    (BASE_LOCKOUT_SECS << doublings).min(MAX_LOCKOUT_SECS)
+}
+
+fn describe_wait(seconds: i64) -> String {
+    if seconds < 60 {
+        format!("{} seconds", seconds)
+    } else {
+        let minutes = (seconds + 59) / 60;
+        format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+    }
+}
+
+// Check the password of an already known user. Returns their username.
+fn reauthenticate(db: &Database, user_id: &str, password: &str) -> Result<String, String> {
+    let user = db.find_user_by_id(user_id).ok_or_else(|| "User not found".to_string())?;
+    if password.is_empty() {
+        return Err("Please enter your current password".to_string());
+    }
+    if !crypto::verify_password(password, &user.password) {
+        return Err("Current password is incorrect".to_string());
+    }
+    Ok(user.username.clone())
+}
+
+// Range messages use the profile's own units
+pub fn validate_profile(profile: &UserProfile) -> Result<(), String> {
+    units::check_height(profile.height_cm, profile.units)?;
+    units::check_age(profile.age)?;
+    units::check_weight(profile.weight_kg, profile.units, "Weight")?;
+    if let Some(goal) = &profile.weight_goal {
+        units::check_weight(goal.target_weight_kg, profile.units, "Goal weight")?;
+    }
+
+    validate_weight_goal(&profile.weight_goal, Local::now().date_naive())
+}
+
+pub fn validate_weight_goal(goal: &Option<WeightGoal>, today: NaiveDate) -> Result<(), String> {
+    match goal {
+        None => Ok(()),
+        Some(WeightGoal { target_weight_kg, .. }) if !target_weight_kg.is_finite() || *target_weight_kg <= 0.0 => {
+            Err("Goal weight must be a positive number".to_string())
+        }
+        Some(WeightGoal { pace: GoalPace::TargetDate(date), .. }) => {
+            match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+                Ok(date) if date > today => Ok(()),
+                Ok(_) => Err("Goal date must be in the future".to_string()),
+                Err(_) => Err("Goal date must be in YYYY-MM-DD format".to_string()),
+            }
+        }
+        Some(WeightGoal { pace: GoalPace::WeeklyRate(rate), .. }) if !rate.is_finite() || *rate <= 0.0 => {
+            Err("Weekly rate must be a positive number".to_string())
+        }
+        Some(WeightGoal { pace: GoalPace::WeeklyRate(_), .. }) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActivityLevel, CalorieCalculationMethod, Gender, Meal};
+    use crate::service::{logging, templates};
+    use crate::units::Units;
+
+    fn setup() -> (Database, UndoManager, String) {
+        let mut db = Database::default();
+        let profile = UserProfile {
+            gender: Gender::Male,
+            height_cm: 180.0,
+            age: 40,
+            calorie_method: CalorieCalculationMethod::MifflinStJeor,
+            weight_kg: 80.0,
+            activity_level: ActivityLevel::Moderate,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        };
+        let user_id = register(&mut db, "bob", "hunter2", profile).unwrap();
+        let mut undo = UndoManager::with_log_path(10, None);
+        undo.initialize(db.clone());
+        (db, undo, user_id)
+    }
+
+    #[test]
+    fn change_password_needs_the_current_one() {
+        let (mut db, mut undo, user_id) = setup();
+        logging::add_entry(&mut db, &mut undo, &user_id, "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+
+        assert!(change_password(&mut db, &mut undo, &user_id, "wrong", "new-secret").is_err());
+        change_password(&mut db, &mut undo, &user_id, "hunter2", "new-secret").unwrap();
+
+        assert!(authenticate(&mut db, "bob", "hunter2").is_err());
+        assert_eq!(authenticate(&mut db, "bob", "new-secret").unwrap(), user_id);
+        // Undoing the earlier log entry must not bring the old password back
+        assert!(!undo.can_undo());
+    }
+
+    #[test]
+    fn rename_keeps_the_user_id_and_data() {
+        let (mut db, mut undo, user_id) = setup();
+        logging::add_entry(&mut db, &mut undo, &user_id, "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+        let profile = db.find_user_by_id(&user_id).unwrap().profile.clone();
+        register(&mut db, "carol", "pw", profile).unwrap();
+
+        assert!(rename_user(&mut db, &mut undo, &user_id, "hunter2", "carol").is_err());
+        rename_user(&mut db, &mut undo, &user_id, "hunter2", "  robert ").unwrap();
+
+        assert!(!db.users.contains_key("bob"));
+        assert_eq!(db.users["robert"].username, "robert");
+        assert_eq!(authenticate(&mut db, "robert", "hunter2").unwrap(), user_id);
+        assert_eq!(logging::entries_for_day(&db, &user_id, "2025-03-01").len(), 1);
+    }
+
+    #[test]
+    fn profiles_outside_sane_ranges_are_refused_in_their_own_units() {
+        let (mut db, mut undo, user_id) = setup();
+        let mut profile = db.find_user_by_id(&user_id).unwrap().profile.clone();
+
+        profile.weight_kg = 0.0;
+        assert_eq!(update_profile(&mut db, &mut undo, &user_id, profile.clone()), Err("Weight must be between 30 and 250 kg".to_string()));
+        profile.units = Units::Imperial;
+        assert_eq!(update_profile(&mut db, &mut undo, &user_id, profile.clone()), Err("Weight must be between 67 and 551 lb".to_string()));
+
+        profile.weight_kg = 80.0;
+        profile.age = 0;
+        assert!(update_profile(&mut db, &mut undo, &user_id, profile.clone()).is_err());
+        profile.age = 40;
+        profile.weight_goal = Some(WeightGoal { target_weight_kg: 10.0, pace: GoalPace::WeeklyRate(0.5) });
+        assert!(update_profile(&mut db, &mut undo, &user_id, profile.clone()).is_err());
+
+        profile.weight_goal = None;
+        update_profile(&mut db, &mut undo, &user_id, profile).unwrap();
+        assert_eq!(db.find_user_by_id(&user_id).unwrap().profile.units, Units::Imperial);
+    }
+
+    #[test]
+    fn unknown_user_ids_have_no_write_access() {
+        let (mut db, mut undo, user_id) = setup();
+        assert_eq!(require_write_access(&db, &user_id), Ok(()));
+        assert!(require_write_access(&db, "no-such-id").is_err());
+        assert!(require_write_access(&db, "").is_err());
+        assert!(logging::add_entry(&mut db, &mut undo, "no-such-id", "2025-03-01", "apple", 1.0, Meal::Lunch).is_err());
+    }
+
+    #[test]
+    fn weight_goals_must_be_real_positive_numbers() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let goal = |target_weight_kg: f32, pace: GoalPace| Some(WeightGoal { target_weight_kg, pace });
+        assert!(validate_weight_goal(&None, today).is_ok());
+        assert!(validate_weight_goal(&goal(70.0, GoalPace::WeeklyRate(0.5)), today).is_ok());
+        assert!(validate_weight_goal(&goal(f32::NAN, GoalPace::WeeklyRate(0.5)), today).is_err());
+        assert!(validate_weight_goal(&goal(70.0, GoalPace::WeeklyRate(f32::NAN)), today).is_err());
+        assert!(validate_weight_goal(&goal(70.0, GoalPace::WeeklyRate(f32::INFINITY)), today).is_err());
+    }
+
+    #[test]
+    fn unknown_users_and_wrong_passwords_get_the_same_error() {
+        let (mut db, _, _) = setup();
+
+        let unknown = authenticate(&mut db, "nobody", "hunter2").unwrap_err();
+        let wrong = authenticate(&mut db, "bob", "wrong").unwrap_err();
+        assert_eq!(unknown, wrong);
+        assert_eq!(db.audit_log.len(), 2);
+        assert_eq!(db.audit_log[0].username, "nobody");
+    }
+
+    #[test]
+    fn unknown_usernames_are_locked_out_like_real_ones() {
+        let (mut db, _, _) = setup();
+        let start = Local::now();
+
+        // Every attempt, including the ones refused by the lockout, reads the same
+        for attempt in 0..FREE_LOGIN_ATTEMPTS + 3 {
+            let now = start + Duration::seconds(attempt as i64);
+            let unknown = authenticate_at(&mut db, "nobody", "guess", now).unwrap_err();
+            let wrong = authenticate_at(&mut db, "bob", "guess", now).unwrap_err();
+            assert_eq!(unknown, wrong);
+        }
+        let locked = authenticate_at(&mut db, "nobody", "guess", start + Duration::seconds(10)).unwrap_err();
+        assert!(locked.starts_with("Too many failed attempts"));
+
+        // Registering the name starts the new account with a clean slate
+        let profile = db.users["bob"].profile.clone();
+        let user_id = register(&mut db, "nobody", "secret", profile).unwrap();
+        assert_eq!(authenticate_at(&mut db, "nobody", "secret", start + Duration::seconds(10)).unwrap(), user_id);
+    }
+
+    #[test]
+    fn failures_for_unknown_names_are_forgotten_and_capped() {
+        let (mut db, _, user_id) = setup();
+        let start = Local::now();
+        authenticate_at(&mut db, "bob", "wrong", start).unwrap_err();
+        authenticate_at(&mut db, "nobody", "guess", start).unwrap_err();
+
+        // A day later both counts are gone, so neither name stands out
+        let next_day = start + Duration::seconds(FORGET_FAILURES_SECS);
+        authenticate_at(&mut db, "somebody", "guess", next_day).unwrap_err();
+        assert!(!db.failed_logins.contains_key(&user_id));
+        assert!(!db.failed_logins.contains_key(&unknown_user_key("nobody")));
+
+        for n in 0..MAX_UNKNOWN_USER_ENTRIES + 5 {
+            let now = next_day + Duration::seconds(n as i64 + 1);
+            db.failed_logins.insert(
+                unknown_user_key(&format!("name{}", n)),
+                FailedLogins { count: 1, locked_until: None, last_failed: Some(now.to_rfc3339()) },
+            );
+        }
+        authenticate_at(&mut db, "bob", "wrong", next_day + Duration::seconds(2000)).unwrap_err();
+        assert_eq!(db.failed_logins.len(), MAX_UNKNOWN_USER_ENTRIES + 1);
+        assert!(!db.failed_logins.contains_key(&unknown_user_key("somebody")));
+        assert!(db.failed_logins.contains_key(&unknown_user_key(&format!("name{}", MAX_UNKNOWN_USER_ENTRIES + 4))));
+    }
+
+    #[test]
+    fn usernames_are_registered_without_surrounding_spaces() {
+        let (mut db, _, _) = setup();
+        let profile = db.users["bob"].profile.clone();
+        assert_eq!(register(&mut db, "  bob ", "other", profile.clone()).unwrap_err(), "Username already exists.");
+        assert!(register(&mut db, "   ", "other", profile.clone()).is_err());
+        register(&mut db, " carol ", "secret", profile).unwrap();
+        assert_eq!(db.users["carol"].username, "carol");
+    }
+
+    #[test]
+    fn repeated_failures_lock_the_account_for_longer_each_time() {
+        let (mut db, _, user_id) = setup();
+        let start = Local::now();
+
+        for _ in 0..FREE_LOGIN_ATTEMPTS {
+            assert!(authenticate_at(&mut db, "bob", "wrong", start).is_err());
+        }
+        assert!(db.failed_logins[&user_id].locked_until.is_none());
+
+        // The next failure locks the account, even against the right password
+        authenticate_at(&mut db, "bob", "wrong", start).unwrap_err();
+        let locked = authenticate_at(&mut db, "bob", "hunter2", start + Duration::seconds(10)).unwrap_err();
+        assert!(locked.starts_with("Too many failed attempts"));
+        assert_eq!(lockout_remaining(&db, &user_id, start), Some(BASE_LOCKOUT_SECS));
+
+        // Failing again after it expires doubles the wait
+        let later = start + Duration::seconds(BASE_LOCKOUT_SECS + 1);
+        authenticate_at(&mut db, "bob", "wrong", later).unwrap_err();
+        assert_eq!(lockout_remaining(&db, &user_id, later), Some(BASE_LOCKOUT_SECS * 2));
+        assert_eq!(lockout_seconds(40), MAX_LOCKOUT_SECS);
+
+        // A successful login clears the count
+        let after = later + Duration::seconds(BASE_LOCKOUT_SECS * 2 + 1);
+        assert_eq!(authenticate_at(&mut db, "bob", "hunter2", after).unwrap(), user_id);
+        assert!(!db.failed_logins.contains_key(&user_id));
+        assert!(db.audit_log.last().unwrap().event.starts_with("Logged in after 5 failed attempts"));
+    }
+
+    fn enroll(db: &mut Database, undo: &mut UndoManager, user_id: &str) -> (String, Vec<String>) {
+        let (secret, uri) = new_two_factor_secret(db, user_id);
+        assert!(uri.contains(&secret) && uri.contains(":bob?"));
+        let code = totp::code_for_step(&secret, totp::step_at(Local::now().timestamp() as u64)).unwrap();
+        let recovery_codes = enable_two_factor(db, undo, user_id, "hunter2", &secret, &code).unwrap();
+        (secret, recovery_codes)
+    }
+
+    #[test]
+    fn two_factor_codes_are_checked_after_the_password() {
+        let (mut db, mut undo, user_id) = setup();
+        let (secret, recovery_codes) = enroll(&mut db, &mut undo, &user_id);
+        assert_eq!(recovery_codes.len(), RECOVERY_CODE_COUNT);
+        assert!(two_factor_enabled(&db, &user_id));
+
+        // The code used to turn it on can't be replayed
+        let now = Local::now();
+        let current = totp::code_for_step(&secret, totp::step_at(now.timestamp() as u64)).unwrap();
+        assert!(verify_second_factor_at(&mut db, &user_id, &current, now).is_err());
+
+        let later = now + Duration::seconds(totp::STEP_SECONDS as i64 * 2);
+        let next = totp::code_for_step(&secret, totp::step_at(later.timestamp() as u64)).unwrap();
+        verify_second_factor_at(&mut db, &user_id, &next, later).unwrap();
+        assert!(verify_second_factor_at(&mut db, &user_id, &next, later).is_err());
+    }
+
+    #[test]
+    fn recovery_codes_work_once() {
+        let (mut db, mut undo, user_id) = setup();
+        let (_, recovery_codes) = enroll(&mut db, &mut undo, &user_id);
+
+        let code = recovery_codes[0].to_uppercase();
+        verify_second_factor(&mut db, &user_id, &code).unwrap();
+        assert!(verify_second_factor(&mut db, &user_id, &code).is_err());
+        assert_eq!(db.find_user_by_id(&user_id).unwrap().two_factor.as_ref().unwrap().recovery_codes.len(), RECOVERY_CODE_COUNT - 1);
+    }
+
+    #[test]
+    fn wrong_codes_count_towards_the_lockout() {
+        let (mut db, mut undo, user_id) = setup();
+        enroll(&mut db, &mut undo, &user_id);
+
+        // A right password alone doesn't reset the count
+        for _ in 0..=FREE_LOGIN_ATTEMPTS {
+            authenticate(&mut db, "bob", "hunter2").unwrap();
+            assert_eq!(verify_second_factor(&mut db, &user_id, "not-a-code").unwrap_err(), "Invalid verification code");
+        }
+        assert!(authenticate(&mut db, "bob", "hunter2").unwrap_err().starts_with("Too many failed attempts"));
+    }
+
+    #[test]
+    fn turning_two_factor_off_needs_the_password() {
+        let (mut db, mut undo, user_id) = setup();
+        enroll(&mut db, &mut undo, &user_id);
+
+        assert!(disable_two_factor(&mut db, &mut undo, &user_id, "wrong").is_err());
+        disable_two_factor(&mut db, &mut undo, &user_id, "hunter2").unwrap();
+        assert!(!two_factor_enabled(&db, &user_id));
+    }
+
+    #[test]
+    fn delete_account_removes_all_of_the_users_data() {
+        let (mut db, mut undo, user_id) = setup();
+        logging::add_entry(&mut db, &mut undo, &user_id, "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+        templates::save_template(&mut db, &mut undo, &user_id, "Lunch", "2025-03-01", None).unwrap();
+
+        assert!(delete_account(&mut db, &mut undo, &user_id, "wrong").is_err());
+        delete_account(&mut db, &mut undo, &user_id, "hunter2").unwrap();
+
+        assert!(db.users.is_empty());
+        assert!(db.current_user.is_empty());
+        assert!(!db.food_logs.contains_key(&user_id));
+        assert!(!db.trackers.contains_key(&user_id));
+        assert!(!db.meal_templates.contains_key(&user_id));
+        assert!(!undo.can_undo());
+    }
+}
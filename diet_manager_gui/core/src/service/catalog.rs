@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use crate::models::{Database, BasicFood, CompositeFood, FoodComponent, Meal, parse_keywords, plain_food_id, private_food_key, validate_food_id};
+use crate::import::{self, ConflictStrategy, ImportedFood, ImportReport};
+use crate::search;
+use crate::service::auth;
+use crate::undo::UndoManager;
+
+// A basic or composite food as shown in food pickers
+#[derive(Serialize, Debug, Clone)]
+pub struct FoodMatch {
+    pub id: String,
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub calories_per_serving: f32,
+    pub is_composite: bool,
+    pub is_private: bool,
+}
+
+pub fn add_basic_food(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    id: &str,
+    name: &str,
+    keywords: &str,
+    calories: &str
+) -> Result<String, String> {
+    auth::require_write_access(db, user_id)?;
+    let mut food = BasicFood::from_input(id, name, keywords, calories)?;
+    food.id = new_food_key(db, user_id, &food.id)?;
+    food.owner = owner_of_new_food(user_id);
+
+    let key = food.id.clone();
+    let description = format!("Added basic food {}", food.name);
+    db.basic_foods.insert(key.clone(), food);
+
+    undo.record_action(db.clone(), &description);
+    Ok(key)
+}
+
+pub fn add_composite_food(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    id: &str,
+    name: &str,
+    keywords: &str,
+    components: Vec<FoodComponent>
+) -> Result<String, String> {
+    auth::require_write_access(db, user_id)?;
+    let key = new_food_key(db, user_id, id.trim())?;
+
+    if name.trim().is_empty() {
+        return Err("Food Name cannot be empty".to_string());
+    }
+
+    let keywords = parse_keywords(keywords);
+    if keywords.is_empty() {
+        return Err("Please provide at least one valid keyword".to_string());
+    }
+
+    if components.is_empty() {
+        return Err("At least one component is required".to_string());
+    }
+
+    for component in &components {
+        if !db.food_visible_to(&component.food_id, user_id) {
+            return Err(format!("Unknown component food '{}'", component.food_id));
+        }
+        if !(component.servings > 0.0 && component.servings.is_finite()) {
+            return Err("Please enter a valid number of servings (must be positive)".to_string());
+        }
+    }
+
+    let food = CompositeFood {
+        id: key.clone(),
+        name: name.trim().to_string(),
+        keywords,
+        components,
+        owner: owner_of_new_food(user_id),
+    };
+
+    let description = format!("Added composite food {}", food.name);
+    db.composite_foods.insert(key.clone(), food);
+
+    undo.record_action(db.clone(), &description);
+    Ok(key)
+}
+
+// Foods added by a user start out private to them. Without a user (the CLI
+// operator) they go straight into the shared catalog.
+fn owner_of_new_food(user_id: &str) -> Option<String> {
+    (!user_id.is_empty()).then(|| user_id.to_string())
+}
+
+// The key a new food is stored under. The identifier may not clash with the
+// user's own foods or the shared catalog; other users' private foods don't matter.
+fn new_food_key(db: &Database, user_id: &str, id: &str) -> Result<String, String> {
+    validate_food_id(id)?;
+    if db.resolve_food_id(user_id, id).is_some() {
+        return Err("A food with this identifier already exists".to_string());
+    }
+    Ok(match owner_of_new_food(user_id) {
+        Some(owner) => private_food_key(&owner, id),
+        None => id.to_string(),
+    })
+}
+
+// Move a private food into the shared catalog under its plain identifier, so
+// everyone can find and log it. Allowed for its owner and for admins. A
+// composite food can only be shared once all of its components are. Returns
+// the food's new key.
+pub fn share_food(db: &mut Database, undo: &mut UndoManager, user_id: &str, food_id: &str) -> Result<String, String> {
+    auth::require_write_access(db, user_id)?;
+    let owner = match db.food_owner(food_id) {
+        Some(Some(owner)) => owner.to_string(),
+        Some(None) => return Err("This food is already shared".to_string()),
+        None => return Err(format!("Food '{}' not found", food_id)),
+    };
+    if owner != user_id && !auth::is_admin(db, user_id) {
+        return Err(format!("Food '{}' not found", food_id));
+    }
+
+    let shared_id = plain_food_id(food_id).to_string();
+    if db.food_exists(&shared_id) {
+        return Err(format!("The shared catalog already has a food called '{}'", shared_id));
+    }
+    if let Some(food) = db.composite_foods.get(food_id) {
+        if let Some(private) = food.components.iter().find(|c| db.food_owner(&c.food_id) != Some(None)) {
+            return Err(format!("Share '{}' first; it is part of this food", db.food_name(&private.food_id)));
+        }
+    }
+
+    let name = db.food_name(food_id);
+    if let Some(mut food) = db.basic_foods.remove(food_id) {
+        food.id = shared_id.clone();
+        food.owner = None;
+        db.basic_foods.insert(shared_id.clone(), food);
+    } else if let Some(mut food) = db.composite_foods.remove(food_id) {
+        food.id = shared_id.clone();
+        food.owner = None;
+        db.composite_foods.insert(shared_id.clone(), food);
+    }
+    rename_food_references(db, food_id, &shared_id);
+
+    undo.record_action(db.clone(), &format!("Shared {} with everyone", name));
+    Ok(shared_id)
+}
+
+// Point logs, recipes, templates and shopping lists at a food's new key
+fn rename_food_references(db: &mut Database, old_id: &str, new_id: &str) {
+    let rename = |id: &mut String| {
+        if id == old_id {
+            *id = new_id.to_string();
+        }
+    };
+    db.food_logs.values_mut().flatten().for_each(|entry| rename(&mut entry.food_id));
+    db.composite_foods
+        .values_mut()
+        .flat_map(|food| food.components.iter_mut())
+        .for_each(|component| rename(&mut component.food_id));
+    db.meal_templates
+        .values_mut()
+        .flatten()
+        .flat_map(|template| template.items.iter_mut())
+        .for_each(|item| rename(&mut item.food_id));
+    db.shopping_checked.values_mut().flatten().for_each(rename);
+}
+
+// A user's own private foods, by name
+pub fn private_foods(db: &Database, user_id: &str) -> Vec<FoodMatch> {
+    let mut foods: Vec<FoodMatch> = search_foods(db, Some(user_id), "", false)
+        .into_iter()
+        .filter(|food| food.is_private)
+        .collect();
+    foods.sort_by(|a, b| a.name.cmp(&b.name));
+    foods
+}
+
+// How often and how lately a user ate a food (plans don't count)
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct FoodUsage {
+    pub food_id: String,
+    pub times_logged: usize,
+    pub last_logged: String, // YYYY-MM-DD
+    pub last_servings: f32,  // Servings of the latest entry, used for quick-add
+    pub last_meal: Meal,
+}
+
+pub fn food_usage(db: &Database, user_id: &str) -> HashMap<String, FoodUsage> {
+    let mut usage: HashMap<String, FoodUsage> = HashMap::new();
+    for entry in db.food_logs.get(user_id).into_iter().flatten().filter(|entry| !entry.planned) {
+        let food = usage.entry(entry.food_id.clone()).or_insert_with(|| FoodUsage {
+            food_id: entry.food_id.clone(),
+            ..FoodUsage::default()
+        });
+        food.times_logged += 1;
+        // Later entries win ties, so the most recently added one on a day counts
+        if entry.date >= food.last_logged {
+            food.last_logged = entry.date.clone();
+            food.last_servings = entry.servings;
+            food.last_meal = entry.meal;
+        }
+    }
+    usage
+}
+
+// Foods the user logged most lately, newest first. Deleted foods are left out.
+pub fn recent_foods(db: &Database, user_id: &str, limit: usize) -> Vec<FoodUsage> {
+    let mut foods = known_usage(db, user_id);
+    foods.sort_by(|a, b| b.last_logged.cmp(&a.last_logged).then_with(|| b.times_logged.cmp(&a.times_logged)));
+    foods.truncate(limit);
+    foods
+}
+
+// Foods the user logged most often, most logged first
+pub fn frequent_foods(db: &Database, user_id: &str, limit: usize) -> Vec<FoodUsage> {
+    let mut foods = known_usage(db, user_id);
+    foods.sort_by(|a, b| b.times_logged.cmp(&a.times_logged).then_with(|| b.last_logged.cmp(&a.last_logged)));
+    foods.truncate(limit);
+    foods
+}
+
+fn known_usage(db: &Database, user_id: &str) -> Vec<FoodUsage> {
+    food_usage(db, user_id)
+        .into_values()
+        .filter(|food| db.food_exists(&food.food_id))
+        .collect()
+}
+
+// Basic and composite foods matching the query, best match first (see search::score).
+// With a user, their private foods are included and foods they log often or
+// logged lately rank higher; without one only the shared catalog is searched.
+// An empty query lists every food, most used first, then by name.
+pub fn search_foods(db: &Database, user_id: Option<&str>, query: &str, match_all: bool) -> Vec<FoodMatch> {
+    let terms = search::tokenize(query);
+    let usage = user_id.map(|user_id| food_usage(db, user_id)).unwrap_or_default();
+    let today = Local::now().date_naive();
+
+    let boost = |food_id: &str| {
+        usage.get(food_id).map_or(0.0, |food| {
+            let days_since = NaiveDate::parse_from_str(&food.last_logged, "%Y-%m-%d")
+                .ok()
+                .map(|date| (today - date).num_days());
+            search::usage_boost(food.times_logged, days_since)
+        })
+    };
+
+    let visible = |owner: &Option<String>| owner.is_none() || owner.as_deref() == user_id;
+    let basic = db.basic_foods.values().filter(|food| visible(&food.owner)).map(|food| {
+        (food.id.as_str(), food.name.as_str(), &food.keywords, food.calories_per_serving, false, food.owner.is_some())
+    });
+    let composite = db.composite_foods.values().filter(|food| visible(&food.owner)).map(|food| {
+        (food.id.as_str(), food.name.as_str(), &food.keywords, db.get_food_calories(&food.id).unwrap_or(0.0), true, food.owner.is_some())
+    });
+
+    let mut ranked: Vec<(f32, FoodMatch)> = basic
+        .chain(composite)
+        .filter_map(|(id, name, keywords, calories, is_composite, is_private)| {
+            let document = search::Document { id: plain_food_id(id), name, keywords };
+            let relevance = search::score(&terms, &document, match_all)?;
+            let food = FoodMatch {
+                id: id.to_string(),
+                name: name.to_string(),
+                keywords: keywords.clone(),
+                calories_per_serving: calories,
+                is_composite,
+                is_private,
+            };
+            Some((relevance + boost(id), food))
+        })
+        .collect();
+
+    ranked.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.total_cmp(a_score).then_with(|| a.name.cmp(&b.name))
+    });
+    ranked.into_iter().map(|(_, food)| food).collect()
+}
+
+// Where a user's imports go: admins fill the shared catalog, everyone else
+// gets private foods, like the ones they add by hand
+pub fn import_owner(db: &Database, user_id: &str) -> Option<String> {
+    (!auth::is_admin(db, user_id)).then(|| user_id.to_string())
+}
+
+// Merge a batch of imported foods as a single undoable action. Members may only
+// overwrite their own private foods.
+pub fn import_foods(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    foods: &[ImportedFood],
+    strategy: ConflictStrategy,
+    source: &str
+) -> Result<ImportReport, String> {
+    auth::require_write_access(db, user_id)?;
+    let owner = import_owner(db, user_id);
+    if let Some(owner) = &owner {
+        let replaces_shared = strategy == ConflictStrategy::Overwrite
+            && foods.iter().any(|food| db.resolve_food_id(owner, food.id()).is_some_and(|key| db.food_visible_to(&key, "")));
+        if replaces_shared {
+            return Err("Only admins can overwrite foods that are already in the catalog".to_string());
+        }
+    }
+
+    let report = import::apply_import(db, foods, strategy, owner.as_deref());
+
+    if report.changed() {
+        undo.record_action(db.clone(), &format!("Imported foods from {}", source));
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActivityLevel, CalorieCalculationMethod, Gender, UserProfile};
+    use crate::service::logging;
+    use crate::units::Units;
+
+    fn profile() -> UserProfile {
+        UserProfile {
+            gender: Gender::Male,
+            height_cm: 180.0,
+            age: 30,
+            calorie_method: CalorieCalculationMethod::MifflinStJeor,
+            weight_kg: 80.0,
+            activity_level: ActivityLevel::Moderate,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        }
+    }
+
+    // An admin (the first account) and two members
+    fn setup() -> (Database, UndoManager, String, String, String) {
+        let mut db = Database::default();
+        let admin_id = auth::register(&mut db, "ada", "admin-pass", profile()).unwrap();
+        let max_id = auth::register(&mut db, "max", "member-pass", profile()).unwrap();
+        let sam_id = auth::register(&mut db, "sam", "member-pass", profile()).unwrap();
+        let mut undo = UndoManager::with_log_path(10, None);
+        undo.initialize(db.clone());
+        (db, undo, admin_id, max_id, sam_id)
+    }
+
+    fn ids(foods: &[FoodMatch]) -> Vec<&str> {
+        foods.iter().map(|food| food.id.as_str()).collect()
+    }
+
+    #[test]
+    fn private_foods_are_only_visible_to_their_owner() {
+        let (mut db, mut undo, _, max_id, sam_id) = setup();
+        let max_food = add_basic_food(&mut db, &mut undo, &max_id, "lasagna", "Mom's Lasagna", "dinner", "600").unwrap();
+        // The same identifier is free for another user, but not twice for the same one
+        let sam_food = add_basic_food(&mut db, &mut undo, &sam_id, "lasagna", "Lasagna", "dinner", "500").unwrap();
+        assert!(add_basic_food(&mut db, &mut undo, &max_id, "lasagna", "Again", "dinner", "1").is_err());
+        assert!(add_basic_food(&mut db, &mut undo, &max_id, "apple", "My Apple", "fruit", "1").is_err());
+        assert!(add_basic_food(&mut db, &mut undo, &max_id, "a/b", "Slash", "fruit", "1").is_err());
+        assert_ne!(max_food, sam_food);
+
+        assert_eq!(ids(&search_foods(&db, Some(&max_id), "lasagna", false)), vec![max_food.as_str()]);
+        assert!(search_foods(&db, Some(&max_id), "lasagna", false)[0].is_private);
+        assert!(search_foods(&db, None, "lasagna", false).is_empty());
+        assert_eq!(ids(&private_foods(&db, &sam_id)), vec![sam_food.as_str()]);
+
+        // Plain identifiers resolve to the user's own food; other users' keys are refused
+        logging::add_entry(&mut db, &mut undo, &max_id, "2025-03-01", "lasagna", 1.0, Meal::Dinner).unwrap();
+        assert_eq!(db.food_logs[&max_id][0].food_id, max_food);
+        assert!(logging::add_entry(&mut db, &mut undo, &max_id, "2025-03-01", &sam_food, 1.0, Meal::Dinner).is_err());
+
+        // Composite foods can use the owner's private foods only
+        let components = |food_id: &str| vec![FoodComponent { food_id: food_id.to_string(), servings: 1.0 }];
+        assert!(add_composite_food(&mut db, &mut undo, &max_id, "feast", "Feast", "dinner", components(&sam_food)).is_err());
+        add_composite_food(&mut db, &mut undo, &max_id, "feast", "Feast", "dinner", components(&max_food)).unwrap();
+
+        db.remove_user_data(&max_id);
+        assert!(!db.food_exists(&max_food));
+        assert!(db.food_exists(&sam_food));
+    }
+
+    #[test]
+    fn sharing_moves_a_food_and_its_references_into_the_catalog() {
+        let (mut db, mut undo, admin_id, max_id, sam_id) = setup();
+        let sauce = add_basic_food(&mut db, &mut undo, &max_id, "sauce", "Tomato Sauce", "sauce", "80").unwrap();
+        let sam_sauce = add_basic_food(&mut db, &mut undo, &sam_id, "sauce", "Pesto", "sauce", "90").unwrap();
+        let components = vec![FoodComponent { food_id: sauce.clone(), servings: 2.0 }];
+        let pasta = add_composite_food(&mut db, &mut undo, &max_id, "pasta", "Pasta", "dinner", components).unwrap();
+        logging::add_entry(&mut db, &mut undo, &max_id, "2025-03-01", &pasta, 1.0, Meal::Dinner).unwrap();
+
+        // Other members can't share it, and a recipe waits for its components
+        assert!(share_food(&mut db, &mut undo, &sam_id, &sauce).is_err());
+        assert!(share_food(&mut db, &mut undo, &max_id, &pasta).is_err());
+
+        assert_eq!(share_food(&mut db, &mut undo, &max_id, &sauce).unwrap(), "sauce");
+        assert_eq!(share_food(&mut db, &mut undo, &admin_id, &pasta).unwrap(), "pasta");
+        assert!(share_food(&mut db, &mut undo, &max_id, "pasta").is_err());
+
+        assert_eq!(db.composite_foods["pasta"].components[0].food_id, "sauce");
+        assert_eq!(db.food_logs[&max_id][0].food_id, "pasta");
+        assert_eq!(db.calories_consumed(&max_id, "2025-03-01"), 160.0);
+        assert_eq!(ids(&search_foods(&db, Some(&sam_id), "pasta", false)), vec!["pasta"]);
+        assert!(private_foods(&db, &max_id).is_empty());
+
+        // The shared identifier is taken now, so another user's "sauce" stays private
+        assert!(share_food(&mut db, &mut undo, &sam_id, &sam_sauce).is_err());
+        assert_eq!(db.resolve_food_id(&sam_id, "sauce"), Some(sam_sauce));
+    }
+
+    #[test]
+    fn members_import_private_foods_and_admins_shared_ones() {
+        let (mut db, mut undo, admin_id, max_id, sam_id) = setup();
+        let file = r#"{
+            "basic_foods": [{"id": "kiwi", "name": "Kiwi", "keywords": ["fruit"], "calories_per_serving": 42.0}],
+            "composite_foods": [{"id": "kiwi_salad", "name": "Kiwi Salad", "keywords": ["salad"],
+                "components": [{"food_id": "kiwi", "servings": 2.0}, {"food_id": "apple", "servings": 1.0}]}]
+        }"#;
+        let preview = import::parse_json(file, &db);
+        assert!(preview.errors.is_empty());
+
+        let report = import_foods(&mut db, &mut undo, &max_id, &preview.foods, ConflictStrategy::Skip, "file").unwrap();
+        assert_eq!(report.added, 2);
+        let salad = db.resolve_food_id(&max_id, "kiwi_salad").unwrap();
+        assert_eq!(salad, private_food_key(&max_id, "kiwi_salad"));
+        assert_eq!(db.composite_foods[&salad].components[0].food_id, private_food_key(&max_id, "kiwi"));
+        assert_eq!(db.get_food_calories(&salad), Some(42.0 * 2.0 + 95.0));
+        assert!(!db.food_exists("kiwi"));
+        assert_eq!(db.resolve_food_id(&sam_id, "kiwi"), None);
+
+        // Clashes are with the member's own foods; shared ones stay out of reach
+        assert_eq!(preview.conflicts(&db, Some(&max_id)), vec!["kiwi", "kiwi_salad"]);
+        let report = import_foods(&mut db, &mut undo, &max_id, &preview.foods, ConflictStrategy::Rename, "file").unwrap();
+        assert_eq!(report.renamed[0], ("kiwi".to_string(), "kiwi_2".to_string()));
+        import_foods(&mut db, &mut undo, &max_id, &preview.foods, ConflictStrategy::Overwrite, "file").unwrap();
+        let apple = import::parse_csv("apple,Apple,fruit,10");
+        assert!(import_foods(&mut db, &mut undo, &max_id, &apple.foods, ConflictStrategy::Overwrite, "file").is_err());
+
+        // An admin's import fills the shared catalog
+        assert!(preview.conflicts(&db, None).is_empty());
+        import_foods(&mut db, &mut undo, &admin_id, &preview.foods, ConflictStrategy::Skip, "file").unwrap();
+        assert_eq!(db.composite_foods["kiwi_salad"].components[0].food_id, "kiwi");
+        assert_eq!(db.resolve_food_id(&sam_id, "kiwi").as_deref(), Some("kiwi"));
+    }
+}
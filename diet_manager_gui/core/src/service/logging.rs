@@ -0,0 +1,575 @@
+use chrono::{Local, NaiveDate};
+use crate::models::{Database, Exercise, ExerciseLogEntry, FoodLogEntry, HabitTracker, Meal, WATER_TRACKER_ID};
+use crate::service::{admin, auth};
+use crate::undo::UndoManager;
+
+// A user's entries for one date, in the order they were logged
+pub fn entries_for_day(db: &Database, user_id: &str, date: &str) -> Vec<FoodLogEntry> {
+    db.food_logs
+        .get(user_id)
+        .map_or(Vec::new(), |entries| {
+            entries
+                .iter()
+                .filter(|entry| entry.date == date)
+                .cloned()
+                .collect()
+        })
+}
+
+pub fn find_entry<'a>(db: &'a Database, user_id: &str, entry_id: &str) -> Option<&'a FoodLogEntry> {
+    db.food_logs
+        .get(user_id)
+        .and_then(|entries| entries.iter().find(|entry| entry.id == entry_id))
+}
+
+// Log a food that was eaten. Returns the new entry's id.
+pub fn add_entry(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    date: &str,
+    food_id: &str,
+    servings: f32,
+    meal: Meal
+) -> Result<String, String> {
+    auth::require_write_access(db, user_id)?;
+    let food_id = &validate_new_entry(db, user_id, date, food_id, servings)?;
+    if is_future(date) {
+        return Err("Food for a future date can only be planned".to_string());
+    }
+
+    let entry = FoodLogEntry::new(user_id, date, food_id, servings, meal, false);
+    let id = entry.id.clone();
+    db.food_logs.entry(user_id.to_string()).or_default().push(entry);
+
+    undo.record_action(db.clone(), &format!("Added {} to food log", db.food_name(food_id)));
+    Ok(id)
+}
+
+// Plan a food for today or a later date. It counts towards the projected
+// total but not what was eaten until it is marked as eaten. Returns the new entry's id.
+pub fn plan_entry(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    date: &str,
+    food_id: &str,
+    servings: f32,
+    meal: Meal
+) -> Result<String, String> {
+    auth::require_write_access(db, user_id)?;
+    let food_id = &validate_new_entry(db, user_id, date, food_id, servings)?;
+    if date < today().as_str() {
+        return Err("Meals can only be planned for today or later".to_string());
+    }
+
+    let entry = FoodLogEntry::new(user_id, date, food_id, servings, meal, true);
+    let id = entry.id.clone();
+    db.food_logs.entry(user_id.to_string()).or_default().push(entry);
+
+    undo.record_action(db.clone(), &format!("Planned {} for {}", db.food_name(food_id), date));
+    Ok(id)
+}
+
+// Turn a planned entry into a real one. A plan for a later date is logged as eaten today.
+pub fn mark_eaten(db: &mut Database, undo: &mut UndoManager, user_id: &str, entry_id: &str) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    let entry = find_entry_mut(db, user_id, entry_id)?;
+    if !entry.planned {
+        return Err("This entry is already logged as eaten".to_string());
+    }
+
+    entry.planned = false;
+    if is_future(&entry.date) {
+        entry.date = today();
+    }
+    let food_id = entry.food_id.clone();
+
+    undo.record_action(db.clone(), &format!("Marked {} as eaten", db.food_name(&food_id)));
+    Ok(())
+}
+
+pub fn update_servings(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    entry_id: &str,
+    servings: f32
+) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    if !(servings > 0.0 && servings.is_finite()) {
+        return Err("Please enter a valid number of servings".to_string());
+    }
+
+    let entry = find_entry_mut(db, user_id, entry_id)?;
+
+    let old_servings = entry.servings;
+    if old_servings == servings {
+        return Ok(());
+    }
+    entry.servings = servings;
+    let food_id = entry.food_id.clone();
+
+    undo.record_action(
+        db.clone(),
+        &format!(
+            "Changed servings of {} from {:.1} to {:.1}",
+            db.food_name(&food_id), old_servings, servings
+        )
+    );
+    Ok(())
+}
+
+pub fn remove_entry(db: &mut Database, undo: &mut UndoManager, user_id: &str, entry_id: &str) -> Result<FoodLogEntry, String> {
+    auth::require_write_access(db, user_id)?;
+    let entries = db.food_logs
+        .get_mut(user_id)
+        .ok_or_else(|| "Food log entry not found".to_string())?;
+    let index = entries
+        .iter()
+        .position(|entry| entry.id == entry_id)
+        .ok_or_else(|| "Food log entry not found".to_string())?;
+
+    let removed = entries.remove(index);
+
+    undo.record_action(db.clone(), &format!("Removed {} from food log", db.food_name(&removed.food_id)));
+    Ok(removed)
+}
+
+// Log a day's entries, or just one meal's, again on one or more other dates
+// (e.g. yesterday's breakfast today, or a whole day repeated all week) as a
+// single undoable action. Returns how many entries were added.
+pub fn copy_entries(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    from_date: &str,
+    to_dates: &[String],
+    meal: Option<Meal>
+) -> Result<usize, String> {
+    auth::require_write_access(db, user_id)?;
+    validate_date(from_date)?;
+    if to_dates.is_empty() {
+        return Err("Please pick a date to copy to".to_string());
+    }
+    for date in to_dates {
+        validate_date(date)?;
+        if date == from_date {
+            return Err("Cannot copy entries onto the same date".to_string());
+        }
+    }
+
+    let what = meal.map_or("entries".to_string(), |meal| meal.label().to_lowercase());
+    let source: Vec<FoodLogEntry> = entries_for_day(db, user_id, from_date)
+        .into_iter()
+        .filter(|entry| meal.is_none_or(|meal| entry.meal == meal))
+        .collect();
+    if source.is_empty() {
+        return Err(format!("No {} logged on {}", what, from_date));
+    }
+
+    let copies: Vec<FoodLogEntry> = to_dates
+        .iter()
+        .flat_map(|date| {
+            // Copies onto later dates are plans until they are eaten; copies onto
+            // today or earlier count as eaten, even when the originals were plans
+            let planned = is_future(date);
+            source.iter().map(move |entry| {
+                FoodLogEntry::new(user_id, date, &entry.food_id, entry.servings, entry.meal, planned)
+            })
+        })
+        .collect();
+    let count = copies.len();
+    db.food_logs.entry(user_id.to_string()).or_default().extend(copies);
+
+    let target = match to_dates {
+        [date] => date.clone(),
+        dates => format!("{} days", dates.len()),
+    };
+    undo.record_action(db.clone(), &format!("Copied {} from {} to {}", what, from_date, target));
+    Ok(count)
+}
+
+pub fn add_exercise(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    date: &str,
+    exercise_id: &str,
+    duration_minutes: f32
+) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    let exercise_name = db.exercises
+        .get(exercise_id)
+        .map(|exercise| exercise.name.clone())
+        .ok_or_else(|| "Please select an activity".to_string())?;
+
+    if !(duration_minutes > 0.0 && duration_minutes.is_finite()) {
+        return Err("Please enter a valid duration".to_string());
+    }
+
+    validate_date(date)?;
+
+    db.exercise_logs.entry(user_id.to_string()).or_default().push(ExerciseLogEntry {
+        date: date.to_string(),
+        exercise_id: exercise_id.to_string(),
+        duration_minutes,
+        user_id: user_id.to_string(),
+    });
+
+    undo.record_action(db.clone(), &format!("Logged {} min of {}", duration_minutes, exercise_name));
+    Ok(())
+}
+
+pub fn remove_exercise(db: &mut Database, undo: &mut UndoManager, user_id: &str, index: usize) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    let entries = db.exercise_logs
+        .get_mut(user_id)
+        .filter(|entries| index < entries.len())
+        .ok_or_else(|| "Exercise entry not found".to_string())?;
+
+    let removed = entries.remove(index);
+    let name = db.exercises
+        .get(&removed.exercise_id)
+        .map_or(removed.exercise_id.clone(), |exercise| exercise.name.clone());
+
+    undo.record_action(db.clone(), &format!("Removed {} from exercise log", name));
+    Ok(())
+}
+
+// Add an activity to the shared exercise catalog. Returns its id. Everyone
+// sees the catalog, so only admins may add to it, like the shared foods.
+pub fn add_custom_exercise(db: &mut Database, undo: &mut UndoManager, user_id: &str, name: &str, met: &str) -> Result<String, String> {
+    let admin = admin::require_admin(db, user_id)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Activity name cannot be empty".to_string());
+    }
+
+    let met = match met.trim().parse::<f32>() {
+        Ok(met) if met > 0.0 && met <= 25.0 => met,
+        _ => return Err("MET value must be a number between 0 and 25".to_string()),
+    };
+
+    let id = slug(name);
+    if db.exercises.contains_key(&id) {
+        return Err("An activity with this name already exists".to_string());
+    }
+
+    db.exercises.insert(id.clone(), Exercise {
+        id: id.clone(),
+        name: name.to_string(),
+        met,
+    });
+    db.record_audit(&admin, &format!("Added the activity {}", name));
+
+    // Undo would also take back the audit entry
+    undo.initialize(db.clone());
+    Ok(id)
+}
+
+pub fn adjust_tracker(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    tracker_id: &str,
+    date: &str,
+    delta: f32
+) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    validate_date(date)?;
+    if !delta.is_finite() {
+        return Err("Please enter a valid amount".to_string());
+    }
+    let tracker = db.trackers
+        .get(user_id)
+        .and_then(|trackers| trackers.iter().find(|t| t.id == tracker_id))
+        .cloned()
+        .ok_or_else(|| "Tracker not found".to_string())?;
+
+    db.adjust_tracker(user_id, tracker_id, date, delta);
+
+    undo.record_action(
+        db.clone(),
+        &format!(
+            "{} {:.0} {} {}",
+            if delta > 0.0 { "Added" } else { "Removed" },
+            delta.abs(),
+            tracker.unit,
+            tracker.name
+        )
+    );
+    Ok(())
+}
+
+pub fn add_tracker(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    name: &str,
+    unit: &str,
+    daily_goal: &str,
+    increment: &str
+) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tracker name cannot be empty".to_string());
+    }
+
+    let id = slug(name);
+    let exists = db.trackers
+        .get(user_id)
+        .is_some_and(|trackers| trackers.iter().any(|t| t.id == id));
+    if exists {
+        return Err("A tracker with this name already exists".to_string());
+    }
+
+    let daily_goal = if daily_goal.trim().is_empty() {
+        None
+    } else {
+        match daily_goal.trim().parse::<f32>() {
+            Ok(goal) if goal > 0.0 && goal.is_finite() => Some(goal),
+            _ => return Err("Daily goal must be a positive number".to_string()),
+        }
+    };
+
+    let increment = match increment.trim().parse::<f32>() {
+        Ok(step) if step > 0.0 && step.is_finite() => step,
+        _ => return Err("Step must be a positive number".to_string()),
+    };
+
+    let unit = unit.trim();
+    db.trackers.entry(user_id.to_string()).or_default().push(HabitTracker {
+        id,
+        name: name.to_string(),
+        unit: if unit.is_empty() { "count".to_string() } else { unit.to_string() },
+        daily_goal,
+        increment,
+    });
+
+    undo.record_action(db.clone(), &format!("Added tracker {}", name));
+    Ok(())
+}
+
+// Remove a tracker along with its history. The built-in water tracker stays.
+pub fn remove_tracker(db: &mut Database, undo: &mut UndoManager, user_id: &str, tracker_id: &str) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    if tracker_id == WATER_TRACKER_ID {
+        return Err("The water tracker cannot be removed".to_string());
+    }
+
+    let trackers = db.trackers
+        .get_mut(user_id)
+        .ok_or_else(|| "Tracker not found".to_string())?;
+    let position = trackers
+        .iter()
+        .position(|t| t.id == tracker_id)
+        .ok_or_else(|| "Tracker not found".to_string())?;
+    let removed = trackers.remove(position);
+
+    if let Some(entries) = db.tracker_logs.get_mut(user_id) {
+        entries.retain(|entry| entry.tracker_id != tracker_id);
+    }
+
+    undo.record_action(db.clone(), &format!("Removed tracker {}", removed.name));
+    Ok(())
+}
+
+// Returns the key of the food, which may be given by its plain identifier
+fn validate_new_entry(db: &Database, user_id: &str, date: &str, food_id: &str, servings: f32) -> Result<String, String> {
+    if food_id.is_empty() {
+        return Err("Please select a food to add".to_string());
+    }
+
+    let food_id = db.resolve_food_id(user_id, food_id)
+        .ok_or_else(|| format!("Unknown food '{}'", food_id))?;
+
+    if !(servings > 0.0 && servings.is_finite()) {
+        return Err("Please enter a valid number of servings".to_string());
+    }
+
+    validate_date(date)?;
+    Ok(food_id)
+}
+
+fn find_entry_mut<'a>(db: &'a mut Database, user_id: &str, entry_id: &str) -> Result<&'a mut FoodLogEntry, String> {
+    db.food_logs
+        .get_mut(user_id)
+        .and_then(|entries| entries.iter_mut().find(|entry| entry.id == entry_id))
+        .ok_or_else(|| "Food log entry not found".to_string())
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+// ISO dates compare correctly as strings
+pub(crate) fn is_future(date: &str) -> bool {
+    date > today().as_str()
+}
+
+pub(crate) fn validate_date(date: &str) -> Result<(), String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))
+}
+
+fn slug(name: &str) -> String {
+    name.to_lowercase().split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_user;
+
+    fn setup() -> (Database, UndoManager) {
+        let mut db = Database::default();
+        db.users.insert("ann".to_string(), test_user("u1", "ann"));
+        let mut undo = UndoManager::with_log_path(10, None);
+        undo.initialize(db.clone());
+        (db, undo)
+    }
+
+    fn tomorrow() -> String {
+        (Local::now().date_naive() + chrono::Duration::days(1)).format("%Y-%m-%d").to_string()
+    }
+
+    fn dates(dates: &[&str]) -> Vec<String> {
+        dates.iter().map(|date| date.to_string()).collect()
+    }
+
+    #[test]
+    fn copy_entries_copies_only_that_meal_as_one_action() {
+        let (mut db, mut undo) = setup();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Breakfast).unwrap();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "banana", 2.0, Meal::Breakfast).unwrap();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Dinner).unwrap();
+
+        let copied = copy_entries(&mut db, &mut undo, "u1", "2025-03-01", &dates(&["2025-03-02"]), Some(Meal::Breakfast)).unwrap();
+        assert_eq!(copied, 2);
+
+        let today = entries_for_day(&db, "u1", "2025-03-02");
+        assert_eq!(today.len(), 2);
+        assert!(today.iter().all(|entry| entry.meal == Meal::Breakfast));
+        assert_eq!(today[1].servings, 2.0);
+
+        let (restored, _) = undo.undo().unwrap();
+        assert!(entries_for_day(&restored, "u1", "2025-03-02").is_empty());
+        assert_eq!(entries_for_day(&restored, "u1", "2025-03-01").len(), 3);
+    }
+
+    #[test]
+    fn copy_entries_repeats_a_whole_day() {
+        let (mut db, mut undo) = setup();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Breakfast).unwrap();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "egg", 2.0, Meal::Dinner).unwrap();
+
+        let targets = dates(&["2025-03-02", "2025-03-03", "2025-03-04"]);
+        assert_eq!(copy_entries(&mut db, &mut undo, "u1", "2025-03-01", &targets, None).unwrap(), 6);
+        for date in &targets {
+            assert_eq!(entries_for_day(&db, "u1", date).len(), 2);
+        }
+        assert_eq!(undo.last_action_description().unwrap(), "Copied entries from 2025-03-01 to 3 days");
+    }
+
+    #[test]
+    fn planned_food_counts_once_marked_eaten() {
+        let (mut db, mut undo) = setup();
+        let today = today();
+        let id = plan_entry(&mut db, &mut undo, "u1", &today, "apple", 2.0, Meal::Snack).unwrap();
+        assert_eq!(db.calories_consumed("u1", &today), 0.0);
+        assert_eq!(db.calories_planned("u1", &today), 190.0);
+
+        mark_eaten(&mut db, &mut undo, "u1", &id).unwrap();
+        assert_eq!(db.calories_consumed("u1", &today), 190.0);
+        assert!(mark_eaten(&mut db, &mut undo, "u1", &id).is_err());
+    }
+
+    #[test]
+    fn future_dates_can_only_be_planned() {
+        let (mut db, mut undo) = setup();
+        let tomorrow = tomorrow();
+
+        assert!(add_entry(&mut db, &mut undo, "u1", &tomorrow, "apple", 1.0, Meal::Lunch).is_err());
+        assert!(plan_entry(&mut db, &mut undo, "u1", "2020-01-01", "apple", 1.0, Meal::Lunch).is_err());
+        let id = plan_entry(&mut db, &mut undo, "u1", &tomorrow, "apple", 1.0, Meal::Lunch).unwrap();
+
+        // Eating a plan early logs it today
+        mark_eaten(&mut db, &mut undo, "u1", &id).unwrap();
+        assert_eq!(db.food_logs["u1"][0].date, today());
+    }
+
+    #[test]
+    fn copies_onto_future_dates_are_planned() {
+        let (mut db, mut undo) = setup();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Breakfast).unwrap();
+
+        copy_entries(&mut db, &mut undo, "u1", "2025-03-01", &[tomorrow()], None).unwrap();
+        assert!(entries_for_day(&db, "u1", &tomorrow())[0].planned);
+    }
+
+    #[test]
+    fn copies_of_a_planned_day_onto_past_dates_are_eaten() {
+        let (mut db, mut undo) = setup();
+        plan_entry(&mut db, &mut undo, "u1", &tomorrow(), "apple", 1.0, Meal::Breakfast).unwrap();
+
+        copy_entries(&mut db, &mut undo, "u1", &tomorrow(), &dates(&["2025-03-01"]), None).unwrap();
+        assert!(!entries_for_day(&db, "u1", "2025-03-01")[0].planned);
+        assert_eq!(db.calories_consumed("u1", "2025-03-01"), 95.0);
+    }
+
+    #[test]
+    fn tracker_values_must_be_real_numbers_on_real_dates() {
+        let (mut db, mut undo) = setup();
+        assert!(add_tracker(&mut db, &mut undo, "u1", "Steps", "steps", "inf", "100").is_err());
+        assert!(add_tracker(&mut db, &mut undo, "u1", "Steps", "steps", "10000", "NaN").is_err());
+        add_tracker(&mut db, &mut undo, "u1", "Steps", "steps", "10000", "100").unwrap();
+
+        assert!(adjust_tracker(&mut db, &mut undo, "u1", "steps", "2025-02-30", 100.0).is_err());
+        assert!(adjust_tracker(&mut db, &mut undo, "u1", "steps", "2025-03-01", f32::NAN).is_err());
+        assert!(adjust_tracker(&mut db, &mut undo, "u1", "steps", "2025-03-01", f32::INFINITY).is_err());
+        adjust_tracker(&mut db, &mut undo, "u1", "steps", "2025-03-01", 100.0).unwrap();
+        assert_eq!(undo.last_action_description().unwrap(), "Added 100 steps Steps");
+    }
+
+    #[test]
+    fn identical_entries_are_edited_and_removed_by_id() {
+        let (mut db, mut undo) = setup();
+        let first = add_entry(&mut db, &mut undo, "u1", "2025-03-01", "egg", 1.0, Meal::Breakfast).unwrap();
+        let second = add_entry(&mut db, &mut undo, "u1", "2025-03-01", "egg", 1.0, Meal::Breakfast).unwrap();
+        assert_ne!(first, second);
+
+        update_servings(&mut db, &mut undo, "u1", &second, 3.0).unwrap();
+        remove_entry(&mut db, &mut undo, "u1", &first).unwrap();
+
+        let left = entries_for_day(&db, "u1", "2025-03-01");
+        assert_eq!(left.len(), 1);
+        assert_eq!((left[0].id.as_str(), left[0].servings), (second.as_str(), 3.0));
+        assert!(remove_entry(&mut db, &mut undo, "u1", &first).is_err());
+
+        // Undo brings back the same entry, id and all
+        let (restored, _) = undo.undo().unwrap();
+        assert!(find_entry(&restored, "u1", &first).is_some());
+    }
+
+    #[test]
+    fn copies_get_their_own_ids() {
+        let (mut db, mut undo) = setup();
+        let original = add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Lunch).unwrap();
+
+        copy_entries(&mut db, &mut undo, "u1", "2025-03-01", &dates(&["2025-03-02", "2025-03-03"]), None).unwrap();
+        let copy = &entries_for_day(&db, "u1", "2025-03-02")[0];
+        assert_ne!(copy.id, original);
+        assert_ne!(copy.id, entries_for_day(&db, "u1", "2025-03-03")[0].id);
+    }
+
+    #[test]
+    fn copy_entries_fails_when_nothing_was_logged() {
+        let (mut db, mut undo) = setup();
+
+        assert!(copy_entries(&mut db, &mut undo, "u1", "2025-03-01", &dates(&["2025-03-02"]), Some(Meal::Lunch)).is_err());
+        assert!(!undo.can_undo());
+    }
+}
@@ -0,0 +1,10 @@
+// Business rules shared by every frontend. Mutating calls validate, apply the
+// change and record the resulting state with the UndoManager, so the GUI, the
+// CLI and tests all behave the same way.
+pub mod admin;
+pub mod auth;
+pub mod catalog;
+pub mod logging;
+pub mod reporting;
+pub mod shopping;
+pub mod templates;
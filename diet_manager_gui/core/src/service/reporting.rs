@@ -0,0 +1,109 @@
+use serde::Serialize;
+use crate::models::{Database, FoodLogEntry};
+
+// Everything the summary cards show for one user and date
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DailySummary {
+    pub date: String,
+    pub consumed: f32,
+    pub burned: f32,
+    pub target: f32,
+    pub allowance: f32, // Target plus any exercise calories earned back
+    pub remaining: f32, // Negative when the allowance is exceeded
+    pub planned: f32,   // Planned but not eaten yet
+}
+
+impl DailySummary {
+    // Fraction of the allowance eaten so far (0.0 when there is no allowance)
+    pub fn progress(&self) -> f32 {
+        if self.allowance > 0.0 {
+            self.consumed / self.allowance
+        } else {
+            0.0
+        }
+    }
+
+    // What will be left once everything planned is eaten too
+    pub fn projected_remaining(&self) -> f32 {
+        self.remaining - self.planned
+    }
+}
+
+pub fn daily_summary(db: &Database, user_id: &str, date: &str) -> DailySummary {
+    let (consumed, target, remaining) = db.calculate_calories(user_id, date);
+
+    DailySummary {
+        date: date.to_string(),
+        consumed,
+        burned: db.calories_burned(user_id, date),
+        target,
+        allowance: target + db.exercise_credit(user_id, date),
+        remaining,
+        planned: db.calories_planned(user_id, date),
+    }
+}
+
+// How a day went, for coloring calendars
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum DayStatus {
+    NoData,
+    Planned, // Only planned entries so far
+    UnderTarget,
+    OverTarget,
+}
+
+pub fn day_status(db: &Database, user_id: &str, date: &str) -> DayStatus {
+    let entries: Vec<&FoodLogEntry> = db.food_logs
+        .get(user_id)
+        .map_or(Vec::new(), |entries| entries.iter().filter(|entry| entry.date == date).collect());
+    if entries.is_empty() {
+        return DayStatus::NoData;
+    }
+    if entries.iter().all(|entry| entry.planned) {
+        return DayStatus::Planned;
+    }
+
+    if daily_summary(db, user_id, date).remaining < 0.0 {
+        DayStatus::OverTarget
+    } else {
+        DayStatus::UnderTarget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActivityLevel, CalorieCalculationMethod, Gender, Meal, UserProfile};
+    use crate::service::{auth, logging};
+    use crate::undo::UndoManager;
+    use crate::units::Units;
+
+    fn setup() -> (Database, UndoManager, String) {
+        let mut db = Database::default();
+        let profile = UserProfile {
+            gender: Gender::Female,
+            height_cm: 165.0,
+            age: 30,
+            calorie_method: CalorieCalculationMethod::HarrisBenedict,
+            weight_kg: 60.0,
+            activity_level: ActivityLevel::Sedentary,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        };
+        let user_id = auth::register(&mut db, "alice", "secret", profile).unwrap();
+        (db, UndoManager::with_log_path(10, None), user_id)
+    }
+
+    #[test]
+    fn day_status_follows_the_target() {
+        let (mut db, mut undo, user_id) = setup();
+        assert_eq!(day_status(&db, &user_id, "2025-03-01"), DayStatus::NoData);
+
+        logging::add_entry(&mut db, &mut undo, &user_id, "2025-03-01", "apple", 2.0, Meal::Snack).unwrap();
+        assert_eq!(day_status(&db, &user_id, "2025-03-01"), DayStatus::UnderTarget);
+
+        logging::add_entry(&mut db, &mut undo, &user_id, "2025-03-01", "apple", 30.0, Meal::Dinner).unwrap();
+        assert_eq!(day_status(&db, &user_id, "2025-03-01"), DayStatus::OverTarget);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::Serialize;
+use crate::export::csv_field;
+use crate::models::{Database, MAX_RECIPE_DEPTH};
+use crate::service::auth;
+use crate::service::logging::validate_date;
+use crate::undo::UndoManager;
+
+// One basic food to buy, totalled over every planned entry in the range
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub food_id: String,
+    pub name: String,
+    pub group: String, // The food's first keyword, e.g. "Fruit"
+    pub servings: f32,
+    pub checked: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShoppingListFormat {
+    Text, // Printable checklist
+    Csv,
+}
+
+impl ShoppingListFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ShoppingListFormat::Text => "txt",
+            ShoppingListFormat::Csv => "csv",
+        }
+    }
+}
+
+// Basic foods needed for the meals planned between two dates (inclusive).
+// Composite foods are broken down into their components, recursively.
+// Sorted by group, then name.
+pub fn shopping_list(db: &Database, user_id: &str, from: &str, to: &str) -> Result<Vec<ShoppingItem>, String> {
+    validate_date(from)?;
+    validate_date(to)?;
+    if from > to {
+        return Err("Start date must not be after the end date".to_string());
+    }
+
+    let mut totals: HashMap<String, f32> = HashMap::new();
+    let planned = db.food_logs
+        .get(user_id)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.planned && entry.date.as_str() >= from && entry.date.as_str() <= to);
+    for entry in planned {
+        expand(db, &entry.food_id, entry.servings, 0, &mut totals);
+    }
+
+    let checked = db.shopping_checked.get(user_id);
+    let mut items: Vec<ShoppingItem> = totals
+        .into_iter()
+        .filter_map(|(food_id, servings)| {
+            let food = db.basic_foods.get(&food_id)?;
+            Some(ShoppingItem {
+                name: food.name.clone(),
+                group: food.keywords.first().map_or("Other".to_string(), |keyword| capitalize(keyword)),
+                servings,
+                checked: checked.is_some_and(|ids| ids.contains(&food_id)),
+                food_id,
+            })
+        })
+        .collect();
+
+    items.sort_by(|a, b| a.group.cmp(&b.group).then_with(|| a.name.cmp(&b.name)));
+    Ok(items)
+}
+
+// Tick an item off (or back on). Ticks are kept until cleared. Takes the food's
+// full key, so private foods ("<user_id>/<id>") can be ticked by their owner.
+pub fn set_checked(db: &mut Database, undo: &mut UndoManager, user_id: &str, food_id: &str, checked: bool) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    if !db.food_exists(food_id) || !db.food_visible_to(food_id, user_id) {
+        return Err(format!("Unknown food '{}'", food_id));
+    }
+    let name = db.food_name(food_id);
+
+    let ticked = db.shopping_checked.entry(user_id.to_string()).or_default();
+    let already = ticked.iter().any(|id| id == food_id);
+    if already == checked {
+        return Ok(());
+    }
+    if checked {
+        ticked.push(food_id.to_string());
+    } else {
+        ticked.retain(|id| id != food_id);
+    }
+
+    let action = if checked { "Checked off" } else { "Unchecked" };
+    undo.record_action(db.clone(), &format!("{} {} on the shopping list", action, name));
+    Ok(())
+}
+
+pub fn clear_checked(db: &mut Database, undo: &mut UndoManager, user_id: &str) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    if db.shopping_checked.remove(user_id).is_some_and(|ticked| !ticked.is_empty()) {
+        undo.record_action(db.clone(), "Cleared shopping list ticks");
+    }
+    Ok(())
+}
+
+// A checklist grouped by section, ready to print
+pub fn shopping_list_to_text(items: &[ShoppingItem], from: &str, to: &str) -> String {
+    let mut text = format!("Shopping list for {} to {}\n", from, to);
+    let mut group = None;
+    for item in items {
+        if group != Some(&item.group) {
+            text.push_str(&format!("\n{}\n", item.group));
+            group = Some(&item.group);
+        }
+        let tick = if item.checked { "[x]" } else { "[ ]" };
+        text.push_str(&format!("  {} {} x{:.1}\n", tick, item.name, item.servings));
+    }
+    if items.is_empty() {
+        text.push_str("\nNothing planned\n");
+    }
+    text
+}
+
+pub fn shopping_list_to_csv(items: &[ShoppingItem]) -> String {
+    let mut csv = String::from("group,food_id,name,servings,checked\n");
+    for item in items {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&item.group),
+            csv_field(&item.food_id),
+            csv_field(&item.name),
+            item.servings,
+            item.checked
+        ));
+    }
+    csv
+}
+
+// Returns the number of items written
+pub fn export_shopping_list(
+    items: &[ShoppingItem],
+    from: &str,
+    to: &str,
+    format: ShoppingListFormat,
+    path: &Path
+) -> io::Result<usize> {
+    let data = match format {
+        ShoppingListFormat::Text => shopping_list_to_text(items, from, to),
+        ShoppingListFormat::Csv => shopping_list_to_csv(items),
+    };
+    fs::write(path, data)?;
+    Ok(items.len())
+}
+
+fn expand(db: &Database, food_id: &str, servings: f32, depth: usize, totals: &mut HashMap<String, f32>) {
+    if db.basic_foods.contains_key(food_id) {
+        *totals.entry(food_id.to_string()).or_insert(0.0) += servings;
+    } else if let Some(recipe) = db.composite_foods.get(food_id) {
+        if depth >= MAX_RECIPE_DEPTH {
+            return;
+        }
+        for component in &recipe.components {
+            expand(db, &component.food_id, servings * component.servings, depth + 1, totals);
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Other".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{private_food_key, test_user, BasicFood, CompositeFood, FoodComponent, FoodLogEntry, Meal, Role};
+
+    fn plan(db: &mut Database, date: &str, food_id: &str, servings: f32, planned: bool) {
+        db.food_logs.entry("u1".to_string()).or_default().push(FoodLogEntry::new("u1", date, food_id, servings, Meal::Lunch, planned));
+    }
+
+    fn setup() -> Database {
+        let mut db = Database::default();
+        db.users.insert("ann".to_string(), test_user("u1", "ann"));
+        let component = |food_id: &str, servings: f32| FoodComponent { food_id: food_id.to_string(), servings };
+        db.composite_foods.insert("rice_bowl".to_string(), CompositeFood {
+            id: "rice_bowl".to_string(),
+            name: "Rice Bowl".to_string(),
+            keywords: vec!["lunch".to_string()],
+            components: vec![component("brown_rice", 1.0), component("chicken_breast", 0.5)],
+            owner: None,
+        });
+        db.composite_foods.insert("double_bowl".to_string(), CompositeFood {
+            id: "double_bowl".to_string(),
+            name: "Double Bowl".to_string(),
+            keywords: vec!["lunch".to_string()],
+            components: vec![component("rice_bowl", 2.0), component("egg", 1.0)],
+            owner: None,
+        });
+        db
+    }
+
+    fn servings_of(items: &[ShoppingItem], food_id: &str) -> f32 {
+        items.iter().find(|item| item.food_id == food_id).map_or(0.0, |item| item.servings)
+    }
+
+    #[test]
+    fn recipes_are_expanded_and_aggregated() {
+        let mut db = setup();
+        plan(&mut db, "2025-03-01", "rice_bowl", 1.0, true);
+        plan(&mut db, "2025-03-02", "double_bowl", 1.0, true);
+        plan(&mut db, "2025-03-02", "brown_rice", 0.5, true);
+
+        let items = shopping_list(&db, "u1", "2025-03-01", "2025-03-07").unwrap();
+        assert_eq!(servings_of(&items, "brown_rice"), 3.5);
+        assert_eq!(servings_of(&items, "chicken_breast"), 1.5);
+        assert_eq!(servings_of(&items, "egg"), 1.0);
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn eaten_entries_and_other_dates_are_left_out() {
+        let mut db = setup();
+        plan(&mut db, "2025-03-01", "apple", 1.0, false);
+        plan(&mut db, "2025-03-09", "apple", 1.0, true);
+
+        assert!(shopping_list(&db, "u1", "2025-03-01", "2025-03-07").unwrap().is_empty());
+        assert!(shopping_list(&db, "u1", "2025-03-07", "2025-03-01").is_err());
+    }
+
+    #[test]
+    fn ticks_are_persisted_until_cleared() {
+        let mut db = setup();
+        let mut undo = UndoManager::with_log_path(10, None);
+        plan(&mut db, "2025-03-01", "apple", 2.0, true);
+
+        set_checked(&mut db, &mut undo, "u1", "apple", true).unwrap();
+        let items = shopping_list(&db, "u1", "2025-03-01", "2025-03-01").unwrap();
+        assert!(items[0].checked);
+        assert!(shopping_list_to_text(&items, "2025-03-01", "2025-03-01").contains("[x] Apple x2.0"));
+
+        clear_checked(&mut db, &mut undo, "u1").unwrap();
+        assert!(!shopping_list(&db, "u1", "2025-03-01", "2025-03-01").unwrap()[0].checked);
+    }
+
+    #[test]
+    fn private_foods_can_be_ticked_by_their_owner_only() {
+        let mut db = setup();
+        let mut undo = UndoManager::with_log_path(10, None);
+        let key = private_food_key("u1", "kiwi");
+        let mut kiwi = BasicFood::from_input("kiwi", "Kiwi", "fruit", "42").unwrap();
+        kiwi.owner = Some("u1".to_string());
+        db.basic_foods.insert(key.clone(), kiwi);
+        plan(&mut db, "2025-03-01", &key, 1.0, true);
+        undo.initialize(db.clone());
+
+        set_checked(&mut db, &mut undo, "u1", &key, true).unwrap();
+        assert!(shopping_list(&db, "u1", "2025-03-01", "2025-03-01").unwrap()[0].checked);
+        assert_eq!(undo.last_action_description().unwrap(), "Checked off Kiwi on the shopping list");
+
+        db.users.insert("bob".to_string(), test_user("u2", "bob"));
+        assert!(set_checked(&mut db, &mut undo, "u2", &key, true).is_err());
+    }
+
+    #[test]
+    fn read_only_accounts_cannot_change_ticks() {
+        let mut db = setup();
+        let mut undo = UndoManager::with_log_path(10, None);
+        set_checked(&mut db, &mut undo, "u1", "apple", true).unwrap();
+        db.users.get_mut("ann").unwrap().role = Role::ReadOnly;
+
+        assert!(set_checked(&mut db, &mut undo, "u1", "apple", false).is_err());
+        assert!(clear_checked(&mut db, &mut undo, "u1").is_err());
+        assert!(set_checked(&mut db, &mut undo, "nobody", "apple", true).is_err());
+        assert_eq!(db.shopping_checked["u1"], vec!["apple".to_string()]);
+    }
+}
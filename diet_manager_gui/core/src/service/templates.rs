@@ -0,0 +1,160 @@
+use crate::models::{Database, FoodComponent, FoodLogEntry, Meal, MealTemplate};
+use crate::service::auth;
+use crate::service::logging::{entries_for_day, is_future, validate_date};
+use crate::undo::UndoManager;
+
+pub fn list_templates(db: &Database, user_id: &str) -> Vec<MealTemplate> {
+    let mut templates = db.meal_templates.get(user_id).cloned().unwrap_or_default();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+    templates
+}
+
+// Save what was logged on a date (optionally just one meal) under a name
+pub fn save_template(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    name: &str,
+    date: &str,
+    meal: Option<Meal>
+) -> Result<usize, String> {
+    auth::require_write_access(db, user_id)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+
+    validate_date(date)?;
+    if find_template(db, user_id, name).is_some() {
+        return Err("A template with this name already exists".to_string());
+    }
+
+    let items: Vec<FoodComponent> = entries_for_day(db, user_id, date)
+        .into_iter()
+        .filter(|entry| meal.is_none_or(|meal| entry.meal == meal))
+        .map(|entry| FoodComponent { food_id: entry.food_id, servings: entry.servings })
+        .collect();
+    if items.is_empty() {
+        return Err("There is nothing logged to save as a template".to_string());
+    }
+
+    let count = items.len();
+    db.meal_templates.entry(user_id.to_string()).or_default().push(MealTemplate {
+        name: name.to_string(),
+        items,
+    });
+
+    undo.record_action(db.clone(), &format!("Saved template {}", name));
+    Ok(count)
+}
+
+// Log every food in a template as part of one meal, in a single undoable action.
+// On a later date the foods are planned rather than eaten. Foods deleted from
+// the catalog since the template was saved are skipped.
+pub fn apply_template(
+    db: &mut Database,
+    undo: &mut UndoManager,
+    user_id: &str,
+    name: &str,
+    date: &str,
+    meal: Meal
+) -> Result<usize, String> {
+    auth::require_write_access(db, user_id)?;
+    validate_date(date)?;
+    let template = find_template(db, user_id, name)
+        .cloned()
+        .ok_or_else(|| format!("Template '{}' not found", name))?;
+
+    let planned = is_future(date);
+    let entries: Vec<FoodLogEntry> = template.items
+        .iter()
+        .filter(|item| db.food_visible_to(&item.food_id, user_id))
+        .map(|item| FoodLogEntry::new(user_id, date, &item.food_id, item.servings, meal, planned))
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("None of the foods in '{}' exist any more", template.name));
+    }
+
+    let count = entries.len();
+    db.food_logs.entry(user_id.to_string()).or_default().extend(entries);
+
+    undo.record_action(db.clone(), &format!("Added template {} to {}", template.name, meal.label().to_lowercase()));
+    Ok(count)
+}
+
+pub fn delete_template(db: &mut Database, undo: &mut UndoManager, user_id: &str, name: &str) -> Result<(), String> {
+    auth::require_write_access(db, user_id)?;
+    let templates = db.meal_templates
+        .get_mut(user_id)
+        .ok_or_else(|| format!("Template '{}' not found", name))?;
+    let position = templates
+        .iter()
+        .position(|template| template.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Template '{}' not found", name))?;
+    let removed = templates.remove(position);
+
+    undo.record_action(db.clone(), &format!("Deleted template {}", removed.name));
+    Ok(())
+}
+
+// Template names are matched case-insensitively
+fn find_template<'a>(db: &'a Database, user_id: &str, name: &str) -> Option<&'a MealTemplate> {
+    db.meal_templates
+        .get(user_id)?
+        .iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_user;
+    use crate::service::logging::add_entry;
+
+    fn setup() -> (Database, UndoManager) {
+        let mut db = Database::default();
+        db.users.insert("ann".to_string(), test_user("u1", "ann"));
+        let mut undo = UndoManager::with_log_path(10, None);
+        undo.initialize(db.clone());
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "chicken_breast", 1.0, Meal::Lunch).unwrap();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "brown_rice", 1.5, Meal::Lunch).unwrap();
+        add_entry(&mut db, &mut undo, "u1", "2025-03-01", "apple", 1.0, Meal::Snack).unwrap();
+        (db, undo)
+    }
+
+    #[test]
+    fn saved_template_applies_as_one_action() {
+        let (mut db, mut undo) = setup();
+        assert_eq!(save_template(&mut db, &mut undo, "u1", "Workday lunch", "2025-03-01", Some(Meal::Lunch)).unwrap(), 2);
+
+        let added = apply_template(&mut db, &mut undo, "u1", "workday LUNCH", "2025-03-05", Meal::Dinner).unwrap();
+        assert_eq!(added, 2);
+        let day = entries_for_day(&db, "u1", "2025-03-05");
+        assert!(day.iter().all(|entry| entry.meal == Meal::Dinner));
+        assert_eq!(day[1].servings, 1.5);
+
+        let (restored, _) = undo.undo().unwrap();
+        assert!(entries_for_day(&restored, "u1", "2025-03-05").is_empty());
+        assert_eq!(list_templates(&restored, "u1").len(), 1);
+    }
+
+    #[test]
+    fn template_names_must_be_unique() {
+        let (mut db, mut undo) = setup();
+        save_template(&mut db, &mut undo, "u1", "Day", "2025-03-01", None).unwrap();
+
+        assert!(save_template(&mut db, &mut undo, "u1", " day ", "2025-03-01", None).is_err());
+        assert!(save_template(&mut db, &mut undo, "u1", "Empty", "2025-03-02", None).is_err());
+    }
+
+    #[test]
+    fn deleted_foods_are_skipped_when_applying() {
+        let (mut db, mut undo) = setup();
+        save_template(&mut db, &mut undo, "u1", "Lunch", "2025-03-01", Some(Meal::Lunch)).unwrap();
+        db.basic_foods.remove("brown_rice");
+
+        assert_eq!(apply_template(&mut db, &mut undo, "u1", "Lunch", "2025-03-02", Meal::Lunch).unwrap(), 1);
+        delete_template(&mut db, &mut undo, "u1", "Lunch").unwrap();
+        assert!(apply_template(&mut db, &mut undo, "u1", "Lunch", "2025-03-02", Meal::Lunch).is_err());
+    }
+}
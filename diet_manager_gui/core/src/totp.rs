@@ -0,0 +1,154 @@
+// Time-based one-time passwords (RFC 6238) as used by authenticator apps:
+// HMAC-SHA1 over 30-second steps, 6 digits. Everything is computed locally.
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+
+pub const STEP_SECONDS: u64 = 30;
+pub const DIGITS: u32 = 6;
+// Codes from one step either side are accepted to allow for clock drift
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+const SECRET_BYTES: usize = 20;
+// Recovery codes avoid look-alike characters (0/o, 1/l/i)
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+// A new random secret, base32 encoded the way authenticator apps expect it
+pub fn generate_secret() -> String {
+    let bytes: [u8; SECRET_BYTES] = rand::thread_rng().gen();
+    BASE32_NOPAD.encode(&bytes)
+}
+
+// The time step a Unix timestamp falls in
+pub fn step_at(unix_time: u64) -> u64 {
+    unix_time / STEP_SECONDS
+}
+
+// The code shown by an authenticator app during the given step
+pub fn code_for_step(secret: &str, step: u64) -> Result<String, String> {
+    let key = decode_secret(secret)?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).map_err(|e| e.to_string())?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation: four bytes at an offset taken from the last nibble
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    Ok(format!("{:0width$}", value % 10u32.pow(DIGITS), width = DIGITS as usize))
+}
+
+// The step a code matches at the given time, if any
+pub fn verify(secret: &str, code: &str, unix_time: u64) -> Option<u64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let now = step_at(unix_time);
+    (now.saturating_sub(ALLOWED_DRIFT_STEPS)..=now + ALLOWED_DRIFT_STEPS)
+        .find(|step| code_for_step(secret, *step).is_ok_and(|expected| expected == code))
+}
+
+// Link that authenticator apps import, e.g. from a QR code or by pasting it
+pub fn otpauth_uri(secret: &str, account: &str, issuer: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+// One-time codes for when the authenticator is not at hand, e.g. "k7pq-3mzx"
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            let mut code: String = (0..8)
+                .map(|_| RECOVERY_ALPHABET[rng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+                .collect();
+            code.insert(4, '-');
+            code
+        })
+        .collect()
+}
+
+// Recovery codes compare without case, spaces or dashes
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+// Secrets are shown in groups of four, so accept spaces and lowercase too
+fn decode_secret(secret: &str) -> Result<Vec<u8>, String> {
+    let cleaned: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    BASE32_NOPAD
+        .decode(cleaned.as_bytes())
+        .map_err(|_| "The secret is not valid base32".to_string())
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The SHA1 secret from RFC 6238, "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn codes_match_the_rfc_test_vectors() {
+        assert_eq!(code_for_step(RFC_SECRET, step_at(59)).unwrap(), "287082");
+        assert_eq!(code_for_step(RFC_SECRET, step_at(1111111109)).unwrap(), "081804");
+        assert_eq!(code_for_step(RFC_SECRET, step_at(2000000000)).unwrap(), "279037");
+    }
+
+    #[test]
+    fn verify_allows_one_step_of_drift() {
+        let time = 1111111109;
+        let code = code_for_step(RFC_SECRET, step_at(time)).unwrap();
+
+        assert_eq!(verify(RFC_SECRET, &code, time), Some(step_at(time)));
+        assert_eq!(verify(RFC_SECRET, &code, time + STEP_SECONDS), Some(step_at(time)));
+        assert_eq!(verify(RFC_SECRET, &code, time + 3 * STEP_SECONDS), None);
+        assert_eq!(verify(RFC_SECRET, "12345", time), None);
+    }
+
+    #[test]
+    fn generated_secrets_decode_and_differ() {
+        let secret = generate_secret();
+        assert_eq!(decode_secret(&secret).unwrap().len(), SECRET_BYTES);
+        assert_ne!(secret, generate_secret());
+        assert!(code_for_step(&secret.to_lowercase(), 1).is_ok());
+    }
+
+    #[test]
+    fn otpauth_uri_escapes_the_label() {
+        let uri = otpauth_uri("ABC", "jane doe", "Diet Manager");
+        assert_eq!(uri, "otpauth://totp/Diet%20Manager:jane%20doe?secret=ABC&issuer=Diet%20Manager&algorithm=SHA1&digits=6&period=30");
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_normalize() {
+        let codes = generate_recovery_codes(8);
+        assert_eq!(codes.len(), 8);
+        assert!(codes.iter().all(|code| code.len() == 9 && code.chars().nth(4) == Some('-')));
+        assert_eq!(normalize_recovery_code(" K7PQ-3mzx "), "k7pq3mzx");
+    }
+}
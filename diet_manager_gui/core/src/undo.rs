@@ -0,0 +1,267 @@
+use crate::models::Database;
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::Local;
+use crate::config;
+use crate::database;
+
+// Linear undo history. Index 0 holds the state at login; every later entry is the
+// state right *after* the described action, so undoing steps back one snapshot.
+pub struct UndoManager {
+    history: Vec<(Database, String)>,  // (Database snapshot, action description)
+    capacity: usize,
+    current_index: usize,
+    log_path: Option<PathBuf>, // Where actions are appended; None disables the log
+    // The log is plain text, so nothing is written while this file is encrypted
+    database_file: Option<PathBuf>,
+    version: u64, // Bumped whenever the history moves, so callers can tell when to save
+}
+
+impl UndoManager {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            database_file: Some(config::database_file()),
+            ..Self::with_log_path(capacity, Some(config::data_paths().action_log_file()))
+        }
+    }
+
+    pub fn with_log_path(capacity: usize, log_path: Option<PathBuf>) -> Self {
+        Self {
+            history: Vec::with_capacity(capacity),
+            capacity,
+            current_index: 0,
+            log_path,
+            database_file: None,
+            version: 0,
+        }
+    }
+
+    pub fn initialize(&mut self, initial_db: Database) {
+        self.history.clear();
+        self.history.push((initial_db, "Initial state".to_string()));
+        self.current_index = 0;
+        self.version += 1;
+    }
+
+    // Call once the action has been applied, passing the resulting state
+    pub fn record_action(&mut self, db_snapshot: Database, description: &str) {
+        // If we're not at the end of the history, truncate
+        if !self.history.is_empty() && self.current_index < self.history.len() - 1 {
+            self.history.truncate(self.current_index + 1);
+        }
+
+        // Add new action
+        self.history.push((db_snapshot, description.to_string()));
+        self.current_index = self.history.len() - 1;
+
+        // Remove oldest entries if exceeding capacity
+        while self.history.len() > self.capacity {
+            self.history.remove(0);
+            self.current_index = self.current_index.saturating_sub(1);
+        }
+
+        self.version += 1;
+        self.write_log(description);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current_index > 0
+    }
+
+    pub fn undo(&mut self) -> Option<(Database, String)> {
+        if self.can_undo() {
+            // The action being undone is at the current index
+            let current_action = self.history[self.current_index].1.clone();
+
+            // Move back one step in history
+            self.current_index -= 1;
+
+            // Get the state we're reverting to
+            let (db, _) = &self.history[self.current_index];
+
+            self.version += 1;
+            self.write_log(&format!("UNDO: {}", current_action));
+
+            return Some((db.clone(), current_action));
+        }
+        None
+    }
+
+    pub fn last_action_description(&self) -> Option<String> {
+        if self.history.len() > 1 && self.current_index > 0 {
+            Some(self.history[self.current_index].1.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.current_index = 0;
+        self.version += 1;
+    }
+
+    // Changes after every initialize, record_action, undo and clear
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn write_log(&self, message: &str) {
+        let path = match &self.log_path {
+            Some(path) => path,
+            None => return,
+        };
+        if self.database_file.as_deref().is_some_and(database::is_encrypted) {
+            return;
+        }
+
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let log_entry = format!("[{}] {}\n", timestamp, message);
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(log_entry.as_bytes()))
+            .unwrap_or_else(|e| eprintln!("Failed to write to log file: {}", e));
+    }
+
+    // Debug function to print state of the undo manager
+    pub fn debug_print_state(&self) {
+        println!("UndoManager state:");
+        println!("  - History length: {}", self.history.len());
+        println!("  - Current index: {}", self.current_index);
+        println!("  - Can undo: {}", self.can_undo());
+
+        for (i, (_, action)) in self.history.iter().enumerate() {
+            println!("  - [{}] {}{}", i,
+                if i == self.current_index { "-> " } else { "" },
+                action);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Snapshots told apart by current_user
+    fn state(label: &str) -> Database {
+        Database {
+            current_user: label.to_string(),
+            ..Database::default()
+        }
+    }
+
+    #[test]
+    fn actions_on_an_encrypted_database_are_not_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        let log_path = dir.path().join("actions.log");
+        database::save_database_to(&Database::default(), &path).unwrap();
+        let mut undo = UndoManager {
+            database_file: Some(path.clone()),
+            ..UndoManager::with_log_path(10, Some(log_path.clone()))
+        };
+        undo.initialize(state("initial"));
+
+        undo.record_action(state("a"), "Logged lunch");
+        assert!(std::fs::read_to_string(&log_path).unwrap().contains("Logged lunch"));
+
+        database::encrypt_database(&path, "correct horse").unwrap();
+        undo.record_action(state("b"), "Secret snack");
+        undo.undo();
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(!log.contains("Secret snack"));
+    }
+
+    fn manager(capacity: usize) -> UndoManager {
+        let mut undo = UndoManager::with_log_path(capacity, None);
+        undo.initialize(state("initial"));
+        undo
+    }
+
+    #[test]
+    fn nothing_to_undo_after_initialize() {
+        let mut undo = manager(10);
+
+        assert!(!undo.can_undo());
+        assert!(undo.last_action_description().is_none());
+        assert!(undo.undo().is_none());
+    }
+
+    #[test]
+    fn undo_steps_back_one_action_at_a_time() {
+        let mut undo = manager(10);
+        undo.record_action(state("a"), "Action A");
+        undo.record_action(state("b"), "Action B");
+
+        assert_eq!(undo.last_action_description().as_deref(), Some("Action B"));
+
+        let (db, action) = undo.undo().unwrap();
+        assert_eq!((db.current_user.as_str(), action.as_str()), ("a", "Action B"));
+
+        let (db, action) = undo.undo().unwrap();
+        assert_eq!((db.current_user.as_str(), action.as_str()), ("initial", "Action A"));
+
+        assert!(!undo.can_undo());
+    }
+
+    #[test]
+    fn recording_after_undo_drops_the_redo_branch() {
+        let mut undo = manager(10);
+        undo.record_action(state("a"), "Action A");
+        undo.record_action(state("b"), "Action B");
+        undo.undo();
+
+        undo.record_action(state("c"), "Action C");
+        assert_eq!(undo.last_action_description().as_deref(), Some("Action C"));
+
+        let (db, _) = undo.undo().unwrap();
+        assert_eq!(db.current_user, "a");
+        let (db, _) = undo.undo().unwrap();
+        assert_eq!(db.current_user, "initial");
+        assert!(undo.undo().is_none());
+    }
+
+    #[test]
+    fn capacity_evicts_oldest_snapshots() {
+        let mut undo = manager(3);
+        for label in ["a", "b", "c", "d"] {
+            undo.record_action(state(label), &format!("Action {}", label));
+        }
+
+        // Only b, c and d remain, so two undos are possible and d's predecessor is c
+        let (db, _) = undo.undo().unwrap();
+        assert_eq!(db.current_user, "c");
+        let (db, _) = undo.undo().unwrap();
+        assert_eq!(db.current_user, "b");
+        assert!(!undo.can_undo());
+    }
+
+    #[test]
+    fn clear_forgets_history() {
+        let mut undo = manager(10);
+        undo.record_action(state("a"), "Action A");
+        undo.clear();
+
+        assert!(!undo.can_undo());
+        assert!(undo.undo().is_none());
+    }
+
+    #[test]
+    fn actions_are_appended_to_the_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actions.log");
+        let mut undo = UndoManager::with_log_path(10, Some(path.clone()));
+        undo.initialize(state("initial"));
+
+        undo.record_action(state("a"), "Action A");
+        undo.undo();
+
+        let log = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("] Action A"));
+        assert!(lines[1].ends_with("] UNDO: Action A"));
+    }
+}
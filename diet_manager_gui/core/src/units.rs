@@ -0,0 +1,212 @@
+// Metric and imperial units for body measurements. Profiles always store
+// centimetres and kilograms; these helpers convert at the edges and parse what
+// people type, so a typo is reported instead of being saved as zero.
+use serde::{Deserialize, Serialize};
+
+pub const CM_PER_INCH: f32 = 2.54;
+pub const KG_PER_LB: f32 = 0.453_592_37;
+pub const INCHES_PER_FOOT: f32 = 12.0;
+
+// Accepted profile values; the calorie formulas are meant for people in these ranges
+pub const MIN_HEIGHT_CM: f32 = 100.0;
+pub const MAX_HEIGHT_CM: f32 = 250.0;
+pub const MIN_WEIGHT_KG: f32 = 30.0;
+pub const MAX_WEIGHT_KG: f32 = 250.0;
+pub const MIN_AGE: u32 = 13;
+pub const MAX_AGE: u32 = 120;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial, // Feet and inches, pounds
+}
+
+impl Units {
+    pub const ALL: [Units; 2] = [Units::Metric, Units::Imperial];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Units::Metric => "Metric (cm, kg)",
+            Units::Imperial => "Imperial (ft/in, lb)",
+        }
+    }
+
+    pub fn weight_unit(&self) -> &'static str {
+        match self {
+            Units::Metric => "kg",
+            Units::Imperial => "lb",
+        }
+    }
+
+    // A weight in kilograms expressed in these units
+    pub fn weight_from_kg(&self, kg: f32) -> f32 {
+        match self {
+            Units::Metric => kg,
+            Units::Imperial => kg / KG_PER_LB,
+        }
+    }
+
+    // A weight in these units converted to kilograms
+    pub fn weight_to_kg(&self, value: f32) -> f32 {
+        match self {
+            Units::Metric => value,
+            Units::Imperial => value * KG_PER_LB,
+        }
+    }
+
+    pub fn format_weight(&self, kg: f32) -> String {
+        format!("{:.1} {}", self.weight_from_kg(kg), self.weight_unit())
+    }
+
+    pub fn format_height(&self, cm: f32) -> String {
+        match self {
+            Units::Metric => format!("{:.0} cm", cm),
+            Units::Imperial => {
+                let (feet, inches) = cm_to_feet_inches(cm);
+                format!("{} ft {} in", feet, inches)
+            }
+        }
+    }
+}
+
+// Whole feet and inches, rounded to the nearest inch
+pub fn cm_to_feet_inches(cm: f32) -> (u32, u32) {
+    let total_inches = (cm / CM_PER_INCH).round().max(0.0) as u32;
+    (total_inches / INCHES_PER_FOOT as u32, total_inches % INCHES_PER_FOOT as u32)
+}
+
+pub fn feet_inches_to_cm(feet: f32, inches: f32) -> f32 {
+    (feet * INCHES_PER_FOOT + inches) * CM_PER_INCH
+}
+
+// Height in centimetres from a form. Metric forms use `main` for centimetres;
+// imperial ones use it for feet and `inches` (which may be left blank) for the rest.
+pub fn parse_height(main: &str, inches: &str, units: Units) -> Result<f32, String> {
+    let cm = match units {
+        Units::Metric => parse_number(main, "Height")?,
+        Units::Imperial => {
+            let feet = parse_number(main, "Feet")?;
+            let inches = if inches.trim().is_empty() { 0.0 } else { parse_number(inches, "Inches")? };
+            if feet.fract() != 0.0 {
+                return Err("Feet must be a whole number; put the rest in inches".to_string());
+            }
+            if !(0.0..INCHES_PER_FOOT).contains(&inches) {
+                return Err("Inches must be between 0 and 11".to_string());
+            }
+            feet_inches_to_cm(feet, inches)
+        }
+    };
+    check_height(cm, units)?;
+    Ok(cm)
+}
+
+// A body weight in kilograms from a form, entered in the given units
+pub fn parse_weight(text: &str, units: Units, field: &str) -> Result<f32, String> {
+    let kg = units.weight_to_kg(parse_number(text, field)?);
+    check_weight(kg, units, field)?;
+    Ok(kg)
+}
+
+pub fn parse_age(text: &str) -> Result<u32, String> {
+    let age = text
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| if text.trim().is_empty() { "Age is required".to_string() } else { "Age must be a whole number of years".to_string() })?;
+    check_age(age)?;
+    Ok(age)
+}
+
+pub fn check_height(cm: f32, units: Units) -> Result<(), String> {
+    if (MIN_HEIGHT_CM..=MAX_HEIGHT_CM).contains(&cm) {
+        return Ok(());
+    }
+    Err(format!(
+        "Height must be between {} and {}",
+        units.format_height(MIN_HEIGHT_CM),
+        units.format_height(MAX_HEIGHT_CM)
+    ))
+}
+
+pub fn check_weight(kg: f32, units: Units, field: &str) -> Result<(), String> {
+    // A little slack so values converted back and forth stay inside the range
+    if (MIN_WEIGHT_KG - 0.05..=MAX_WEIGHT_KG + 0.05).contains(&kg) {
+        return Ok(());
+    }
+    Err(format!(
+        "{} must be between {:.0} and {:.0} {}",
+        field,
+        units.weight_from_kg(MIN_WEIGHT_KG).ceil(),
+        units.weight_from_kg(MAX_WEIGHT_KG).floor(),
+        units.weight_unit()
+    ))
+}
+
+pub fn check_age(age: u32) -> Result<(), String> {
+    if (MIN_AGE..=MAX_AGE).contains(&age) {
+        Ok(())
+    } else {
+        Err(format!("Age must be between {} and {}", MIN_AGE, MAX_AGE))
+    }
+}
+
+fn parse_number(text: &str, field: &str) -> Result<f32, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(format!("{} is required", field));
+    }
+    match text.replace(',', ".").parse::<f32>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(value),
+        Ok(_) => Err(format!("{} must be a positive number", field)),
+        Err(_) => Err(format!("{} must be a number", field)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_metric_and_imperial_heights() {
+        assert_eq!(parse_height(" 180 ", "", Units::Metric), Ok(180.0));
+        assert!((parse_height("5", "11", Units::Imperial).unwrap() - 180.34).abs() < 0.01);
+        assert!((parse_height("6", "", Units::Imperial).unwrap() - 182.88).abs() < 0.01);
+        assert_eq!(cm_to_feet_inches(180.34), (5, 11));
+        assert_eq!(Units::Imperial.format_height(180.0), "5 ft 11 in");
+
+        assert_eq!(parse_height("", "", Units::Metric), Err("Height is required".to_string()));
+        assert_eq!(parse_height("abc", "", Units::Metric), Err("Height must be a number".to_string()));
+        assert!(parse_height("5", "12", Units::Imperial).is_err());
+        assert!(parse_height("5.5", "", Units::Imperial).is_err());
+        assert!(parse_height("18", "", Units::Metric).is_err());
+        assert_eq!(
+            parse_height("9", "0", Units::Imperial),
+            Err("Height must be between 3 ft 3 in and 8 ft 2 in".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_weights_in_either_unit() {
+        assert_eq!(parse_weight("72,5", Units::Metric, "Weight"), Ok(72.5));
+        assert!((parse_weight("160", Units::Imperial, "Weight").unwrap() - 72.57).abs() < 0.01);
+        assert_eq!(Units::Imperial.format_weight(72.57), "160.0 lb");
+
+        assert_eq!(parse_weight("-3", Units::Metric, "Weight"), Err("Weight must be a positive number".to_string()));
+        assert_eq!(
+            parse_weight("20", Units::Imperial, "Goal weight"),
+            Err("Goal weight must be between 67 and 551 lb".to_string())
+        );
+        // The limits themselves survive a round trip through pounds
+        let lb = Units::Imperial.weight_from_kg(MIN_WEIGHT_KG);
+        assert!(parse_weight(&format!("{:.1}", lb), Units::Imperial, "Weight").is_ok());
+    }
+
+    #[test]
+    fn ages_must_be_whole_and_in_range() {
+        assert_eq!(parse_age("35"), Ok(35));
+        assert!(parse_age("35.5").is_err());
+        assert!(parse_age("").is_err());
+        assert!(parse_age("4").is_err());
+        assert!(parse_age("150").is_err());
+    }
+}
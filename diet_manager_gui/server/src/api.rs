@@ -0,0 +1,621 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use chrono::{Local, NaiveDate};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use diet_manager_core::config;
+use diet_manager_core::database::{self, read_database, save_database, DATABASE_CHANGED};
+use diet_manager_core::models::{Database, FoodLogEntry, Meal};
+use diet_manager_core::service::{admin, auth, catalog, logging, reporting};
+use diet_manager_core::undo::UndoManager;
+
+// A transport-independent request, so routes can be exercised without a socket
+pub struct ApiRequest {
+    pub method: String,
+    pub path: String, // Path plus optional query string
+    pub token: Option<String>,
+    pub body: String,
+}
+
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn created(body: Value) -> Self {
+        Self { status: 201, body }
+    }
+
+    pub(crate) fn error(status: u16, message: &str) -> Self {
+        Self { status, body: json!({ "error": message }) }
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginBody {
+    username: String,
+    password: String,
+    #[serde(default)]
+    code: Option<String>, // Needed for accounts with two-factor authentication
+}
+
+#[derive(Deserialize)]
+struct NewFoodBody {
+    id: String,
+    #[serde(default)]
+    name: String,
+    keywords: Vec<String>,
+    calories: f32,
+}
+
+#[derive(Deserialize)]
+struct NewEntryBody {
+    food_id: String,
+    #[serde(default = "default_servings")]
+    servings: f32,
+    date: Option<String>,
+    #[serde(default)]
+    meal: Meal,
+    #[serde(default)]
+    planned: bool,
+}
+
+#[derive(Deserialize)]
+struct ServingsBody {
+    servings: f32,
+}
+
+fn default_servings() -> f32 {
+    1.0
+}
+
+// How long a token stays valid after login
+const SESSION_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+
+struct Session {
+    user_id: String,
+    password: String, // The stored hash at login; a new password ends the session
+    expires: Instant,
+}
+
+pub struct Api {
+    db: Arc<Mutex<Database>>,
+    sessions: Mutex<HashMap<String, Session>>, // token -> session
+    persist: bool,
+}
+
+impl Api {
+    // With `persist` set, every change is written through save_database like the
+    // GUI does. The GUI and CLI may change the same file while the server runs, so
+    // the file is read again whenever it changed, and a change is refused instead
+    // of overwriting theirs if the file changes while a request is handled.
+    pub fn new(db: Arc<Mutex<Database>>, persist: bool) -> Self {
+        Self {
+            db,
+            sessions: Mutex::new(HashMap::new()),
+            persist,
+        }
+    }
+
+    pub fn handle(&self, request: &ApiRequest) -> ApiResponse {
+        if let Err(error) = self.reload_if_changed() {
+            return error;
+        }
+
+        let (path, query) = split_query(&request.path);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        if request.method == "POST" && segments == ["api", "login"] {
+            return self.login(&request.body);
+        }
+
+        let user_id = match self.authorize(request) {
+            Some(user_id) => user_id,
+            None => return ApiResponse::error(401, "Missing or invalid token"),
+        };
+
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["api", "logout"]) => Ok(self.logout(request)),
+            ("GET", ["api", "users"]) => self.list_users(&user_id),
+            ("GET", ["api", "users", "me"]) => self.current_user(&user_id),
+            ("GET", ["api", "foods"]) => self.search_foods(&user_id, &query),
+            ("POST", ["api", "foods"]) => self.add_food(&user_id, &request.body),
+            ("POST", ["api", "foods", food_id, "share"]) => self.share_food(&user_id, food_id),
+            ("GET", ["api", "log"]) => self.list_entries(&user_id, &query),
+            ("POST", ["api", "log"]) => self.add_entry(&user_id, &request.body),
+            ("PATCH", ["api", "log", entry_id]) => self.update_entry(&user_id, entry_id, &request.body),
+            ("DELETE", ["api", "log", entry_id]) => self.remove_entry(&user_id, entry_id),
+            ("POST", ["api", "log", entry_id, "eaten"]) => self.mark_eaten(&user_id, entry_id),
+            ("GET", ["api", "totals"]) => self.totals(&user_id, &query),
+            _ => Err(ApiResponse::error(404, "Not found")),
+        };
+
+        result.unwrap_or_else(|error| error)
+    }
+
+    fn login(&self, body: &str) -> ApiResponse {
+        let body: LoginBody = match serde_json::from_str(body) {
+            Ok(body) => body,
+            Err(e) => return ApiResponse::error(400, &format!("Invalid request body: {}", e)),
+        };
+
+        let user_id = match self.lock_db() {
+            // Failed attempts and lockouts are stored in the database
+            Ok(mut db) => {
+                let result = auth::authenticate(&mut db, &body.username, &body.password).and_then(|user_id| {
+                    if auth::two_factor_enabled(&db, &user_id) {
+                        auth::verify_second_factor(&mut db, &user_id, body.code.as_deref().unwrap_or_default())?;
+                    }
+                    Ok(user_id)
+                });
+                if let Err(error) = self.save(&db) {
+                    return error;
+                }
+                result
+            }
+            Err(error) => return error,
+        };
+
+        match user_id {
+            Ok(user_id) => {
+                let password = match self.lock_db() {
+                    Ok(db) => db.find_user_by_id(&user_id).map(|user| user.password.clone()).unwrap_or_default(),
+                    Err(error) => return error,
+                };
+                let token = Uuid::new_v4().simple().to_string();
+                let mut sessions = self.lock_sessions();
+                sessions.retain(|_, session| session.expires > Instant::now());
+                sessions.insert(token.clone(), Session { user_id: user_id.clone(), password, expires: Instant::now() + SESSION_TTL });
+                ApiResponse::ok(json!({ "token": token, "user_id": user_id }))
+            }
+            Err(e) if e.starts_with("Too many") => ApiResponse::error(429, &e),
+            Err(e) if e == auth::ACCOUNT_DISABLED => ApiResponse::error(403, &e),
+            Err(e) => ApiResponse::error(401, &e),
+        }
+    }
+
+    fn logout(&self, request: &ApiRequest) -> ApiResponse {
+        if let Some(token) = &request.token {
+            self.lock_sessions().remove(token);
+        }
+        ApiResponse::ok(json!({ "logged_out": true }))
+    }
+
+    // Tokens only stay valid for SESSION_TTL, and while their user still exists,
+    // isn't disabled and keeps the password they logged in with. Others are dropped.
+    fn authorize(&self, request: &ApiRequest) -> Option<String> {
+        let token = request.token.as_ref()?;
+        let db = self.lock_db().ok()?;
+        let mut sessions = self.lock_sessions();
+        let session = sessions.get(token)?;
+        let valid = session.expires > Instant::now()
+            && db
+                .find_user_by_id(&session.user_id)
+                .is_some_and(|user| !user.disabled && user.password == session.password);
+        if !valid {
+            sessions.remove(token);
+            return None;
+        }
+        Some(session.user_id.clone())
+    }
+
+    // Usernames are only listed to admins
+    fn list_users(&self, user_id: &str) -> Result<ApiResponse, ApiResponse> {
+        let db = self.lock_db()?;
+        if !auth::is_admin(&db, user_id) {
+            return Err(ApiResponse::error(403, admin::ADMIN_ONLY));
+        }
+        let mut users: Vec<Value> = db.users
+            .values()
+            .map(|user| json!({ "user_id": user.user_id, "username": user.username, "role": user.role }))
+            .collect();
+        users.sort_by(|a, b| a["username"].as_str().cmp(&b["username"].as_str()));
+        Ok(ApiResponse::ok(Value::Array(users)))
+    }
+
+    fn current_user(&self, user_id: &str) -> Result<ApiResponse, ApiResponse> {
+        let db = self.lock_db()?;
+        let user = db.find_user_by_id(user_id).ok_or_else(|| ApiResponse::error(404, "User not found"))?;
+
+        // Never send the password back
+        Ok(ApiResponse::ok(json!({
+            "user_id": user.user_id,
+            "username": user.username,
+            "role": user.role,
+            "profile": user.profile,
+            "daily_target": user.profile.calculate_target_calories(),
+        })))
+    }
+
+    fn search_foods(&self, user_id: &str, query: &HashMap<String, String>) -> Result<ApiResponse, ApiResponse> {
+        let db = self.lock_db()?;
+        let terms = query.get("q").map_or("", String::as_str);
+        let match_all = query.get("all").is_some_and(|all| all == "true" || all == "1");
+
+        Ok(ApiResponse::ok(json!(catalog::search_foods(&db, Some(user_id), terms, match_all))))
+    }
+
+    fn add_food(&self, user_id: &str, body: &str) -> Result<ApiResponse, ApiResponse> {
+        let body: NewFoodBody = parse_body(body)?;
+        let mut db = self.lock_db()?;
+
+        let food_id = catalog::add_basic_food(
+            &mut db,
+            &mut scratch_undo(),
+            user_id,
+            &body.id,
+            &body.name,
+            &body.keywords.join(","),
+            &body.calories.to_string()
+        ).map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        let food = catalog::search_foods(&db, Some(user_id), "", false)
+            .into_iter()
+            .find(|food| food.id == food_id);
+        Ok(ApiResponse::created(json!(food)))
+    }
+
+    fn share_food(&self, user_id: &str, food_id: &str) -> Result<ApiResponse, ApiResponse> {
+        let mut db = self.lock_db()?;
+        let food_id = db.resolve_food_id(user_id, food_id).unwrap_or_else(|| food_id.to_string());
+        let food_id = catalog::share_food(&mut db, &mut scratch_undo(), user_id, &food_id)
+            .map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        let food = catalog::search_foods(&db, Some(user_id), "", false)
+            .into_iter()
+            .find(|food| food.id == food_id);
+        Ok(ApiResponse::ok(json!(food)))
+    }
+
+    fn list_entries(&self, user_id: &str, query: &HashMap<String, String>) -> Result<ApiResponse, ApiResponse> {
+        let date = parse_date(query)?;
+        let db = self.lock_db()?;
+
+        let entries: Vec<Value> = logging::entries_for_day(&db, user_id, &date)
+            .into_iter()
+            .map(|entry| entry_json(&db, &entry))
+            .collect();
+        Ok(ApiResponse::ok(Value::Array(entries)))
+    }
+
+    fn add_entry(&self, user_id: &str, body: &str) -> Result<ApiResponse, ApiResponse> {
+        let body: NewEntryBody = parse_body(body)?;
+        let date = match &body.date {
+            Some(date) => normalize_date(date)?,
+            None => today(),
+        };
+        let mut db = self.lock_db()?;
+
+        let undo = &mut scratch_undo();
+        let added = if body.planned {
+            logging::plan_entry(&mut db, undo, user_id, &date, &body.food_id, body.servings, body.meal)
+        } else {
+            logging::add_entry(&mut db, undo, user_id, &date, &body.food_id, body.servings, body.meal)
+        };
+        let entry_id = added.map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        Ok(ApiResponse::created(find_entry_json(&db, user_id, &entry_id)?))
+    }
+
+    fn update_entry(&self, user_id: &str, entry_id: &str, body: &str) -> Result<ApiResponse, ApiResponse> {
+        let body: ServingsBody = parse_body(body)?;
+        let mut db = self.lock_db()?;
+
+        logging::update_servings(&mut db, &mut scratch_undo(), user_id, entry_id, body.servings)
+            .map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        Ok(ApiResponse::ok(find_entry_json(&db, user_id, entry_id)?))
+    }
+
+    fn mark_eaten(&self, user_id: &str, entry_id: &str) -> Result<ApiResponse, ApiResponse> {
+        let mut db = self.lock_db()?;
+
+        logging::mark_eaten(&mut db, &mut scratch_undo(), user_id, entry_id)
+            .map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        Ok(ApiResponse::ok(find_entry_json(&db, user_id, entry_id)?))
+    }
+
+    fn remove_entry(&self, user_id: &str, entry_id: &str) -> Result<ApiResponse, ApiResponse> {
+        let mut db = self.lock_db()?;
+
+        let removed = logging::remove_entry(&mut db, &mut scratch_undo(), user_id, entry_id)
+            .map_err(|e| service_error(&e))?;
+        self.save(&db)?;
+
+        Ok(ApiResponse::ok(entry_json(&db, &removed)))
+    }
+
+
+    fn totals(&self, user_id: &str, query: &HashMap<String, String>) -> Result<ApiResponse, ApiResponse> {
+        let date = parse_date(query)?;
+        let db = self.lock_db()?;
+        Ok(ApiResponse::ok(json!(reporting::daily_summary(&db, user_id, &date))))
+    }
+
+    fn lock_db(&self) -> Result<MutexGuard<'_, Database>, ApiResponse> {
+        self.db.lock().map_err(|_| ApiResponse::error(500, "Database is unavailable"))
+    }
+
+    fn lock_sessions(&self) -> MutexGuard<'_, HashMap<String, Session>> {
+        // A panic while holding the map can't leave it half-updated, so recover it
+        self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Pick up changes the GUI or CLI saved since the file was last read or written
+    fn reload_if_changed(&self) -> Result<(), ApiResponse> {
+        if !self.persist {
+            return Ok(());
+        }
+        let mut db = self.lock_db()?;
+        let path = config::database_file();
+        if database::changed_on_disk(&path) {
+            *db = read_database(&path)
+                .map_err(|e| ApiResponse::error(500, &format!("Failed to reload database: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    // Called with the database lock held, after a change was made in memory
+    fn save(&self, db: &Database) -> Result<(), ApiResponse> {
+        if !self.persist {
+            return Ok(());
+        }
+        // On a conflict the stale copy is left; the next request reloads the file without this change
+        save_database(db).map_err(|e| match e.to_string() {
+            message if message == DATABASE_CHANGED => ApiResponse::error(409, &message),
+            message => ApiResponse::error(500, &format!("Failed to save database: {}", message)),
+        })
+    }
+}
+
+// Each request is independent, so changes are recorded in a throwaway history
+// that doesn't write to the action log
+fn scratch_undo() -> UndoManager {
+    UndoManager::with_log_path(1, None)
+}
+
+fn entry_json(db: &Database, entry: &FoodLogEntry) -> Value {
+    json!({
+        "id": entry.id,
+        "date": entry.date,
+        "food_id": entry.food_id,
+        "food_name": db.food_name(&entry.food_id),
+        "servings": entry.servings,
+        "meal": entry.meal,
+        "planned": entry.planned,
+        "calories": db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings,
+    })
+}
+
+fn find_entry_json(db: &Database, user_id: &str, entry_id: &str) -> Result<Value, ApiResponse> {
+    logging::find_entry(db, user_id, entry_id)
+        .map(|entry| entry_json(db, entry))
+        .ok_or_else(|| ApiResponse::error(404, "Food log entry not found"))
+}
+
+fn service_error(message: &str) -> ApiResponse {
+    let status = if message == auth::READ_ONLY_ACCOUNT || message == auth::ACCOUNT_DISABLED || message == admin::ADMIN_ONLY {
+        403
+    } else if message.contains("not found") {
+        404
+    } else {
+        400
+    };
+    ApiResponse::error(status, message)
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, ApiResponse> {
+    serde_json::from_str(body).map_err(|e| ApiResponse::error(400, &format!("Invalid request body: {}", e)))
+}
+
+fn parse_date(query: &HashMap<String, String>) -> Result<String, ApiResponse> {
+    match query.get("date") {
+        Some(date) => normalize_date(date),
+        None => Ok(today()),
+    }
+}
+
+fn normalize_date(date: &str) -> Result<String, ApiResponse> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .map_err(|_| ApiResponse::error(400, &format!("Invalid date '{}', expected YYYY-MM-DD", date)))
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn split_query(path: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_component(key), decode_component(value))
+        })
+        .collect();
+    (path, params)
+}
+
+// Undo form encoding: '+' is a space and %XX is a byte
+fn decode_component(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diet_manager_core::models::{ActivityLevel, BasicFood, CalorieCalculationMethod, Gender, Role, UserProfile};
+    use diet_manager_core::units::Units;
+
+    fn profile() -> UserProfile {
+        UserProfile {
+            gender: Gender::Female,
+            height_cm: 165.0,
+            age: 35,
+            calorie_method: CalorieCalculationMethod::MifflinStJeor,
+            weight_kg: 60.0,
+            activity_level: ActivityLevel::Light,
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: Units::Metric,
+        }
+    }
+
+    // An admin, a member and a read-only account sharing a catalog with one food
+    fn setup() -> Api {
+        let mut db = Database::default();
+        auth::register(&mut db, "ada", "admin-pass", profile()).unwrap();
+        auth::register(&mut db, "max", "member-pass", profile()).unwrap();
+        auth::register(&mut db, "val", "viewer-pass", profile()).unwrap();
+        db.users.get_mut("val").unwrap().role = Role::ReadOnly;
+        let apple = BasicFood::from_input("apple", "Apple", "fruit", "95").unwrap();
+        db.basic_foods.insert(apple.id.clone(), apple);
+        Api::new(Arc::new(Mutex::new(db)), false)
+    }
+
+    fn call(api: &Api, method: &str, path: &str, token: Option<&str>, body: Value) -> ApiResponse {
+        api.handle(&ApiRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            token: token.map(str::to_string),
+            body: if body.is_null() { String::new() } else { body.to_string() },
+        })
+    }
+
+    fn login(api: &Api, username: &str, password: &str) -> String {
+        let response = call(api, "POST", "/api/login", None, json!({ "username": username, "password": password }));
+        assert_eq!(response.status, 200, "{}", response.body);
+        response.body["token"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn login_hands_out_a_token_that_opens_protected_routes() {
+        let api = setup();
+        let token = login(&api, "max", "member-pass");
+
+        let me = call(&api, "GET", "/api/users/me", Some(&token), Value::Null);
+        assert_eq!(me.status, 200);
+        assert_eq!(me.body["username"], "max");
+
+        let wrong = call(&api, "POST", "/api/login", None, json!({ "username": "max", "password": "nope" }));
+        assert_eq!(wrong.status, 401);
+        let malformed = call(&api, "POST", "/api/login", None, json!({ "username": "max" }));
+        assert_eq!(malformed.status, 400);
+
+        assert_eq!(call(&api, "POST", "/api/logout", Some(&token), Value::Null).status, 200);
+        assert_eq!(call(&api, "GET", "/api/users/me", Some(&token), Value::Null).status, 401);
+    }
+
+    #[test]
+    fn tokens_expire_and_end_when_the_password_changes() {
+        let api = setup();
+        let token = login(&api, "max", "member-pass");
+        api.lock_sessions().get_mut(&token).unwrap().expires = Instant::now();
+        assert_eq!(call(&api, "GET", "/api/users/me", Some(&token), Value::Null).status, 401);
+        assert!(api.lock_sessions().is_empty());
+
+        let token = login(&api, "max", "member-pass");
+        {
+            let mut db = api.db.lock().unwrap();
+            let (ada, max) = (db.users["ada"].user_id.clone(), db.users["max"].user_id.clone());
+            let mut undo = UndoManager::with_log_path(1, None);
+            admin::reset_password(&mut db, &mut undo, &ada, &max, "new-pass").unwrap();
+        }
+        assert_eq!(call(&api, "GET", "/api/users/me", Some(&token), Value::Null).status, 401);
+        assert!(api.lock_sessions().is_empty());
+    }
+
+    #[test]
+    fn protected_routes_need_a_valid_token() {
+        let api = setup();
+        assert_eq!(call(&api, "GET", "/api/log", None, Value::Null).status, 401);
+        assert_eq!(call(&api, "GET", "/api/log", Some("made-up"), Value::Null).status, 401);
+        assert_eq!(call(&api, "POST", "/api/log", None, json!({ "food_id": "apple" })).status, 401);
+    }
+
+    #[test]
+    fn log_entries_can_be_added_changed_and_removed() {
+        let api = setup();
+        let token = login(&api, "max", "member-pass");
+
+        let added = call(&api, "POST", "/api/log", Some(&token), json!({ "food_id": "apple", "servings": 2.0, "date": "2024-05-01" }));
+        assert_eq!(added.status, 201, "{}", added.body);
+        let entry_id = added.body["id"].as_str().unwrap().to_string();
+
+        let listed = call(&api, "GET", "/api/log?date=2024-05-01", Some(&token), Value::Null);
+        assert_eq!(listed.body.as_array().unwrap().len(), 1);
+
+        let path = format!("/api/log/{}", entry_id);
+        assert_eq!(call(&api, "PATCH", &path, Some(&token), json!({ "servings": 3.0 })).status, 200);
+        assert_eq!(call(&api, "DELETE", &path, Some(&token), Value::Null).status, 200);
+        assert_eq!(call(&api, "DELETE", &path, Some(&token), Value::Null).status, 404);
+
+        let listed = call(&api, "GET", "/api/log?date=2024-05-01", Some(&token), Value::Null);
+        assert!(listed.body.as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn errors_map_to_status_codes() {
+        let api = setup();
+        let member = login(&api, "max", "member-pass");
+        let viewer = login(&api, "val", "viewer-pass");
+        let admin = login(&api, "ada", "admin-pass");
+
+        let bad_body = call(&api, "POST", "/api/log", Some(&member), json!({ "servings": 1.0 }));
+        assert_eq!(bad_body.status, 400);
+        let bad_date = call(&api, "GET", "/api/log?date=soon", Some(&member), Value::Null);
+        assert_eq!(bad_date.status, 400);
+        let unknown_food = call(&api, "POST", "/api/log", Some(&member), json!({ "food_id": "durian" }));
+        assert_eq!(unknown_food.status, 400);
+        assert_eq!(call(&api, "GET", "/api/nothing", Some(&member), Value::Null).status, 404);
+
+        let read_only = call(&api, "POST", "/api/log", Some(&viewer), json!({ "food_id": "apple" }));
+        assert_eq!(read_only.status, 403);
+        assert_eq!(read_only.body["error"], auth::READ_ONLY_ACCOUNT);
+
+        // Only admins see the list of accounts
+        let users = call(&api, "GET", "/api/users", Some(&member), Value::Null);
+        assert_eq!(users.status, 403);
+        assert_eq!(users.body["error"], admin::ADMIN_ONLY);
+        let users = call(&api, "GET", "/api/users", Some(&admin), Value::Null);
+        assert_eq!(users.status, 200);
+        assert_eq!(users.body.as_array().unwrap().len(), 3);
+    }
+}
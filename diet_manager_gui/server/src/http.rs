@@ -0,0 +1,90 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Request, Response, Server};
+use crate::api::{Api, ApiRequest, ApiResponse};
+
+// Requests are small JSON documents; anything larger is refused before it is read
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+// Requests are handled by this many threads; further connections wait their turn
+const WORKERS: usize = 8;
+
+// Open the listening socket, so failures surface before anything is announced
+pub fn bind(addr: &str) -> Result<Server, String> {
+    Server::http(addr).map_err(|e| format!("Could not listen on {}: {}", addr, e))
+}
+
+// Serve the API until the process is stopped. A fixed set of worker threads
+// takes requests from the listener; the Api's locks keep concurrent access to
+// the database safe.
+pub fn serve(api: Arc<Api>, server: Server) {
+    let server = Arc::new(server);
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let api = Arc::clone(&api);
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    respond(&api, request);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+fn respond(api: &Api, mut request: Request) {
+    let api_response = match read_body(&mut request) {
+        Ok(body) => {
+            let token = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+                .map(|token| token.trim().to_string());
+
+            let api_request = ApiRequest {
+                method: request.method().as_str().to_uppercase(),
+                path: request.url().to_string(),
+                token,
+                body,
+            };
+            api.handle(&api_request)
+        }
+        Err(error) => error,
+    };
+
+    let mut response = Response::from_string(api_response.body.to_string())
+        .with_status_code(api_response.status);
+    if let Ok(header) = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]) {
+        response = response.with_header(header);
+    }
+
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send response: {}", e);
+    }
+}
+
+// The body as text, or a 413 when it is over MAX_BODY_BYTES. The declared length
+// is checked first and the read is capped too, for chunked bodies that declare none.
+fn read_body(request: &mut Request) -> Result<String, ApiResponse> {
+    if request.body_length().is_some_and(|length| length > MAX_BODY_BYTES) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    if request.as_reader().take(MAX_BODY_BYTES as u64 + 1).read_to_end(&mut bytes).is_err() {
+        bytes.clear();
+    }
+    if bytes.len() > MAX_BODY_BYTES {
+        return Err(too_large());
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn too_large() -> ApiResponse {
+    ApiResponse::error(413, &format!("Request body is larger than {} bytes", MAX_BODY_BYTES))
+}
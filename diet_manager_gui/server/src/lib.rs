@@ -0,0 +1,3 @@
+// Local HTTP JSON API over the shared diet database
+pub mod api;
+pub mod http;
@@ -0,0 +1,77 @@
+// Serves the diet database to phones and scripts over HTTP
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use clap::Parser;
+use diet_manager_core::config::{self, DataPaths};
+use diet_manager_core::crypto;
+use diet_manager_core::database::{self, load_database};
+use diet_manager_server::api::Api;
+use diet_manager_server::http;
+
+#[derive(Parser)]
+#[command(name = "diet-manager-server", about = "Local HTTP JSON API for the diet database")]
+struct Cli {
+    /// Address to listen on. Use 0.0.0.0 to reach it from other devices on your network.
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    addr: String,
+
+    /// Directory holding database.json (overrides DIET_MANAGER_DATA_DIR)
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+
+    /// Serve a separate per-profile database (overrides DIET_MANAGER_PROFILE)
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let paths = match DataPaths::resolve(cli.data_dir, cli.profile) {
+        Ok(paths) => paths,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let paths = match config::init(paths) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("Error: Could not prepare data directory: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // An encrypted database needs its passphrase before anything is served
+    let database_file = config::database_file();
+    if database::is_locked(&database_file) {
+        if let Err(error) = read_passphrase().and_then(|passphrase| database::unlock_database(&database_file, &passphrase)) {
+            eprintln!("Error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let api = Arc::new(Api::new(Arc::new(Mutex::new(load_database())), true));
+
+    let server = match http::bind(&cli.addr) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Diet Manager API listening on http://{}", cli.addr);
+    println!("Using database {}", paths.database_file().display());
+    http::serve(api, server);
+    ExitCode::SUCCESS
+}
+
+// From DIET_MANAGER_PASSPHRASE when set (e.g. under a service manager),
+// otherwise typed without echo
+fn read_passphrase() -> Result<String, String> {
+    match std::env::var(crypto::PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => rpassword::prompt_password("Database passphrase: ").map_err(|e| format!("Could not read the passphrase: {}", e)),
+    }
+}
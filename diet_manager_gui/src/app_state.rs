@@ -9,4 +9,13 @@ pub enum AppState {
     EditFoodLog,
     UpdateProfile,
     DownloadFoodData,
+    LogExercise,
+    ManageTrackers,
+    Export,
+    Import,
+    ShoppingList,
+    Account,
+    TwoFactor,
+    Unlock,
+    Admin,
 }
\ No newline at end of file
@@ -0,0 +1,225 @@
+use eframe::egui;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+pub struct AccountScreen {
+    current_password: String,
+    new_password: String,
+    confirm_password: String,
+    new_username: String,
+    rename_password: String,
+    delete_password: String,
+    delete_confirm_name: String,
+    confirming_delete: bool,
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl AccountScreen {
+    pub fn new() -> Self {
+        Self {
+            current_password: String::new(),
+            new_password: String::new(),
+            confirm_password: String::new(),
+            new_username: String::new(),
+            rename_password: String::new(),
+            delete_password: String::new(),
+            delete_confirm_name: String::new(),
+            confirming_delete: false,
+            status_message: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
+        let theme = styling::AppTheme::default();
+        let user_id = db.current_user.clone();
+        let username = match db.find_user_by_id(&user_id) {
+            Some(user) => user.username.clone(),
+            None => {
+                *current_state = AppState::Login;
+                return;
+            }
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Account").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label(format!("Signed in as {}", username));
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            styling::card_frame().show(ui, |ui| {
+                styling::section_header(ui, "Change Password");
+
+                egui::Grid::new("account_password_grid")
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Current password:");
+                        ui.add(egui::TextEdit::singleline(&mut self.current_password).password(true).desired_width(250.0));
+                        ui.end_row();
+
+                        ui.label("New password:");
+                        ui.add(egui::TextEdit::singleline(&mut self.new_password).password(true).desired_width(250.0));
+                        ui.end_row();
+
+                        ui.label("Repeat new password:");
+                        ui.add(egui::TextEdit::singleline(&mut self.confirm_password).password(true).desired_width(250.0));
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                if styling::primary_button(ui, "Change Password").clicked() {
+                    self.change_password(db, undo_manager, &user_id);
+                }
+
+                ui.add_space(16.0);
+                styling::section_header(ui, "Change Username");
+
+                egui::Grid::new("account_rename_grid")
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("New username:");
+                        ui.add(egui::TextEdit::singleline(&mut self.new_username).desired_width(250.0));
+                        ui.end_row();
+
+                        ui.label("Current password:");
+                        ui.add(egui::TextEdit::singleline(&mut self.rename_password).password(true).desired_width(250.0));
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                if styling::primary_button(ui, "Rename").clicked() {
+                    let result = service::auth::rename_user(db, undo_manager, &user_id, &self.rename_password, &self.new_username);
+                    self.rename_password.clear();
+                    if result.is_ok() {
+                        self.new_username.clear();
+                    }
+                    self.finish(db, result, "Username changed");
+                }
+
+                ui.add_space(16.0);
+                styling::section_header(ui, "Two-Factor Authentication");
+
+                let enabled = service::auth::two_factor_enabled(db, &user_id);
+                ui.label(if enabled { "On: logging in also needs a code from your authenticator app." } else { "Off" });
+                ui.add_space(8.0);
+                if styling::primary_button(ui, if enabled { "Manage" } else { "Set Up" }).clicked() {
+                    self.status_message = None;
+                    *current_state = AppState::TwoFactor;
+                }
+
+                ui.add_space(16.0);
+                styling::section_header(ui, "Delete Account");
+
+                ui.label("Deletes your account together with your food and exercise logs, trackers, templates and shopping list. This cannot be undone.");
+                ui.add_space(8.0);
+
+                if self.confirming_delete {
+                    self.render_delete_confirmation(ui, db, current_state, undo_manager, &user_id, &username);
+                } else if styling::error_button(ui, "Delete Account").clicked() {
+                    self.confirming_delete = true;
+                    self.status_message = None;
+                }
+
+                if let Some((ref message, is_error)) = self.status_message {
+                    ui.add_space(12.0);
+                    let color = if is_error { theme.error_color } else { theme.success_color };
+                    ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+                }
+            });
+
+            ui.add_space(16.0);
+
+            if styling::warning_button(ui, "Back to Home").clicked() {
+                *self = Self::new();
+                *current_state = AppState::Home;
+            }
+        });
+    }
+
+    fn render_delete_confirmation(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager,
+        user_id: &str,
+        username: &str
+    ) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.label(format!("Type your username ({}) and password to confirm.", username));
+            ui.add_space(4.0);
+
+            egui::Grid::new("account_delete_grid")
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Username:");
+                    ui.add(egui::TextEdit::singleline(&mut self.delete_confirm_name).desired_width(250.0));
+                    ui.end_row();
+
+                    ui.label("Password:");
+                    ui.add(egui::TextEdit::singleline(&mut self.delete_password).password(true).desired_width(250.0));
+                    ui.end_row();
+                });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                let confirmed = self.delete_confirm_name.trim() == username;
+                let delete = ui.add_enabled_ui(confirmed, |ui| styling::error_button(ui, "Delete Forever")).inner;
+                if delete.clicked() {
+                    let result = service::auth::delete_account(db, undo_manager, user_id, &self.delete_password);
+                    self.delete_password.clear();
+                    match result {
+                        Ok(()) => {
+                            if let Err(e) = crate::database::save_database(db) {
+                                eprintln!("Failed to save database: {}", e);
+                            }
+                            *self = Self::new();
+                            *current_state = AppState::Login;
+                        }
+                        Err(e) => self.status_message = Some((e, true)),
+                    }
+                }
+
+                if styling::warning_button(ui, "Cancel").clicked() {
+                    self.confirming_delete = false;
+                    self.delete_password.clear();
+                    self.delete_confirm_name.clear();
+                }
+            });
+        });
+    }
+
+    fn change_password(&mut self, db: &mut Database, undo_manager: &mut UndoManager, user_id: &str) {
+        if self.new_password != self.confirm_password {
+            self.status_message = Some(("The new passwords do not match".to_string(), true));
+            return;
+        }
+
+        let result = service::auth::change_password(db, undo_manager, user_id, &self.current_password, &self.new_password);
+        self.current_password.clear();
+        self.new_password.clear();
+        self.confirm_password.clear();
+        self.finish(db, result, "Password changed");
+    }
+
+    // Account changes are saved right away rather than on exit
+    fn finish(&mut self, db: &Database, result: Result<(), String>, success: &str) {
+        self.status_message = Some(match result {
+            Ok(()) => match crate::database::save_database(db) {
+                Ok(()) => (success.to_string(), false),
+                Err(e) => (format!("{}, but saving failed: {}", success, e), true),
+            },
+            Err(e) => (e, true),
+        });
+    }
+}
@@ -1,13 +1,17 @@
 use eframe::egui;
-use crate::models::{Database, BasicFood};
+use crate::models::Database;
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::gui::undo_manager::UndoManager;
+use crate::undo::UndoManager;
+use crate::service;
+
 pub struct AddBasicFoodScreen {
     new_food_id: String,
     new_food_keywords: String,
     new_food_calories: String,
+    share: bool,
     error_message: Option<String>,
+    status_message: Option<String>,
 }
 
 impl AddBasicFoodScreen {
@@ -16,7 +20,9 @@ impl AddBasicFoodScreen {
             new_food_id: String::new(),
             new_food_keywords: String::new(),
             new_food_calories: String::new(),
+            share: false,
             error_message: None,
+            status_message: None,
         }
     }
 
@@ -33,6 +39,10 @@ impl AddBasicFoodScreen {
             ui.colored_label(egui::Color32::RED, error);
             ui.add_space(12.0);
         }
+        if let Some(status) = &self.status_message {
+            ui.colored_label(styling::AppTheme::default().success_color, status);
+            ui.add_space(12.0);
+        }
 
         styling::card_frame().show(ui, |ui| {
             // Food ID field with enhanced styling
@@ -77,6 +87,10 @@ impl AddBasicFoodScreen {
                 });
             });
 
+            ui.add_space(12.0);
+            ui.checkbox(&mut self.share, "Share with everyone")
+                .on_hover_text("Otherwise only you can see and log this food");
+
             ui.add_space(24.0);
 
             // Save and Cancel buttons
@@ -91,80 +105,89 @@ impl AddBasicFoodScreen {
                     ui.add_space(10.0);
 
                     if styling::success_button(ui, "Save Food").clicked() {
-                        self.save_food(db, current_state);
+                        self.save_food(db, current_state, undo_manager);
                     }
                 });
             });
         });
+
+        ui.add_space(16.0);
+        styling::card_frame().show(ui, |ui| {
+            self.render_private_foods(ui, db, undo_manager);
+        });
+    }
+
+    // Foods only the current user can see, with a way to share them
+    fn render_private_foods(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager) {
+        styling::section_header(ui, "My Private Foods");
+        let user_id = db.current_user.clone();
+        let foods = service::catalog::private_foods(db, &user_id);
+        if foods.is_empty() {
+            ui.label(egui::RichText::new("Foods you add stay private until you share them").italics());
+            return;
+        }
+
+        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("private_foods_grid")
+                .striped(true)
+                .spacing([14.0, 4.0])
+                .show(ui, |ui| {
+                    for food in &foods {
+                        ui.label(&food.name);
+                        ui.label(if food.is_composite { "Composite" } else { "Basic" });
+                        ui.label(format!("{:.0} kcal", food.calories_per_serving));
+                        if ui.button("Share").on_hover_text("Add this food to the shared catalog for everyone").clicked() {
+                            match service::catalog::share_food(db, undo_manager, &user_id, &food.id) {
+                                Ok(_) => {
+                                    self.error_message = None;
+                                    self.status_message = Some(format!("{} is now shared with everyone", food.name));
+                                }
+                                Err(error) => {
+                                    self.status_message = None;
+                                    self.error_message = Some(error);
+                                }
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
     }
 
     fn reset(&mut self) {
         self.new_food_id.clear();
         self.new_food_keywords.clear();
         self.new_food_calories.clear();
+        self.share = false;
         self.error_message = None;
+        self.status_message = None;
     }
 
-    fn save_food(&mut self, db: &mut Database, current_state: &mut AppState) {
-        // Clear previous error
-        self.error_message = None;
-
-        // Validate food ID
-        if self.new_food_id.trim().is_empty() {
-            self.error_message = Some("Food Identifier cannot be empty".to_string());
-            return;
-        }
-
-        // Validate keywords
-        if self.new_food_keywords.trim().is_empty() {
-            self.error_message = Some("Keywords cannot be empty".to_string());
-            return;
-        }
-
-        // Validate calories
-        let calories = match self.new_food_calories.parse::<f32>() {
-            Ok(cal) => {
-                if cal <= 0.0 {
-                    self.error_message = Some("Calories must be a positive number".to_string());
-                    return;
+    fn save_food(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        // The identifier doubles as the name
+        let user_id = db.current_user.clone();
+        match service::catalog::add_basic_food(
+            db,
+            undo_manager,
+            &user_id,
+            &self.new_food_id,
+            &self.new_food_id,
+            &self.new_food_keywords,
+            &self.new_food_calories
+        ) {
+            Ok(food_id) => {
+                if self.share {
+                    if let Err(error) = service::catalog::share_food(db, undo_manager, &user_id, &food_id) {
+                        self.reset();
+                        self.error_message = Some(format!("Saved as a private food, but not shared: {}", error));
+                        return;
+                    }
                 }
-                cal
-            },
-            Err(_) => {
-                self.error_message = Some("Invalid calories value. Please enter a number".to_string());
-                return;
+                // Reset fields and return to home
+                self.reset();
+                *current_state = AppState::Home;
             }
-        };
-
-        // Check for duplicate food ID
-        if db.basic_foods.contains_key(&self.new_food_id) {
-            self.error_message = Some("A food with this identifier already exists".to_string());
-            return;
-        }
-
-        // Validate and process keywords
-        let keywords = self.new_food_keywords
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<String>>();
-
-        if keywords.is_empty() {
-            self.error_message = Some("Please provide at least one valid keyword".to_string());
-            return;
+            Err(error) => self.error_message = Some(error),
         }
-
-        let food = BasicFood {
-            id: self.new_food_id.clone(),
-            name: self.new_food_id.clone(), // Use identifier as name
-            keywords,
-            calories_per_serving: calories,
-        };
-
-        db.basic_foods.insert(self.new_food_id.clone(), food);
-
-        // Reset fields and return to home
-        self.reset();
-        *current_state = AppState::Home;
     }
-}
\ No newline at end of file
+}
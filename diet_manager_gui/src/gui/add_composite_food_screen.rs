@@ -1,8 +1,10 @@
 use eframe::egui;
-use crate::models::{Database, CompositeFood, FoodComponent};
+use crate::models::{Database, FoodComponent};
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::gui::undo_manager::UndoManager;
+use crate::undo::UndoManager;
+use crate::service::{self, catalog::FoodMatch};
+
 pub struct AddCompositeFoodScreen {
     new_food_id: String,
     new_food_name: String,
@@ -11,6 +13,7 @@ pub struct AddCompositeFoodScreen {
     current_food_id: String,
     current_servings: String,
     search_term: String,
+    share: bool,
     error_message: Option<String>,
 }
 
@@ -24,6 +27,7 @@ impl AddCompositeFoodScreen {
             current_food_id: String::new(),
             current_servings: "1.0".to_string(),
             search_term: String::new(),
+            share: false,
             error_message: None,
         }
     }
@@ -91,7 +95,7 @@ impl AddCompositeFoodScreen {
 
                 ui.push_id("components_list", |ui| {
                     for (index, component) in self.selected_components.iter().enumerate() {
-                        let food_name = self.get_food_name(db, &component.food_id);
+                        let food_name = db.food_name(&component.food_id);
                         let calories = self.get_food_calories(db, &component.food_id) * component.servings;
 
                         ui.horizontal(|ui| {
@@ -138,17 +142,11 @@ impl AddCompositeFoodScreen {
                         .desired_width(200.0));
                 });
 
-                // Food selection grid
-                let mut available_foods: Vec<(&String, &str, f32)> = Vec::new();
-
-                // Add basic foods to selection
-                for (id, food) in &db.basic_foods {
-                    if self.search_term.is_empty() ||
-                       id.to_lowercase().contains(&self.search_term.to_lowercase()) ||
-                       food.name.to_lowercase().contains(&self.search_term.to_lowercase()) {
-                        available_foods.push((id, &food.name, food.calories_per_serving));
-                    }
-                }
+                // Food selection grid (basic foods only, best match first)
+                let available_foods: Vec<FoodMatch> = service::catalog::search_foods(db, Some(&db.current_user), &self.search_term, false)
+                    .into_iter()
+                    .filter(|food| !food.is_composite)
+                    .collect();
 
                 ui.label("Select food:");
                 egui::ScrollArea::vertical()
@@ -158,8 +156,9 @@ impl AddCompositeFoodScreen {
                             .striped(true)
                             .spacing([8.0, 4.0])
                             .show(ui, |ui| {
-                                for (id, name, calories) in available_foods {
-                                    ui.radio_value(&mut self.current_food_id, id.clone(), format!("{} ({:.0} kcal)", name, calories));
+                                for food in available_foods {
+                                    let lock = if food.is_private { " 🔒" } else { "" };
+                                    ui.radio_value(&mut self.current_food_id, food.id, format!("{}{} ({:.0} kcal)", food.name, lock, food.calories_per_serving));
                                     ui.end_row();
                                 }
                             });
@@ -180,6 +179,10 @@ impl AddCompositeFoodScreen {
                 });
             });
 
+            ui.add_space(16.0);
+            ui.checkbox(&mut self.share, "Share with everyone")
+                .on_hover_text("Otherwise only you can see and log this food. Its components must be shared too.");
+
             ui.add_space(20.0);
 
             // Error message
@@ -202,7 +205,7 @@ impl AddCompositeFoodScreen {
                     ui.add_space(10.0);
 
                     if styling::success_button(ui, "Save Food").clicked() {
-                        self.save_composite_food(db, current_state);
+                        self.save_composite_food(db, current_state, undo_manager);
                     }
                 });
             });
@@ -247,62 +250,35 @@ impl AddCompositeFoodScreen {
     }
 
 
-     fn save_composite_food(&mut self, db: &mut Database, current_state: &mut AppState) {
-        // Clear previous error
-        self.error_message = None;
-
-        // Validate food ID
-        if self.new_food_id.trim().is_empty() {
-            self.error_message = Some("Food Identifier cannot be empty".to_string());
-            return;
-        }
-
-        // Check for duplicate food ID
-        if db.basic_foods.contains_key(&self.new_food_id) ||
-           db.composite_foods.contains_key(&self.new_food_id) {
-            self.error_message = Some("A food with this identifier already exists".to_string());
-            return;
-        }
-
-        // Validate food name
-        if self.new_food_name.trim().is_empty() {
-            self.error_message = Some("Food Name cannot be empty".to_string());
-            return;
-        }
-
-        // Validate keywords
-        let keywords = self.new_food_keywords
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<String>>();
-
-        if keywords.is_empty() {
-            self.error_message = Some("Please provide at least one valid keyword".to_string());
-            return;
-        }
-
-        // Validate components
-        if self.selected_components.is_empty() {
-            self.error_message = Some("At least one component is required".to_string());
-            return;
+    fn save_composite_food(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        match service::catalog::add_composite_food(
+            db,
+            undo_manager,
+            &user_id,
+            &self.new_food_id,
+            &self.new_food_name,
+            &self.new_food_keywords,
+            self.selected_components.clone()
+        ) {
+            Ok(food_id) => {
+                if self.share {
+                    // The food is saved either way; stay here if it could not be shared
+                    if let Err(error) = service::catalog::share_food(db, undo_manager, &user_id, &food_id) {
+                        self.clear_fields();
+                        self.error_message = Some(format!("Saved as a private food, but not shared: {}", error));
+                        return;
+                    }
+                }
+                // Reset form and return to home
+                self.clear_fields();
+                *current_state = AppState::Home;
+            }
+            Err(error) => self.error_message = Some(error),
         }
-
-        // Create and save the composite food
-        let food = CompositeFood {
-            id: self.new_food_id.clone(),
-            name: self.new_food_name.clone(),
-            keywords,
-            components: self.selected_components.clone(),
-        };
-
-        db.composite_foods.insert(self.new_food_id.clone(), food);
-
-        // Reset form and return to home
-        self.clear_fields();
-        *current_state = AppState::Home;
     }
-     fn clear_fields(&mut self) {
+
+    fn clear_fields(&mut self) {
         self.new_food_id.clear();
         self.new_food_name.clear();
         self.new_food_keywords.clear();
@@ -310,20 +286,10 @@ impl AddCompositeFoodScreen {
         self.current_food_id.clear();
         self.current_servings = "1.0".to_string();
         self.search_term.clear();
+        self.share = false;
         self.error_message = None;
     }
 
-    // Helper to get food name
-    fn get_food_name(&self, db: &Database, food_id: &str) -> String {
-        if let Some(food) = db.basic_foods.get(food_id) {
-            food.name.clone()
-        } else if let Some(food) = db.composite_foods.get(food_id) {
-            food.name.clone()
-        } else {
-            food_id.to_string()
-        }
-    }
-
     // Helper to get food calories
     fn get_food_calories(&self, db: &Database, food_id: &str) -> f32 {
         db.get_food_calories(food_id).unwrap_or(0.0)
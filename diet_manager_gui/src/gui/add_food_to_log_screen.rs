@@ -1,17 +1,26 @@
 use eframe::egui;
-use chrono::{ Local, NaiveDate };
-use crate::models::{ Database, FoodLogEntry };
+use chrono::{ Local, NaiveDate, Timelike };
+use crate::models::{Database, Meal};
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::models::{ BasicFood, CompositeFood};
-use crate::gui::undo_manager::UndoManager;
+use crate::gui::calendar::Calendar;
+use crate::undo::UndoManager;
+use crate::service::{self, catalog::{FoodMatch, FoodUsage}};
+
+// How many foods the Recent and Frequent lists show
+const QUICK_ADD_LIMIT: usize = 5;
+
 pub struct AddFoodToLogScreen {
     selected_food_id: String,
     servings: f32,
     keywords: String,
     match_all_keywords: bool,
     selected_date: NaiveDate,
+    calendar: Calendar,
+    meal: Meal,
+    plan_only: bool, // Add entries as planned instead of eaten
     error_message: Option<String>,
+    status_message: Option<String>,
     show_basic_foods: bool,
     show_composite_foods: bool,
 }
@@ -24,7 +33,11 @@ impl AddFoodToLogScreen {
             keywords: String::new(),
             match_all_keywords: false,
             selected_date: Local::now().date_naive(),
+            calendar: Calendar::new("add_food_calendar", true),
+            meal: meal_for_hour(Local::now().hour()),
+            plan_only: false,
             error_message: None,
+            status_message: None,
             show_basic_foods: true,
             show_composite_foods: true,
         }
@@ -41,40 +54,27 @@ impl AddFoodToLogScreen {
         styling::card_frame().show(ui, |ui| {
             // Date selection
             styling::section_header(ui, "Date");
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("📅").size(20.0));
+            self.calendar.show(ui, db, &mut self.selected_date);
 
-                if ui.button("◀").clicked() {
-                    self.selected_date = self.selected_date
-                        .pred_opt()
-                        .unwrap_or(self.selected_date);
-                }
+            ui.add_space(16.0);
 
-                ui.label(
-                    egui::RichText
-                        ::new(self.selected_date.format("%A, %B %d, %Y").to_string())
-                        .size(16.0)
-                );
+            // Meal selection
+            styling::section_header(ui, "Meal");
+            ui.horizontal(|ui| {
+                for meal in Meal::ALL {
+                    ui.selectable_value(&mut self.meal, meal, meal.label());
+                }
+            });
 
-                let today = Local::now().date_naive();
-                let is_future = self.selected_date >= today.succ_opt().unwrap_or(today);
-                let next_button = ui.button("▶");
+            if self.selected_date > Local::now().date_naive() {
+                ui.label(egui::RichText::new("Food for a future date is added to your plan").italics());
+            } else {
+                ui.checkbox(&mut self.plan_only, "Plan only (mark as eaten later from the daily log)");
+            }
 
-                if next_button.clicked() && !is_future {
-                    self.selected_date = self.selected_date
-                        .succ_opt()
-                        .unwrap_or(self.selected_date);
-                }
+            ui.add_space(16.0);
 
-                if is_future {
-                    ui.label(
-                        egui::RichText
-                            ::new("Cannot select future dates")
-                            .color(styling::AppTheme::default().error_color)
-                            .size(14.0)
-                    );
-                }
-            });
+            self.render_quick_add(ui, db, undo_manager);
 
             ui.add_space(16.0);
 
@@ -82,7 +82,7 @@ impl AddFoodToLogScreen {
             if !self.selected_food_id.is_empty() {
                 styling::section_header(ui, "Selected Food");
                 ui.horizontal(|ui| {
-                    let food_name = self.get_food_name(db, &self.selected_food_id);
+                    let food_name = db.food_name(&self.selected_food_id);
                     let calories_per_serving = db
                         .get_food_calories(&self.selected_food_id)
                         .unwrap_or(0.0);
@@ -127,7 +127,7 @@ impl AddFoodToLogScreen {
                 ui.add(
                     egui::TextEdit
                         ::singleline(&mut self.keywords)
-                        .hint_text("Search by name or keywords")
+                        .hint_text("Search by name or keywords (typos are fine)")
                         .desired_width(300.0)
                 );
             });
@@ -149,175 +149,25 @@ impl AddFoodToLogScreen {
                 ::vertical()
                 .max_height(300.0)
                 .show(ui, |ui| {
-                    let keywords_vec: Vec<String> = self.keywords
-                        .split_whitespace()
-                        .map(|s| s.to_string())
-                        .collect();
+                    let results = service::catalog::search_foods(db, Some(&db.current_user), &self.keywords, self.match_all_keywords);
                     let mut found_foods = false;
 
-                    // Show basic foods - precompute matches
                     if self.show_basic_foods {
-                        // Create a vector of matching foods to avoid borrowing issues
-                        let matching_foods: Vec<(&String, &BasicFood)> = db.basic_foods
-                            .iter()
-                            .filter(|(_, food)| {
-                                if keywords_vec.is_empty() {
-                                    return true;
-                                }
-
-                                // Combine name and keywords for searching
-                                let search_text = format!(
-                                    "{} {}",
-                                    food.name.to_lowercase(),
-                                    food.keywords.iter().map(|k| k.to_lowercase()).collect::<Vec<_>>().join(" ")
-                                );
-
-                                if self.match_all_keywords {
-                                    keywords_vec
-                                        .iter()
-                                        .all(|kw|
-                                            search_text.contains(&kw.to_lowercase())
-                                        )
-                                } else {
-                                    keywords_vec
-                                        .iter()
-                                        .any(|kw|
-                                            search_text.contains(&kw.to_lowercase())
-                                        )
-                                }
-                            })
-                            .collect();
+                        let matching_foods: Vec<&FoodMatch> = results.iter().filter(|f| !f.is_composite).collect();
+                        found_foods |= !matching_foods.is_empty();
 
                         ui.collapsing(egui::RichText::new("Basic Foods").size(16.0).strong(), |ui| {
-                            egui::Grid
-                                ::new("basic_foods_grid")
-                                .striped(true)
-                                .spacing([10.0, 6.0])
-                                .show(ui, |ui| {
-                                    // Header row
-                                    ui.label(egui::RichText::new("Name").strong());
-                                    ui.label(egui::RichText::new("Calories").strong());
-                                    ui.label(egui::RichText::new("Keywords").strong());
-                                    ui.label("");
-                                    ui.end_row();
-
-                                    // Food rows
-                                    let mut foods_shown = false;
-                                    for (id, food) in matching_foods {
-                                        foods_shown = true;
-                                        found_foods = true;
-
-                                        ui.label(&food.name);
-                                        ui.label(format!("{:.0} kcal", food.calories_per_serving));
-                                        ui.label(food.keywords.join(", "));
-
-                                        let is_selected = self.selected_food_id == *id;
-                                        if
-                                            ui
-                                                .selectable_label(is_selected, if is_selected {
-                                                    "Selected"
-                                                } else {
-                                                    "Select"
-                                                })
-                                                .clicked()
-                                        {
-                                            self.selected_food_id = id.clone();
-                                        }
-                                        ui.end_row();
-                                    }
-
-                                    if !foods_shown {
-                                        ui.label("No matching basic foods found");
-                                        ui.end_row();
-                                    }
-                                });
+                            self.render_food_grid(ui, "basic_foods_grid", &matching_foods, "No matching basic foods found");
                         });
                     }
 
-                    // Show composite foods - similar approach
                     if self.show_composite_foods {
-                        // Create a vector of matching foods to avoid borrowing issues
-                        let matching_foods: Vec<(&String, &CompositeFood)> = db.composite_foods
-                            .iter()
-                            .filter(|(_, food)| {
-                                if keywords_vec.is_empty() {
-                                    return true;
-                                }
-
-                                // Combine name and keywords for searching
-                                let search_text = format!(
-                                    "{} {}",
-                                    food.name.to_lowercase(),
-                                    food.keywords.iter().map(|k| k.to_lowercase()).collect::<Vec<_>>().join(" ")
-                                );
-
-                                if self.match_all_keywords {
-                                    keywords_vec
-                                        .iter()
-                                        .all(|kw|
-                                            search_text.contains(&kw.to_lowercase())
-                                        )
-                                } else {
-                                    keywords_vec
-                                        .iter()
-                                        .any(|kw|
-                                            search_text.contains(&kw.to_lowercase())
-                                        )
-                                }
-                            })
-                            .collect();
-
-                        ui.collapsing(
-                            egui::RichText::new("Composite Foods").size(16.0).strong(),
-                            |ui| {
-                                egui::Grid
-                                    ::new("composite_foods_grid")
-                                    .striped(true)
-                                    .spacing([10.0, 6.0])
-                                    .show(ui, |ui| {
-                                        // Header row
-                                        ui.label(egui::RichText::new("Name").strong());
-                                        ui.label(egui::RichText::new("Calories").strong());
-                                        ui.label(egui::RichText::new("Keywords").strong());
-                                        ui.label("");
-                                        ui.end_row();
-
-                                        // Food rows
-                                        let mut foods_shown = false;
-                                        for (id, food) in matching_foods {
-                                            foods_shown = true;
-                                            found_foods = true;
-
-                                            ui.label(&food.name);
-
-                                            // Calculate total calories for composite food
-                                            let calories = db.get_food_calories(id).unwrap_or(0.0);
-                                            ui.label(format!("{:.0} kcal", calories));
-
-                                            ui.label(food.keywords.join(", "));
-
-                                            let is_selected = self.selected_food_id == *id;
-                                            if
-                                                ui
-                                                    .selectable_label(is_selected, if is_selected {
-                                                        "Selected"
-                                                    } else {
-                                                        "Select"
-                                                    })
-                                                    .clicked()
-                                            {
-                                                self.selected_food_id = id.clone();
-                                            }
-                                            ui.end_row();
-                                        }
-
-                                        if !foods_shown {
-                                            ui.label("No matching composite foods found");
-                                            ui.end_row();
-                                        }
-                                    });
-                            }
-                        );
+                        let matching_foods: Vec<&FoodMatch> = results.iter().filter(|f| f.is_composite).collect();
+                        found_foods |= !matching_foods.is_empty();
+
+                        ui.collapsing(egui::RichText::new("Composite Foods").size(16.0).strong(), |ui| {
+                            self.render_food_grid(ui, "composite_foods_grid", &matching_foods, "No matching composite foods found");
+                        });
                     }
 
                     if !found_foods {
@@ -337,7 +187,12 @@ impl AddFoodToLogScreen {
 
             ui.add_space(16.0);
 
-            // Error message
+            // Status and error messages
+            if let Some(ref status) = self.status_message {
+                ui.colored_label(styling::AppTheme::default().success_color, status);
+                ui.add_space(8.0);
+            }
+
             if let Some(ref error) = self.error_message {
                 ui.colored_label(
                     styling::AppTheme::default().error_color,
@@ -355,54 +210,214 @@ impl AddFoodToLogScreen {
 
                     ui.add_space(10.0);
 
-                    if styling::success_button(ui, "Add to Log").clicked() {
+                    let label = if self.is_planning() { "Add to Plan" } else { "Add to Log" };
+                    if styling::success_button(ui, label).clicked() {
                         self.add_to_log(db, current_state, undo_manager);
-
                     }
                 });
             });
         });
     }
 
-    fn add_to_log(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
-        if self.selected_food_id.is_empty() {
-            self.error_message = Some("Please select a food to add".to_string());
-            return;
+    // One-click re-logging of recent and frequent foods with their last servings
+    fn render_quick_add(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        let recent = service::catalog::recent_foods(db, &user_id, QUICK_ADD_LIMIT);
+        let frequent = service::catalog::frequent_foods(db, &user_id, QUICK_ADD_LIMIT);
+
+        styling::section_header(ui, "Quick Add");
+
+        let mut to_add: Option<FoodUsage> = None;
+        if recent.is_empty() {
+            ui.label(egui::RichText::new("Foods you log will show up here").italics());
+        } else {
+            ui.columns(2, |columns| {
+                columns[0].label(egui::RichText::new("Recent").strong());
+                for food in &recent {
+                    if self.quick_add_button(&mut columns[0], db, food, None).clicked() {
+                        to_add = Some(food.clone());
+                    }
+                }
+
+                columns[1].label(egui::RichText::new("Frequent").strong());
+                for food in &frequent {
+                    if self.quick_add_button(&mut columns[1], db, food, Some(food.times_logged)).clicked() {
+                        to_add = Some(food.clone());
+                    }
+                }
+            });
+        }
+
+        let yesterday = self.selected_date.pred_opt().unwrap_or(self.selected_date);
+        let copy_label = format!("Copy yesterday's {}", self.meal.label().to_lowercase());
+        if ui.button(copy_label).on_hover_text(format!("Log everything from {}'s {} again", yesterday.format("%b %d"), self.meal.label().to_lowercase())).clicked() {
+            let from = yesterday.format("%Y-%m-%d").to_string();
+            let to = self.selected_date.format("%Y-%m-%d").to_string();
+            match service::logging::copy_entries(db, undo_manager, &user_id, &from, &[to], Some(self.meal)) {
+                Ok(count) => self.show_status(format!("Copied {} item(s) to {}", count, self.meal.label().to_lowercase())),
+                Err(error) => self.show_error(error),
+            }
+        }
+
+        self.render_templates(ui, db, undo_manager);
+
+        if let Some(food) = to_add {
+            let date = self.selected_date.format("%Y-%m-%d").to_string();
+            match self.add_entry(db, undo_manager, &date, &food.food_id, food.last_servings) {
+                Ok(()) => self.show_status(format!(
+                    "{} {} x{:.1} to {}",
+                    if self.is_planning() { "Planned" } else { "Added" },
+                    db.food_name(&food.food_id),
+                    food.last_servings,
+                    self.meal.label().to_lowercase()
+                )),
+                Err(error) => self.show_error(error),
+            }
         }
+    }
 
-        if self.servings <= 0.0 {
-            self.error_message = Some("Please enter a valid number of servings".to_string());
+    // Saved meal templates, added to the selected meal in one go
+    fn render_templates(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        let templates = service::templates::list_templates(db, &user_id);
+        if templates.is_empty() {
             return;
         }
 
-        // Get food name for better description
-        let food_name = self.get_food_name(db, &self.selected_food_id);
+        ui.add_space(8.0);
+        ui.label(egui::RichText::new("Templates").strong());
+
+        let mut to_apply = None;
+        let mut to_delete = None;
+        for template in &templates {
+            let calories: f32 = template.items
+                .iter()
+                .map(|item| db.get_food_calories(&item.food_id).unwrap_or(0.0) * item.servings)
+                .sum();
+            let foods = template.items
+                .iter()
+                .map(|item| format!("{} x{:.1}", db.food_name(&item.food_id), item.servings))
+                .collect::<Vec<_>>()
+                .join(", ");
 
-        // Record state before change
-        undo_manager.record_action(db.clone(), &format!("Added {} to food log", food_name));
+            ui.horizontal(|ui| {
+                if ui.button(format!("+ {} ({:.0} kcal)", template.name, calories)).on_hover_text(foods).clicked() {
+                    to_apply = Some(template.name.clone());
+                }
+                if ui.small_button("🗑").on_hover_text("Delete template").clicked() {
+                    to_delete = Some(template.name.clone());
+                }
+            });
+        }
 
-        let selected_date_str = self.selected_date.format("%Y-%m-%d").to_string();
+        let date = self.selected_date.format("%Y-%m-%d").to_string();
+        if let Some(name) = to_apply {
+            match service::templates::apply_template(db, undo_manager, &user_id, &name, &date, self.meal) {
+                Ok(count) => self.show_status(format!("Added {} ({} foods) to {}", name, count, self.meal.label().to_lowercase())),
+                Err(error) => self.show_error(error),
+            }
+        }
+        if let Some(name) = to_delete {
+            match service::templates::delete_template(db, undo_manager, &user_id, &name) {
+                Ok(()) => self.show_status(format!("Deleted template {}", name)),
+                Err(error) => self.show_error(error),
+            }
+        }
+    }
 
-        let entry = FoodLogEntry {
-            date: selected_date_str,
-            food_id: self.selected_food_id.clone(),
-            servings: self.servings,
-            user_id: db.current_user.clone(),
-        };
+    fn quick_add_button(&self, ui: &mut egui::Ui, db: &Database, food: &FoodUsage, times_logged: Option<usize>) -> egui::Response {
+        let mut label = format!("+ {} x{:.1}", db.food_name(&food.food_id), food.last_servings);
+        if let Some(times) = times_logged {
+            label.push_str(&format!("  ({}×)", times));
+        }
 
-        db.food_logs.entry(db.current_user.clone()).or_insert_with(Vec::new).push(entry);
+        let calories = db.get_food_calories(&food.food_id).unwrap_or(0.0) * food.last_servings;
+        ui.button(label).on_hover_text(format!("{:.0} kcal, last logged {}", calories, food.last_logged))
+    }
 
-        *current_state = AppState::Home;
+    fn is_planning(&self) -> bool {
+        self.plan_only || self.selected_date > Local::now().date_naive()
     }
 
-    // Helper to get food name
-    fn get_food_name(&self, db: &Database, food_id: &str) -> String {
-        if let Some(food) = db.basic_foods.get(food_id) {
-            food.name.clone()
-        } else if let Some(food) = db.composite_foods.get(food_id) {
-            food.name.clone()
+    // Log the food as eaten, or plan it when planning
+    fn add_entry(&self, db: &mut Database, undo_manager: &mut UndoManager, date: &str, food_id: &str, servings: f32) -> Result<(), String> {
+        let user_id = db.current_user.clone();
+        let added = if self.is_planning() {
+            service::logging::plan_entry(db, undo_manager, &user_id, date, food_id, servings, self.meal)
         } else {
-            food_id.to_string()
+            service::logging::add_entry(db, undo_manager, &user_id, date, food_id, servings, self.meal)
+        };
+        added.map(|_| ())
+    }
+
+    fn show_status(&mut self, message: String) {
+        self.status_message = Some(message);
+        self.error_message = None;
+    }
+
+    fn show_error(&mut self, message: String) {
+        self.error_message = Some(message);
+        self.status_message = None;
+    }
+
+    fn render_food_grid(&mut self, ui: &mut egui::Ui, grid_id: &str, foods: &[&FoodMatch], empty_label: &str) {
+        egui::Grid
+            ::new(grid_id)
+            .striped(true)
+            .spacing([10.0, 6.0])
+            .show(ui, |ui| {
+                // Header row
+                ui.label(egui::RichText::new("Name").strong());
+                ui.label(egui::RichText::new("Calories").strong());
+                ui.label(egui::RichText::new("Keywords").strong());
+                ui.label("");
+                ui.end_row();
+
+                // Food rows
+                for food in foods {
+                    if food.is_private {
+                        ui.label(format!("{} 🔒", food.name)).on_hover_text("Private: only you can see this food");
+                    } else {
+                        ui.label(&food.name);
+                    }
+                    ui.label(format!("{:.0} kcal", food.calories_per_serving));
+                    ui.label(food.keywords.join(", "));
+
+                    let is_selected = self.selected_food_id == food.id;
+                    if ui.selectable_label(is_selected, if is_selected { "Selected" } else { "Select" }).clicked() {
+                        self.selected_food_id = food.id.clone();
+                    }
+                    ui.end_row();
+                }
+
+                if foods.is_empty() {
+                    ui.label(empty_label);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn add_to_log(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let date = self.selected_date.format("%Y-%m-%d").to_string();
+
+        let food_id = self.selected_food_id.clone();
+        match self.add_entry(db, undo_manager, &date, &food_id, self.servings) {
+            Ok(()) => {
+                self.error_message = None;
+                self.status_message = None;
+                *current_state = AppState::Home;
+            }
+            Err(error) => self.error_message = Some(error),
         }
     }
-}
\ No newline at end of file
+}
+
+// Pre-select the meal that fits the time of day
+fn meal_for_hour(hour: u32) -> Meal {
+    match hour {
+        4..=10 => Meal::Breakfast,
+        11..=15 => Meal::Lunch,
+        17..=21 => Meal::Dinner,
+        _ => Meal::Snack,
+    }
+}
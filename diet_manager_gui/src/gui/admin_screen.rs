@@ -0,0 +1,244 @@
+use eframe::egui;
+use crate::models::{Database, Role};
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+const AUDIT_ENTRIES_SHOWN: usize = 50;
+const FOODS_SHOWN: usize = 20;
+
+pub struct AdminScreen {
+    resetting: Option<(String, String)>, // (user_id, username) whose password is being reset
+    new_password: String,
+    confirm_password: String,
+    food_query: String,
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl AdminScreen {
+    pub fn new() -> Self {
+        Self {
+            resetting: None,
+            new_password: String::new(),
+            confirm_password: String::new(),
+            food_query: String::new(),
+            status_message: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
+        let theme = styling::AppTheme::default();
+        let admin_id = db.current_user.clone();
+        let users = match service::admin::user_stats(db, &admin_id) {
+            Ok(users) => users,
+            Err(_) => {
+                *current_state = AppState::Home;
+                return;
+            }
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Admin Console").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Manage accounts and the shared food catalog");
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if let Some((ref message, is_error)) = self.status_message {
+                let color = if is_error { theme.error_color } else { theme.success_color };
+                ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+                ui.add_space(8.0);
+            }
+
+            styling::card_frame().show(ui, |ui| {
+                styling::section_header(ui, "Users");
+
+                egui::Grid::new("admin_users_grid")
+                    .striped(true)
+                    .spacing([14.0, 6.0])
+                    .show(ui, |ui| {
+                        for heading in ["Username", "Role", "Status", "2FA", "Food entries", "Days logged", "Last logged", "Exercise", ""] {
+                            ui.label(egui::RichText::new(heading).strong());
+                        }
+                        ui.end_row();
+
+                        for user in &users {
+                            ui.label(&user.username);
+
+                            let mut role = user.role;
+                            egui::ComboBox::from_id_source(("admin_role", &user.user_id))
+                                .selected_text(role.label())
+                                .show_ui(ui, |ui| {
+                                    for option in Role::ALL {
+                                        ui.selectable_value(&mut role, option, option.label());
+                                    }
+                                });
+                            if role != user.role {
+                                let result = service::admin::set_role(db, undo_manager, &admin_id, &user.user_id, role);
+                                self.finish(db, result, &format!("{} is now {}", user.username, role.label()));
+                            }
+
+                            let status = if user.disabled {
+                                egui::RichText::new("Disabled").color(theme.error_color)
+                            } else if user.locked_out {
+                                egui::RichText::new("Locked out").color(theme.warning_color)
+                            } else {
+                                egui::RichText::new("Active")
+                            };
+                            ui.label(status);
+                            ui.label(if user.two_factor { "On" } else { "Off" });
+                            ui.label(user.food_entries.to_string());
+                            ui.label(user.days_logged.to_string());
+                            ui.label(user.last_logged.as_deref().unwrap_or("Never"));
+                            ui.label(user.exercise_entries.to_string());
+
+                            ui.horizontal(|ui| {
+                                let (toggle, done) = if user.disabled { ("Enable", "Enabled") } else { ("Disable", "Disabled") };
+                                if ui.button(toggle).clicked() {
+                                    let result = service::admin::set_disabled(db, undo_manager, &admin_id, &user.user_id, !user.disabled);
+                                    self.finish(db, result, &format!("{} {}", done, user.username));
+                                }
+                                if ui.button("Reset Password").clicked() {
+                                    self.resetting = Some((user.user_id.clone(), user.username.clone()));
+                                    self.new_password.clear();
+                                    self.confirm_password.clear();
+                                    self.status_message = None;
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+
+                if self.resetting.is_some() {
+                    ui.add_space(12.0);
+                    self.render_password_reset(ui, db, undo_manager, &admin_id);
+                }
+            });
+
+            ui.add_space(16.0);
+            styling::card_frame().show(ui, |ui| {
+                self.render_foods(ui, db, undo_manager, &admin_id);
+            });
+
+            ui.add_space(16.0);
+            styling::card_frame().show(ui, |ui| {
+                styling::section_header(ui, "Audit Log");
+                let entries = service::admin::audit_log(db, &admin_id, AUDIT_ENTRIES_SHOWN).unwrap_or_default();
+                if entries.is_empty() {
+                    ui.label("Nothing recorded yet");
+                }
+                egui::Grid::new("admin_audit_grid")
+                    .striped(true)
+                    .spacing([14.0, 4.0])
+                    .show(ui, |ui| {
+                        for entry in entries {
+                            ui.label(egui::RichText::new(entry.timestamp).monospace().size(12.0));
+                            ui.label(entry.username);
+                            ui.label(entry.event);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            ui.add_space(16.0);
+
+            if styling::warning_button(ui, "Back to Home").clicked() {
+                *self = Self::new();
+                *current_state = AppState::Home;
+            }
+        });
+    }
+
+    fn render_password_reset(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager, admin_id: &str) {
+        let (user_id, username) = match &self.resetting {
+            Some(resetting) => resetting.clone(),
+            None => return,
+        };
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.label(egui::RichText::new(format!("New password for {}", username)).strong());
+            ui.add_space(4.0);
+
+            egui::Grid::new("admin_reset_grid")
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("New password:");
+                    ui.add(egui::TextEdit::singleline(&mut self.new_password).password(true).desired_width(250.0));
+                    ui.end_row();
+
+                    ui.label("Repeat new password:");
+                    ui.add(egui::TextEdit::singleline(&mut self.confirm_password).password(true).desired_width(250.0));
+                    ui.end_row();
+                });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if styling::primary_button(ui, "Set Password").clicked() {
+                    if self.new_password != self.confirm_password {
+                        self.status_message = Some(("The new passwords do not match".to_string(), true));
+                    } else {
+                        let result = service::admin::reset_password(db, undo_manager, admin_id, &user_id, &self.new_password);
+                        if result.is_ok() {
+                            self.resetting = None;
+                        }
+                        self.finish(db, result, &format!("Password of {} reset", username));
+                    }
+                    self.new_password.clear();
+                    self.confirm_password.clear();
+                }
+                if styling::warning_button(ui, "Cancel").clicked() {
+                    self.resetting = None;
+                    self.new_password.clear();
+                    self.confirm_password.clear();
+                }
+            });
+        });
+    }
+
+    fn render_foods(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager, admin_id: &str) {
+        styling::section_header(ui, "Shared Foods");
+        ui.label("Only foods that nobody has logged and that are not part of a composite food can be deleted.");
+        ui.add_space(4.0);
+        ui.add(egui::TextEdit::singleline(&mut self.food_query).hint_text("Search foods").desired_width(250.0));
+        ui.add_space(4.0);
+
+        let foods = service::catalog::search_foods(db, None, &self.food_query, false);
+        egui::Grid::new("admin_foods_grid")
+            .striped(true)
+            .spacing([14.0, 4.0])
+            .show(ui, |ui| {
+                for food in foods.iter().take(FOODS_SHOWN) {
+                    ui.label(&food.name);
+                    ui.label(egui::RichText::new(&food.id).monospace().size(12.0));
+                    ui.label(format!("{:.0} kcal", food.calories_per_serving));
+                    if styling::error_button(ui, "Delete").clicked() {
+                        let result = service::admin::delete_food(db, undo_manager, admin_id, &food.id);
+                        self.finish(db, result, &format!("Deleted {}", food.name));
+                    }
+                    ui.end_row();
+                }
+            });
+        if foods.len() > FOODS_SHOWN {
+            ui.label(format!("{} more; narrow the search to see them", foods.len() - FOODS_SHOWN));
+        }
+    }
+
+    // Admin changes are saved right away, like account changes
+    fn finish(&mut self, db: &Database, result: Result<(), String>, success: &str) {
+        self.status_message = Some(match result {
+            Ok(()) => match crate::database::save_database(db) {
+                Ok(()) => (success.to_string(), false),
+                Err(e) => (format!("{}, but saving failed: {}", success, e), true),
+            },
+            Err(e) => (e, true),
+        });
+    }
+}
@@ -0,0 +1,164 @@
+use eframe::egui::{self, Color32, Stroke};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use crate::models::Database;
+use crate::gui::styling;
+use crate::service::reporting::{self, DayStatus};
+
+// Date selector shared by the log screens: ◀/▶ day stepping, a "Today" button and
+// a month view that colors each day by how it went against the calorie target.
+pub struct Calendar {
+    id: &'static str,
+    allow_future: bool,
+    open: bool,
+    shown_month: Option<NaiveDate>, // First day of the month in view
+    jump_text: String,
+    jump_error: Option<String>,
+}
+
+impl Calendar {
+    pub fn new(id: &'static str, allow_future: bool) -> Self {
+        Self {
+            id,
+            allow_future,
+            open: false,
+            shown_month: None,
+            jump_text: String::new(),
+            jump_error: None,
+        }
+    }
+
+    // Returns true when the user picked a different date
+    pub fn show(&mut self, ui: &mut egui::Ui, db: &Database, date: &mut NaiveDate) -> bool {
+        let today = Local::now().date_naive();
+        let previous = *date;
+
+        ui.horizontal(|ui| {
+            if ui.selectable_label(self.open, egui::RichText::new("📅").size(20.0)).on_hover_text("Show calendar").clicked() {
+                self.open = !self.open;
+                self.shown_month = None;
+            }
+
+            if ui.button("◀").clicked() {
+                *date -= Duration::days(1);
+            }
+
+            ui.label(egui::RichText::new(date.format("%A, %B %d, %Y").to_string()).size(16.0));
+
+            let can_go_forward = self.allow_future || *date < today;
+            if ui.add_enabled(can_go_forward, egui::Button::new("▶")).clicked() {
+                *date += Duration::days(1);
+            }
+
+            if ui.add_enabled(*date != today, egui::Button::new("Today")).clicked() {
+                *date = today;
+            }
+        });
+
+        if self.open {
+            self.render_month(ui, db, date, today);
+        }
+
+        if *date != previous {
+            self.shown_month = None;
+            self.jump_error = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn render_month(&mut self, ui: &mut egui::Ui, db: &Database, date: &mut NaiveDate, today: NaiveDate) {
+        let theme = styling::AppTheme::default();
+        let month = *self.shown_month.get_or_insert_with(|| first_of_month(*date));
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("«").clicked() {
+                    self.shown_month = Some(first_of_month(month - Duration::days(1)));
+                }
+                ui.label(egui::RichText::new(month.format("%B %Y").to_string()).strong());
+                if ui.button("»").clicked() {
+                    self.shown_month = Some(next_month(month));
+                }
+
+                ui.add_space(16.0);
+                ui.add(egui::TextEdit::singleline(&mut self.jump_text).hint_text("YYYY-MM-DD").desired_width(90.0));
+                if ui.button("Go").clicked() {
+                    match NaiveDate::parse_from_str(self.jump_text.trim(), "%Y-%m-%d") {
+                        Ok(target) if !self.allow_future && target > today => {
+                            self.jump_error = Some("Future dates are not available here".to_string());
+                        }
+                        Ok(target) => {
+                            *date = target;
+                            self.jump_text.clear();
+                        }
+                        Err(_) => self.jump_error = Some("Enter a date as YYYY-MM-DD".to_string()),
+                    }
+                }
+            });
+
+            if let Some(error) = &self.jump_error {
+                ui.colored_label(theme.error_color, error);
+            }
+
+            ui.add_space(4.0);
+            egui::Grid::new(self.id).spacing([4.0, 4.0]).show(ui, |ui| {
+                for weekday in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"] {
+                    ui.label(egui::RichText::new(weekday).strong());
+                }
+                ui.end_row();
+
+                for _ in 0..month.weekday().num_days_from_monday() {
+                    ui.label("");
+                }
+
+                let mut day = month;
+                while day.month() == month.month() {
+                    let status = reporting::day_status(db, &db.current_user, &day.format("%Y-%m-%d").to_string());
+                    let fill = match status {
+                        DayStatus::UnderTarget => theme.success_color.linear_multiply(0.5),
+                        DayStatus::OverTarget => theme.error_color.linear_multiply(0.5),
+                        DayStatus::Planned => theme.primary_color.linear_multiply(0.5),
+                        DayStatus::NoData => Color32::from_gray(60),
+                    };
+
+                    let mut text = egui::RichText::new(day.day().to_string());
+                    if day == today {
+                        text = text.underline();
+                    }
+                    let mut button = egui::Button::new(text).fill(fill).min_size(egui::vec2(28.0, 24.0));
+                    if day == *date {
+                        button = button.stroke(Stroke::new(2.0, theme.accent_color));
+                    }
+
+                    let enabled = self.allow_future || day <= today;
+                    if ui.add_enabled(enabled, button).clicked() {
+                        *date = day;
+                        self.open = false;
+                    }
+
+                    if day.weekday().num_days_from_monday() == 6 {
+                        ui.end_row();
+                    }
+                    day += Duration::days(1);
+                }
+            });
+
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.colored_label(theme.success_color, "■ Within target");
+                ui.colored_label(theme.error_color, "■ Over target");
+                ui.colored_label(theme.primary_color, "■ Planned");
+                ui.colored_label(Color32::from_gray(120), "■ Nothing logged");
+            });
+        });
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn next_month(first: NaiveDate) -> NaiveDate {
+    first_of_month(first + Duration::days(31))
+}
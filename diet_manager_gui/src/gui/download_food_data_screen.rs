@@ -4,6 +4,7 @@ use crate::app_state::AppState;
 use crate::gui::styling;
 
 #[derive(Debug, PartialEq, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 enum FoodDataSource {
     McDonalds,
     USDA,
@@ -1,24 +1,26 @@
 use eframe::egui;
 use chrono::{ NaiveDate, Local };
-use crate::models::{ Database, FoodLogEntry };
+use crate::models::Database;
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::gui::undo_manager::UndoManager;
+use crate::gui::calendar::Calendar;
+use crate::undo::UndoManager;
+use crate::service;
 
 pub struct EditFoodLogScreen {
     selected_date: NaiveDate,
+    calendar: Calendar,
     editing_servings: f32,
-    selected_entry_index: Option<usize>,
-    editing_entry_index: Option<usize>, // Added to track which entry is being edited
+    editing_entry_id: Option<String>, // The entry whose servings are being changed
 }
 
 impl EditFoodLogScreen {
     pub fn new() -> Self {
         Self {
             selected_date: Local::now().date_naive(),
+            calendar: Calendar::new("edit_log_calendar", true),
             editing_servings: 1.0,
-            selected_entry_index: None,
-            editing_entry_index: None, // Initialize the new field
+            editing_entry_id: None,
         }
     }
 
@@ -39,33 +41,9 @@ impl EditFoodLogScreen {
         styling::card_frame().show(ui, |ui| {
             // Date selection
             styling::section_header(ui, "Select Date");
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("📅").size(20.0));
-
-                if ui.button("◀").clicked() {
-                    self.selected_date = self.selected_date
-                        .pred_opt()
-                        .unwrap_or(self.selected_date);
-                    self.editing_entry_index = None; // Reset when changing date
-                }
-
-                ui.label(
-                    egui::RichText
-                        ::new(self.selected_date.format("%A, %B %d, %Y").to_string())
-                        .size(16.0)
-                );
-
-                let today = Local::now().date_naive();
-                let is_future = self.selected_date >= today.succ_opt().unwrap_or(today);
-                let next_button = ui.button("▶");
-
-                if next_button.clicked() && !is_future {
-                    self.selected_date = self.selected_date
-                        .succ_opt()
-                        .unwrap_or(self.selected_date);
-                    self.editing_entry_index = None; // Reset when changing date
-                }
-            });
+            if self.calendar.show(ui, db, &mut self.selected_date) {
+                self.editing_entry_id = None; // Reset when changing date
+            }
 
             ui.add_space(16.0);
 
@@ -94,24 +72,12 @@ impl EditFoodLogScreen {
                     ui.add_space(20.0);
                 });
             } else {
-                // First, collect all data we need to display
-                // This avoids borrowing db while iterating
-                let mut entries_data: Vec<(usize, FoodLogEntry, String, f32)> = Vec::new();
+                let user_id = db.current_user.clone();
+                let entries_data = service::logging::entries_for_day(db, &user_id, &selected_date_str);
 
-                if let Some(entries) = db.food_logs.get(&db.current_user) {
-                    for (idx, entry) in entries.iter().enumerate() {
-                        if entry.date == selected_date_str {
-                            let food_name = self.get_food_name(db, &entry.food_id);
-                            let calories =
-                                db.get_food_calories(&entry.food_id).unwrap_or(0.0) *
-                                entry.servings;
-                            entries_data.push((idx, entry.clone(), food_name, calories));
-                        }
-                    }
-                }
-
-                // Track entries to remove
-                let mut to_remove = Vec::new();
+                // Apply at most one change per frame, after the list is drawn
+                let mut pending_update: Option<(String, f32)> = None;
+                let mut pending_removal: Option<String> = None;
 
                 // Display entries
                 egui::ScrollArea
@@ -131,28 +97,28 @@ impl EditFoodLogScreen {
 
                         ui.separator();
 
-                        // Use for-loop to avoid nested mutable references
-                        for i in 0..entries_data.len() {
-                            let db_index = entries_data[i].0;
-                            let calories_per_serving = db
-                                .get_food_calories(&entries_data[i].1.food_id)
-                                .unwrap_or(0.0);
+                        for entry in &entries_data {
+                            let calories_per_serving = db.get_food_calories(&entry.food_id).unwrap_or(0.0);
 
-                            ui.push_id(db_index, |ui| {
+                            ui.push_id(&entry.id, |ui| {
                                 ui.horizontal(|ui| {
                                     // Food name
-                                    ui.label(egui::RichText::new(&entries_data[i].2).strong());
+                                    ui.label(egui::RichText::new(db.food_name(&entry.food_id)).strong());
+                                    ui.label(egui::RichText::new(entry.meal.label()).weak());
+                                    if entry.planned {
+                                        ui.label(egui::RichText::new("Planned").italics());
+                                    }
 
                                     ui.add_space(40.0);
 
                                     // Check if this is the entry we're currently editing
-                                    if self.editing_entry_index != Some(i) {
-                                        self.editing_servings = entries_data[i].1.servings;
+                                    let is_editing = self.editing_entry_id.as_deref() == Some(entry.id.as_str());
+                                    if !is_editing {
+                                        self.editing_servings = entry.servings;
                                     }
 
                                     // Servings editing - using DragValue for more precision
                                     ui.horizontal(|ui| {
-                                        // Show the current value
                                         let response = ui.add(
                                             egui::DragValue::new(&mut self.editing_servings)
                                                 .speed(0.1)
@@ -163,58 +129,26 @@ impl EditFoodLogScreen {
 
                                         // If value changed, mark this as the active editing entry
                                         if response.changed() {
-                                            self.editing_entry_index = Some(i);
+                                            self.editing_entry_id = Some(entry.id.clone());
                                         }
 
                                         // Update button
                                         if ui.button("✓").clicked() {
-                                            // First gather all the info we need
-                                            let food_name = entries_data[i].2.clone();
-                                            let old_servings = entries_data[i].1.servings;
-                                            let new_servings = self.editing_servings;
-
-                                            if old_servings != new_servings {
-                                                // Record state before change
-                                                undo_manager.record_action(
-                                                    db.clone(),
-                                                    &format!(
-                                                        "Changed servings of {} from {:.1} to {:.1}",
-                                                        food_name, old_servings, new_servings
-                                                    )
-                                                );
-
-                                                // Update the database
-                                                if let Some(entries) = db.food_logs.get_mut(&db.current_user) {
-                                                    if let Some(entry) = entries.get_mut(db_index) {
-                                                        entry.servings = new_servings;
-
-                                                        // Update our local copy too
-                                                        entries_data[i].1.servings = new_servings;
-
-                                                        // Save immediately
-                                                        if let Err(e) = crate::database::save_database(db) {
-                                                            eprintln!("Failed to save database: {}", e);
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-                                            // Done editing
-                                            self.editing_entry_index = None;
+                                            pending_update = Some((entry.id.clone(), self.editing_servings));
+                                            self.editing_entry_id = None;
                                         }
                                     });
 
                                     ui.add_space(40.0);
 
                                     // Calories - always show updated calculation
-                                    let current_servings = if self.editing_entry_index == Some(i) {
+                                    let current_servings = if is_editing {
                                         self.editing_servings
                                     } else {
-                                        entries_data[i].1.servings
+                                        entry.servings
                                     };
 
-                                    let updated_calories = calories_per_serving * current_servings;
-                                    ui.label(format!("{:.0} kcal", updated_calories));
+                                    ui.label(format!("{:.0} kcal", calories_per_serving * current_servings));
 
                                     ui.add_space(40.0);
 
@@ -224,16 +158,7 @@ impl EditFoodLogScreen {
                                                 .color(styling::AppTheme::default().error_color)
                                         ).clicked()
                                     {
-                                        // Get food name for better description
-                                        let food_name = entries_data[i].2.clone();
-
-                                        // Record state before deletion
-                                        undo_manager.record_action(
-                                            db.clone(),
-                                            &format!("Removed {} from food log", food_name)
-                                        );
-
-                                        to_remove.push(db_index);
+                                        pending_removal = Some(entry.id.clone());
                                     }
                                 });
 
@@ -242,20 +167,24 @@ impl EditFoodLogScreen {
                         }
                     });
 
-                // Remove deleted entries (reverse order to avoid index issues)
-                if !to_remove.is_empty() {
-                    if let Some(entries) = db.food_logs.get_mut(&db.current_user) {
-                        // Sort indices in descending order to safely remove
-                        to_remove.sort_by(|a, b| b.cmp(a));
-                        for index in to_remove {
-                            entries.remove(index);
-                        }
-
-                        // Save after deletions
+                let result = if let Some((entry_id, servings)) = pending_update {
+                    Some(service::logging::update_servings(db, undo_manager, &user_id, &entry_id, servings))
+                } else {
+                    pending_removal.map(|entry_id| {
+                        self.editing_entry_id = None;
+                        service::logging::remove_entry(db, undo_manager, &user_id, &entry_id).map(|_| ())
+                    })
+                };
+
+                match result {
+                    Some(Ok(())) => {
+                        // Save immediately
                         if let Err(e) = crate::database::save_database(db) {
-                            eprintln!("Failed to save database after deletions: {}", e);
+                            eprintln!("Failed to save database: {}", e);
                         }
                     }
+                    Some(Err(e)) => eprintln!("Failed to update food log: {}", e),
+                    None => {}
                 }
 
                 ui.add_space(16.0);
@@ -277,15 +206,4 @@ impl EditFoodLogScreen {
             });
         });
     }
-
-    // Helper to get food name
-    fn get_food_name(&self, db: &Database, food_id: &str) -> String {
-        if let Some(food) = db.basic_foods.get(food_id) {
-            food.name.clone()
-        } else if let Some(food) = db.composite_foods.get(food_id) {
-            food.name.clone()
-        } else {
-            food_id.to_string()
-        }
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,168 @@
+use eframe::egui;
+use chrono::{ Duration, Local, NaiveDate };
+use std::path::Path;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::export::{ self, ExportFormat };
+use crate::gui::styling;
+
+pub struct ExportScreen {
+    from_date: String,
+    to_date: String,
+    format: ExportFormat,
+    log_path: String,
+    catalog_path: String,
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl ExportScreen {
+    pub fn new() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            from_date: (today - Duration::days(30)).format("%Y-%m-%d").to_string(),
+            to_date: today.format("%Y-%m-%d").to_string(),
+            format: ExportFormat::Csv,
+            log_path: "food_log_export.csv".to_string(),
+            catalog_path: "food_catalog.csv".to_string(),
+            status_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Export Data").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Save your food log or the food catalog to a file");
+            ui.add_space(20.0);
+        });
+
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Format");
+
+            ui.horizontal(|ui| {
+                let previous = self.format;
+                ui.radio_value(&mut self.format, ExportFormat::Csv, "CSV");
+                ui.radio_value(&mut self.format, ExportFormat::Json, "JSON");
+                if previous != self.format {
+                    self.log_path = Self::swap_extension(&self.log_path, self.format);
+                    self.catalog_path = Self::swap_extension(&self.catalog_path, self.format);
+                }
+            });
+
+            ui.add_space(16.0);
+
+            // Food log export
+            styling::section_header(ui, "Food Log");
+
+            egui::Grid::new("export_log_grid")
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("From:");
+                    ui.add(egui::TextEdit::singleline(&mut self.from_date)
+                        .hint_text("YYYY-MM-DD")
+                        .desired_width(120.0));
+                    ui.end_row();
+
+                    ui.label("To:");
+                    ui.add(egui::TextEdit::singleline(&mut self.to_date)
+                        .hint_text("YYYY-MM-DD")
+                        .desired_width(120.0));
+                    ui.end_row();
+
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(&mut self.log_path)
+                        .desired_width(300.0));
+                    ui.end_row();
+                });
+
+            ui.add_space(8.0);
+            if styling::primary_button(ui, "Export Log").clicked() {
+                self.status_message = Some(self.export_log(db));
+            }
+
+            ui.add_space(16.0);
+
+            // Catalog export
+            styling::section_header(ui, "Food Catalog");
+
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.add(egui::TextEdit::singleline(&mut self.catalog_path)
+                    .desired_width(300.0));
+            });
+            if self.format == ExportFormat::Csv {
+                ui.label(
+                    egui::RichText::new("CSV holds basic foods only; use JSON to include composite foods")
+                        .size(12.0)
+                        .italics()
+                );
+            }
+
+            ui.add_space(8.0);
+            if styling::primary_button(ui, "Export Catalog").clicked() {
+                self.status_message = Some(self.export_catalog(db));
+            }
+
+            if let Some((ref message, is_error)) = self.status_message {
+                ui.add_space(12.0);
+                let color = if is_error {
+                    styling::AppTheme::default().error_color
+                } else {
+                    styling::AppTheme::default().success_color
+                };
+                ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+            }
+        });
+
+        ui.add_space(16.0);
+
+        if styling::warning_button(ui, "Back to Home").clicked() {
+            self.status_message = None;
+            *current_state = AppState::Home;
+        }
+    }
+
+    fn export_log(&self, db: &Database) -> (String, bool) {
+        let from = match NaiveDate::parse_from_str(self.from_date.trim(), "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => return ("Start date must be in YYYY-MM-DD format".to_string(), true),
+        };
+        let to = match NaiveDate::parse_from_str(self.to_date.trim(), "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => return ("End date must be in YYYY-MM-DD format".to_string(), true),
+        };
+        if from > to {
+            return ("Start date must not be after the end date".to_string(), true);
+        }
+        if self.log_path.trim().is_empty() {
+            return ("Please enter a file name".to_string(), true);
+        }
+
+        match export::export_food_log(
+            db,
+            &db.current_user,
+            &from.format("%Y-%m-%d").to_string(),
+            &to.format("%Y-%m-%d").to_string(),
+            self.format,
+            Path::new(self.log_path.trim())
+        ) {
+            Ok(count) => (format!("Exported {} entries to {}", count, self.log_path.trim()), false),
+            Err(e) => (format!("Export failed: {}", e), true),
+        }
+    }
+
+    fn export_catalog(&self, db: &Database) -> (String, bool) {
+        if self.catalog_path.trim().is_empty() {
+            return ("Please enter a file name".to_string(), true);
+        }
+
+        match export::export_catalog(db, self.format, Path::new(self.catalog_path.trim())) {
+            Ok(count) => (format!("Exported {} foods to {}", count, self.catalog_path.trim()), false),
+            Err(e) => (format!("Export failed: {}", e), true),
+        }
+    }
+
+    fn swap_extension(path: &str, format: ExportFormat) -> String {
+        Path::new(path).with_extension(format.extension()).to_string_lossy().to_string()
+    }
+}
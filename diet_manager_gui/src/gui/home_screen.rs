@@ -1,21 +1,15 @@
 use eframe::egui;
-use crate::models::Database;
+use crate::models::{Database, GoalProjection};
 use crate::app_state::AppState;
-use crate::gui::undo_manager::UndoManager;
+use crate::undo::UndoManager;
 use crate::gui::styling;
+use crate::service;
 
+#[derive(Default)]
 pub struct HomeScreen {
     show_notification: Option<(String, std::time::Instant)>,
 }
 
-impl Default for HomeScreen {
-    fn default() -> Self {
-        Self {
-            show_notification: None,
-        }
-    }
-}
-
 impl HomeScreen {
     pub fn render(
         &mut self,
@@ -42,18 +36,16 @@ impl HomeScreen {
         // Calculate and display calorie information
         if !db.current_user.is_empty() {
             let date = chrono::Local::now().format("%Y-%m-%d").to_string();
-            let (total_calories, target_calories, difference) = self.calculate_user_calories(
-                db,
-                &db.current_user,
-                &date
-            );
+            let summary = service::reporting::daily_summary(db, &db.current_user, &date);
+            let (total_calories, burned_calories, target_calories, difference) =
+                (summary.consumed, summary.burned, summary.target, summary.remaining);
 
             // Nutrition summary card
             styling::card_frame().show(ui, |ui| {
                 styling::section_header(ui, "Today's Nutrition Summary");
                 ui.add_space(8.0);
 
-                ui.columns(3, |cols| {
+                ui.columns(4, |cols| {
                     // Consumed calories
                     cols[0].vertical_centered(|ui| {
                         ui.label(egui::RichText::new("Consumed").size(14.0));
@@ -64,8 +56,18 @@ impl HomeScreen {
                         );
                     });
 
-                    // Target calories
+                    // Burned calories
                     cols[1].vertical_centered(|ui| {
+                        ui.label(egui::RichText::new("Burned").size(14.0));
+                        ui.label(
+                            egui::RichText::new(format!("{:.0} kcal", burned_calories))
+                                .size(22.0)
+                                .color(styling::AppTheme::default().text_color)
+                        );
+                    });
+
+                    // Target calories
+                    cols[2].vertical_centered(|ui| {
                         ui.label(egui::RichText::new("Target").size(14.0));
                         ui.label(
                             egui::RichText::new(format!("{:.0} kcal", target_calories))
@@ -75,7 +77,7 @@ impl HomeScreen {
                     });
 
                     // Remaining calories
-                    cols[2].vertical_centered(|ui| {
+                    cols[3].vertical_centered(|ui| {
                         ui.label(
                             egui::RichText::new(if difference >= 0.0 { "Remaining" } else { "Exceeded" })
                                 .size(14.0)
@@ -96,8 +98,8 @@ impl HomeScreen {
                 ui.add_space(10.0);
 
                 // Progress bar
-                if target_calories > 0.0 {
-                    let progress = total_calories / target_calories;
+                if summary.allowance > 0.0 {
+                    let progress = summary.progress();
                     let progress_text = format!("{:.1}% of daily goal", progress * 100.0);
                     let progress_bar = egui::ProgressBar::new(progress.clamp(0.0, 1.0))
                         .text(progress_text)
@@ -106,6 +108,9 @@ impl HomeScreen {
                 }
             });
 
+            self.render_goal_summary(ui, db);
+            self.render_trackers(ui, db, current_state, undo_manager, &date);
+
             ui.add_space(16.0);
         }
 
@@ -142,7 +147,31 @@ impl HomeScreen {
                 self.menu_button(ui, "Download Food Data", "🌐", || {
                     *current_state = AppState::DownloadFoodData;
                 });
+                self.menu_button(ui, "Log Exercise", "🏃", || {
+                    *current_state = AppState::LogExercise;
+                });
+                self.menu_button(ui, "Export Data", "📤", || {
+                    *current_state = AppState::Export;
+                });
                 ui.end_row();
+
+                self.menu_button(ui, "Import Foods", "📥", || {
+                    *current_state = AppState::Import;
+                });
+                self.menu_button(ui, "Shopping List", "🛒", || {
+                    *current_state = AppState::ShoppingList;
+                });
+                self.menu_button(ui, "Account", "🔐", || {
+                    *current_state = AppState::Account;
+                });
+                ui.end_row();
+
+                if service::auth::is_admin(db, &db.current_user) {
+                    self.menu_button(ui, "Admin", "🛡", || {
+                        *current_state = AppState::Admin;
+                    });
+                    ui.end_row();
+                }
             });
 
         ui.add_space(16.0);
@@ -151,7 +180,7 @@ impl HomeScreen {
         ui.horizontal(|ui| {
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if styling::warning_button(ui, "Logout").clicked() {
-                    db.current_user.clear();
+                    service::auth::logout(db);
                     *current_state = AppState::Login;
                 }
 
@@ -220,22 +249,135 @@ impl HomeScreen {
         button
     }
 
-    fn calculate_user_calories(&self, db: &Database, user_id: &str, date: &str) -> (f32, f32, f32) {
-        let total_calories = db.food_logs.get(user_id).map_or(0.0, |entries| {
-            entries
-                .iter()
-                .filter(|entry| entry.date == date)
-                .map(|entry| db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
-                .sum()
+    fn render_goal_summary(&self, ui: &mut egui::Ui, db: &Database) {
+        let user = match db.find_user_by_id(&db.current_user) {
+            Some(user) => user,
+            None => return,
+        };
+        let goal = match &user.profile.weight_goal {
+            Some(goal) => goal,
+            None => return,
+        };
+
+        let theme = styling::AppTheme::default();
+        let today = chrono::Local::now().date_naive();
+        let target = user.profile.calorie_target_on(today);
+        let weekly_change = user.profile.planned_weekly_change_kg(today);
+
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Weight Goal");
+
+            let direction = if goal.target_weight_kg < user.profile.weight_kg { "Lose" } else { "Gain" };
+            let units = user.profile.units;
+            ui.label(format!(
+                "{} to {} (currently {}) at {:.2} {}/week",
+                direction,
+                units.format_weight(goal.target_weight_kg),
+                units.format_weight(user.profile.weight_kg),
+                units.weight_from_kg(weekly_change.abs()),
+                units.weight_unit()
+            ));
+            ui.label(format!(
+                "Maintenance {:.0} kcal, {} {:.0} kcal/day",
+                target.maintenance,
+                if target.adjustment < 0.0 { "deficit" } else { "surplus" },
+                target.adjustment.abs()
+            ));
+
+            if target.floor_applied {
+                ui.colored_label(
+                    theme.warning_color,
+                    format!("Target raised to the {:.0} kcal safety minimum", target.target)
+                );
+            }
+
+            ui.add_space(4.0);
+            match db.project_goal_date(&db.current_user, today) {
+                GoalProjection::Reached => {
+                    ui.colored_label(theme.success_color, "Goal weight reached!");
+                }
+                GoalProjection::OnTrack(date) => {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Projected to reach goal on {} at your recent intake",
+                            date.format("%B %d, %Y")
+                        ))
+                        .color(theme.accent_color)
+                        .strong()
+                    );
+                }
+                GoalProjection::OffTrack => {
+                    ui.colored_label(
+                        theme.warning_color,
+                        "Your recent intake is not moving you towards this goal"
+                    );
+                }
+                GoalProjection::NoData => {
+                    ui.label(egui::RichText::new("Log a few days of meals to see a projected goal date").italics());
+                }
+            }
         });
+    }
+
+    fn render_trackers(
+        &self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager,
+        date: &str
+    ) {
+        let theme = styling::AppTheme::default();
+        let trackers = db.trackers.get(&db.current_user).cloned().unwrap_or_default();
+
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Daily Trackers");
+
+            let mut adjustment = None;
+
+            egui::Grid::new("home_trackers")
+                .num_columns(4)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    for tracker in &trackers {
+                        let value = db.tracker_value(&db.current_user, &tracker.id, date);
+
+                        ui.label(egui::RichText::new(&tracker.name).strong());
+                        match tracker.daily_goal {
+                            Some(goal) if goal > 0.0 => {
+                                let color = if value >= goal { theme.success_color } else { theme.text_color };
+                                ui.colored_label(color, format!("{:.0} / {:.0} {}", value, goal, tracker.unit));
+                                ui.add(egui::ProgressBar::new((value / goal).clamp(0.0, 1.0)).desired_width(120.0));
+                            }
+                            _ => {
+                                ui.label(format!("{:.0} {}", value, tracker.unit));
+                                ui.label("");
+                            }
+                        }
 
-        let target_calories = db.users
-            .values()
-            .find(|u| u.user_id == user_id)
-            .map(|user| user.profile.calculate_target_calories())
-            .unwrap_or(0.0);
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(value > 0.0, egui::Button::new("−")).clicked() {
+                                adjustment = Some((tracker.id.clone(), -tracker.increment));
+                            }
+                            if ui.button(format!("+{:.0}", tracker.increment)).clicked() {
+                                adjustment = Some((tracker.id.clone(), tracker.increment));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
 
-        let difference = target_calories - total_calories;
-        (total_calories, target_calories, difference)
+            if let Some((tracker_id, delta)) = adjustment {
+                let user_id = db.current_user.clone();
+                if let Err(e) = service::logging::adjust_tracker(db, undo_manager, &user_id, &tracker_id, date, delta) {
+                    eprintln!("Failed to update tracker: {}", e);
+                }
+            }
+
+            ui.add_space(4.0);
+            if ui.button("Manage Trackers").clicked() {
+                *current_state = AppState::ManageTrackers;
+            }
+        });
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,190 @@
+use eframe::egui;
+use std::path::Path;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::import::{ self, ConflictStrategy, ImportPreview, ImportedFood };
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+pub struct ImportScreen {
+    file_path: String,
+    strategy: ConflictStrategy,
+    preview: Option<ImportPreview>,
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl ImportScreen {
+    pub fn new() -> Self {
+        Self {
+            file_path: String::new(),
+            strategy: ConflictStrategy::Skip,
+            preview: None,
+            status_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Import Foods").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Add many foods at once from a CSV or JSON file");
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "File");
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("📄").size(20.0));
+                let response = ui.add(egui::TextEdit::singleline(&mut self.file_path)
+                    .hint_text("e.g., foods.csv or food_catalog.json")
+                    .desired_width(300.0));
+                if response.changed() {
+                    self.preview = None;
+                }
+                if ui.button("Preview").clicked() {
+                    self.load_preview(db);
+                }
+            });
+            ui.label(
+                egui::RichText::new("CSV columns: id, name, keywords (separated by ;), calories")
+                    .size(12.0)
+                    .italics()
+            );
+
+            ui.add_space(16.0);
+
+            styling::section_header(ui, "When an identifier already exists");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.strategy, ConflictStrategy::Skip, "Skip");
+                ui.radio_value(&mut self.strategy, ConflictStrategy::Overwrite, "Overwrite");
+                ui.radio_value(&mut self.strategy, ConflictStrategy::Rename, "Rename (add suffix)");
+            });
+
+            if let Some(ref preview) = self.preview {
+                ui.add_space(16.0);
+                styling::section_header(ui, "Preview");
+
+                let owner = service::catalog::import_owner(db, &db.current_user);
+                let conflicts = preview.conflicts(db, owner.as_deref());
+                ui.label(format!(
+                    "{} valid foods, {} rejected rows, {} conflicts",
+                    preview.foods.len(),
+                    preview.errors.len(),
+                    conflicts.len()
+                ));
+
+                egui::ScrollArea::vertical()
+                    .id_source("import_preview")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("import_preview_grid")
+                            .striped(true)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                for food in &preview.foods {
+                                    let (kind, name) = match food {
+                                        ImportedFood::Basic(food) => ("Basic", &food.name),
+                                        ImportedFood::Composite(food) => ("Composite", &food.name),
+                                    };
+                                    ui.label(food.id());
+                                    ui.label(name);
+                                    ui.label(kind);
+                                    if conflicts.iter().any(|id| id == food.id()) {
+                                        ui.colored_label(styling::AppTheme::default().warning_color, "exists");
+                                    } else {
+                                        ui.label("new");
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+
+                        for error in &preview.errors {
+                            ui.colored_label(styling::AppTheme::default().error_color, error);
+                        }
+                    });
+            }
+
+            if let Some((ref message, is_error)) = self.status_message {
+                ui.add_space(12.0);
+                let color = if is_error {
+                    styling::AppTheme::default().error_color
+                } else {
+                    styling::AppTheme::default().success_color
+                };
+                ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+            }
+
+            ui.add_space(16.0);
+
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if styling::warning_button(ui, "Back to Home").clicked() {
+                        self.preview = None;
+                        self.status_message = None;
+                        *current_state = AppState::Home;
+                    }
+
+                    ui.add_space(10.0);
+
+                    let can_import = self.preview.as_ref().is_some_and(|p| !p.foods.is_empty());
+                    if ui.add_enabled_ui(can_import, |ui| styling::success_button(ui, "Import")).inner.clicked() {
+                        self.run_import(db, undo_manager);
+                    }
+                });
+            });
+        });
+        });
+    }
+
+    fn load_preview(&mut self, db: &Database) {
+        self.status_message = None;
+
+        if self.file_path.trim().is_empty() {
+            self.status_message = Some(("Please enter a file path".to_string(), true));
+            return;
+        }
+
+        match import::read_file(Path::new(self.file_path.trim()), db) {
+            Ok(preview) => self.preview = Some(preview),
+            Err(e) => {
+                self.preview = None;
+                self.status_message = Some((format!("Could not read file: {}", e), true));
+            }
+        }
+    }
+
+    fn run_import(&mut self, db: &mut Database, undo_manager: &mut UndoManager) {
+        let preview = match self.preview.take() {
+            Some(preview) => preview,
+            None => return,
+        };
+
+        // The whole batch is recorded as a single undoable action
+        let user_id = db.current_user.clone();
+        let result = service::catalog::import_foods(
+            db,
+            undo_manager,
+            &user_id,
+            &preview.foods,
+            self.strategy,
+            self.file_path.trim()
+        );
+
+        match result {
+            Ok(report) => {
+                let mut message = report.summary();
+                for error in &report.errors {
+                    message.push_str(&format!("\n{}", error));
+                }
+                self.status_message = Some((message, !report.errors.is_empty()));
+            }
+            Err(e) => {
+                self.preview = Some(preview);
+                self.status_message = Some((e, true));
+            }
+        }
+    }
+}
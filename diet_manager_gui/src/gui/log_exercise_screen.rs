@@ -0,0 +1,256 @@
+use eframe::egui;
+use chrono::{ Local, NaiveDate };
+use crate::models::{ Database, Exercise, ExerciseLogEntry };
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+pub struct LogExerciseScreen {
+    selected_exercise_id: String,
+    duration_minutes: f32,
+    selected_date: NaiveDate,
+    custom_name: String,
+    custom_met: String,
+    error_message: Option<String>,
+}
+
+impl LogExerciseScreen {
+    pub fn new() -> Self {
+        Self {
+            selected_exercise_id: String::new(),
+            duration_minutes: 30.0,
+            selected_date: Local::now().date_naive(),
+            custom_name: String::new(),
+            custom_met: String::new(),
+            error_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Log Exercise").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Record workouts and activities to offset your calories");
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+        styling::card_frame().show(ui, |ui| {
+            // Date selection
+            styling::section_header(ui, "Date");
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("📅").size(20.0));
+
+                if ui.button("◀").clicked() {
+                    self.selected_date = self.selected_date
+                        .pred_opt()
+                        .unwrap_or(self.selected_date);
+                }
+
+                ui.label(
+                    egui::RichText
+                        ::new(self.selected_date.format("%A, %B %d, %Y").to_string())
+                        .size(16.0)
+                );
+
+                let today = Local::now().date_naive();
+                let is_future = self.selected_date >= today;
+                if ui.button("▶").clicked() && !is_future {
+                    self.selected_date = self.selected_date
+                        .succ_opt()
+                        .unwrap_or(self.selected_date);
+                }
+            });
+
+            ui.add_space(16.0);
+
+            let selected_date_str = self.selected_date.format("%Y-%m-%d").to_string();
+            let weight_kg = db
+                .find_user_by_id(&db.current_user)
+                .map_or(0.0, |user| user.profile.weight_kg);
+
+            // Activity selection
+            styling::section_header(ui, "Activity");
+
+            let mut exercises: Vec<&Exercise> = db.exercises.values().collect();
+            exercises.sort_by(|a, b| a.name.cmp(&b.name));
+
+            egui::ScrollArea
+                ::vertical()
+                .id_source("exercise_catalog")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid
+                        ::new("exercise_grid")
+                        .striped(true)
+                        .spacing([10.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new("Activity").strong());
+                            ui.label(egui::RichText::new("MET").strong());
+                            ui.label(egui::RichText::new("Per 30 min").strong());
+                            ui.end_row();
+
+                            for exercise in exercises {
+                                ui.radio_value(&mut self.selected_exercise_id, exercise.id.clone(), &exercise.name);
+                                ui.label(format!("{:.1}", exercise.met));
+                                ui.label(format!("{:.0} kcal", exercise.calories_burned(weight_kg, 30.0)));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Duration:").strong());
+                ui.add(
+                    egui::Slider
+                        ::new(&mut self.duration_minutes, 5.0..=240.0)
+                        .text("minutes")
+                        .clamp_to_range(true)
+                        .smart_aim(false)
+                        .step_by(5.0)
+                );
+            });
+
+            if let Some(exercise) = db.exercises.get(&self.selected_exercise_id) {
+                ui.label(
+                    egui::RichText
+                        ::new(format!(
+                            "Estimated burn: {:.0} kcal",
+                            exercise.calories_burned(weight_kg, self.duration_minutes)
+                        ))
+                        .color(styling::AppTheme::default().accent_color)
+                        .strong()
+                );
+            }
+
+            ui.add_space(8.0);
+
+            if let Some(ref error) = self.error_message {
+                ui.colored_label(
+                    styling::AppTheme::default().error_color,
+                    egui::RichText::new(error).size(14.0).strong()
+                );
+                ui.add_space(8.0);
+            }
+
+            if styling::success_button(ui, "Add Exercise").clicked() {
+                self.add_exercise(db, &selected_date_str, undo_manager);
+            }
+
+            ui.add_space(16.0);
+
+            // Custom activity; the activity list is shared, so only admins add to it
+            if service::auth::is_admin(db, &db.current_user) {
+                ui.collapsing("Add a custom activity", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.add(egui::TextEdit::singleline(&mut self.custom_name)
+                            .hint_text("e.g., Rock Climbing")
+                            .desired_width(200.0));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("MET value:");
+                        ui.add(egui::TextEdit::singleline(&mut self.custom_met)
+                            .hint_text("e.g., 8.0")
+                            .desired_width(80.0));
+                    });
+                    if styling::primary_button(ui, "Save Activity").clicked() {
+                        self.save_custom_exercise(db, undo_manager);
+                    }
+                });
+            }
+
+            ui.add_space(16.0);
+
+            // Entries for the selected date
+            styling::section_header(ui, "Exercise Entries");
+
+            let entries: Vec<(usize, ExerciseLogEntry)> = db.exercise_logs
+                .get(&db.current_user)
+                .map_or(Vec::new(), |entries| {
+                    entries.iter()
+                        .enumerate()
+                        .filter(|(_, entry)| entry.date == selected_date_str)
+                        .map(|(idx, entry)| (idx, entry.clone()))
+                        .collect()
+                });
+
+            if entries.is_empty() {
+                ui.label(egui::RichText::new("No exercise logged for this date").italics());
+            } else {
+                let mut to_remove = None;
+
+                for (idx, entry) in &entries {
+                    let (name, burned) = match db.exercises.get(&entry.exercise_id) {
+                        Some(exercise) => (
+                            exercise.name.clone(),
+                            exercise.calories_burned(weight_kg, entry.duration_minutes),
+                        ),
+                        None => (entry.exercise_id.clone(), 0.0),
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&name).strong());
+                        ui.label(format!("{:.0} min", entry.duration_minutes));
+                        ui.label(format!("{:.0} kcal", burned));
+                        if ui.button(egui::RichText::new("❌").color(styling::AppTheme::default().error_color)).clicked() {
+                            to_remove = Some(*idx);
+                        }
+                    });
+                }
+
+                if let Some(idx) = to_remove {
+                    let user_id = db.current_user.clone();
+                    if let Err(e) = service::logging::remove_exercise(db, undo_manager, &user_id, idx) {
+                        self.error_message = Some(e);
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText
+                        ::new(format!("Total burned: {:.0} kcal", db.calories_burned(&db.current_user, &selected_date_str)))
+                        .strong()
+                );
+            }
+
+            ui.add_space(16.0);
+
+            ui.horizontal(|ui| {
+                if styling::warning_button(ui, "Back to Home").clicked() {
+                    self.error_message = None;
+                    *current_state = AppState::Home;
+                }
+            });
+        });
+        });
+    }
+
+    fn add_exercise(&mut self, db: &mut Database, date: &str, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        self.error_message = service::logging::add_exercise(
+            db,
+            undo_manager,
+            &user_id,
+            date,
+            &self.selected_exercise_id,
+            self.duration_minutes
+        ).err();
+    }
+
+    fn save_custom_exercise(&mut self, db: &mut Database, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        match service::logging::add_custom_exercise(db, undo_manager, &user_id, &self.custom_name, &self.custom_met) {
+            Ok(id) => {
+                self.error_message = None;
+                self.selected_exercise_id = id;
+                self.custom_name.clear();
+                self.custom_met.clear();
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+}
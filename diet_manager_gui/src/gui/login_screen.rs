@@ -1,13 +1,22 @@
+use std::path::{Path, PathBuf};
 use eframe::egui;
 use crate::models::Database;
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::gui::undo_manager::UndoManager;
+use crate::undo::UndoManager;
+use crate::service;
+use crate::config;
+use crate::database;
 
 pub struct LoginScreen {
     username: String,
     password: String,
+    verification_code: String,
+    pending_user_id: Option<String>, // Password was right; waiting for the two-factor code
     error_message: Option<String>,
+    database_path: String,
+    database_message: Option<(String, bool)>, // (message, is_error)
+    switch_to: Option<PathBuf>,
 }
 
 impl LoginScreen {
@@ -15,10 +24,24 @@ impl LoginScreen {
         Self {
             username: String::new(),
             password: String::new(),
+            verification_code: String::new(),
+            pending_user_id: None,
             error_message: None,
+            database_path: String::new(),
+            database_message: None,
+            switch_to: None,
         }
     }
 
+    // The database file the user picked, for the app to switch to
+    pub fn take_database_switch(&mut self) -> Option<PathBuf> {
+        self.switch_to.take()
+    }
+
+    pub fn set_database_message(&mut self, message: String, is_error: bool) {
+        self.database_message = Some((message, is_error));
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
@@ -49,6 +72,11 @@ impl LoginScreen {
                 });
                 ui.add_space(16.0);
 
+                if self.pending_user_id.is_some() {
+                    self.render_second_factor(ui, db, current_state, undo_manager);
+                    return;
+                }
+
                 // Username field with improved styling
                 ui.horizontal(|ui| {
                     ui.label(egui::RichText::new("👤").size(20.0));
@@ -79,8 +107,7 @@ impl LoginScreen {
                 // Login and Register buttons
                 ui.vertical_centered(|ui| {
                     if styling::primary_button(ui, "Login").clicked() {
-                        // Your existing login logic here
-                        self.handle_login(db, current_state,undo_manager);
+                        self.handle_login(db, current_state, undo_manager);
                     }
 
                     ui.add_space(8.0);
@@ -100,32 +127,193 @@ impl LoginScreen {
                     }
                 });
             });
+
+            ui.add_space(16.0);
+            self.render_database_card(ui);
+        });
+    }
+
+    // Open, create or switch between database files
+    fn render_database_card(&mut self, ui: &mut egui::Ui) {
+        let theme = styling::AppTheme::default();
+        let current = config::database_file();
+
+        styling::card_frame().show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(egui::RichText::new("Database").size(20.0));
+            });
+            ui.add_space(8.0);
+            ui.label(format!("Current: {}", current.display()));
+
+            let recent: Vec<PathBuf> = config::data_paths()
+                .recent_databases()
+                .into_iter()
+                .filter(|path| *path != current)
+                .collect();
+            if !recent.is_empty() {
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Recent databases").strong());
+                for path in recent {
+                    let exists = path.is_file();
+                    let button = egui::Button::new(path.display().to_string());
+                    if ui.add_enabled(exists, button).on_disabled_hover_text("File not found").clicked() {
+                        self.open_database(&path);
+                    }
+                }
+            }
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit
+                        ::singleline(&mut self.database_path)
+                        .hint_text("Path to a database file")
+                        .desired_width(300.0)
+                );
+                if styling::styled_button(ui, "Open").clicked() {
+                    match self.entered_path() {
+                        Some(path) => self.open_database(&path),
+                        None => self.database_message = Some(("Please enter a file path".to_string(), true)),
+                    }
+                }
+                if styling::success_button(ui, "Create New").clicked() {
+                    match self.entered_path() {
+                        Some(path) => match database::create_database(&path) {
+                            Ok(()) => self.switch_to = Some(path),
+                            Err(e) => self.database_message = Some((e, true)),
+                        },
+                        None => self.database_message = Some(("Please enter a file path".to_string(), true)),
+                    }
+                }
+            });
+
+            if let Some((message, is_error)) = &self.database_message {
+                ui.add_space(8.0);
+                let color = if *is_error { theme.error_color } else { theme.success_color };
+                ui.colored_label(color, message);
+            }
         });
     }
 
-    // Add your existing login logic method here
-    fn handle_login(&mut self, db: &mut Database, current_state: &mut AppState,undo_manager: &mut UndoManager) {
-        // Implement your login logic here
-        // This is just a placeholder - replace with your actual login code
-        if self.username.is_empty() || self.password.is_empty() {
-            self.error_message = Some("Username and password are required".to_string());
+    fn entered_path(&self) -> Option<PathBuf> {
+        let path = self.database_path.trim();
+        if path.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        }
+    }
+
+    // Only switch to files that actually hold a database. Encrypted ones can't be
+    // checked before their passphrase is entered on the unlock screen.
+    fn open_database(&mut self, path: &Path) {
+        if database::is_locked(path) {
+            self.switch_to = Some(path.to_path_buf());
             return;
         }
+        match database::read_database(path) {
+            Ok(_) => self.switch_to = Some(path.to_path_buf()),
+            Err(e) => self.database_message = Some((e, true)),
+        }
+    }
 
-        // Check if user exists and password matches
-        if let Some(user) = db.users.get(&self.username) {
-            if user.password == self.password {
-                db.current_user = user.user_id.clone();
-                *current_state = AppState::Home;
-
-                // Clear and initialize undo manager with fresh state
-                undo_manager.clear();
-                undo_manager.initialize(db.clone());
-            } else {
-                self.error_message = Some("Invalid password".to_string());
+    fn render_second_factor(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
+        ui.label("Enter the 6-digit code from your authenticator app, or one of your recovery codes.");
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("🔑").size(20.0));
+            ui.add(
+                egui::TextEdit
+                    ::singleline(&mut self.verification_code)
+                    .hint_text("123456")
+                    .desired_width(250.0)
+            );
+        });
+
+        ui.add_space(20.0);
+
+        ui.vertical_centered(|ui| {
+            if styling::primary_button(ui, "Verify").clicked() {
+                self.handle_second_factor(db, current_state, undo_manager);
             }
-        } else {
-            self.error_message = Some("User not found".to_string());
+
+            ui.add_space(8.0);
+            if ui.button("Back").clicked() {
+                self.pending_user_id = None;
+                self.verification_code.clear();
+                self.error_message = None;
+            }
+
+            if let Some(ref error) = self.error_message {
+                ui.add_space(16.0);
+                ui.colored_label(
+                    styling::AppTheme::default().error_color,
+                    egui::RichText::new(error).size(14.0).strong()
+                );
+            }
+        });
+    }
+
+    fn handle_login(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let result = service::auth::authenticate(db, &self.username, &self.password);
+        self.password.clear();
+
+        match result {
+            Ok(user_id) if service::auth::two_factor_enabled(db, &user_id) => {
+                self.error_message = None;
+                self.pending_user_id = Some(user_id);
+            }
+            Ok(user_id) => self.finish_login(db, &user_id, current_state, undo_manager),
+            Err(e) => self.error_message = Some(e),
+        }
+
+        // Keep the failed attempt count even if the app is closed right away
+        if let Err(e) = database::save_database(db) {
+            eprintln!("Failed to save database: {}", e);
+        }
+    }
+
+    fn handle_second_factor(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let user_id = match self.pending_user_id.clone() {
+            Some(user_id) => user_id,
+            None => return,
+        };
+
+        let result = service::auth::verify_second_factor(db, &user_id, &self.verification_code);
+        self.verification_code.clear();
+        match result {
+            Ok(()) => {
+                self.pending_user_id = None;
+                self.finish_login(db, &user_id, current_state, undo_manager);
+            }
+            // Back to the password once the account is locked
+            Err(e) if e.starts_with("Too many") => {
+                self.pending_user_id = None;
+                self.error_message = Some(e);
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+
+        // Used recovery codes and failed attempts must stick
+        if let Err(e) = database::save_database(db) {
+            eprintln!("Failed to save database: {}", e);
         }
     }
+
+    fn finish_login(&mut self, db: &mut Database, user_id: &str, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        service::auth::start_session(db, user_id);
+        self.error_message = None;
+        *current_state = AppState::Home;
+
+        // Clear and initialize undo manager with fresh state
+        undo_manager.clear();
+        undo_manager.initialize(db.clone());
+    }
 }
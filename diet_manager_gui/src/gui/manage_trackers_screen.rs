@@ -0,0 +1,175 @@
+use eframe::egui;
+use crate::models::{ Database, WATER_TRACKER_ID };
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+pub struct ManageTrackersScreen {
+    new_name: String,
+    new_unit: String,
+    new_goal: String,
+    new_increment: String,
+    error_message: Option<String>,
+}
+
+impl ManageTrackersScreen {
+    pub fn new() -> Self {
+        Self {
+            new_name: String::new(),
+            new_unit: String::new(),
+            new_goal: String::new(),
+            new_increment: "1".to_string(),
+            error_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Daily Trackers").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Track water and any other daily counters");
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Your Trackers");
+
+            let mut trackers = db.trackers.get(&db.current_user).cloned().unwrap_or_default();
+            let mut changed = false;
+            let mut to_remove = None;
+
+            egui::Grid::new("trackers_grid")
+                .striped(true)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Name").strong());
+                    ui.label(egui::RichText::new("Daily Goal").strong());
+                    ui.label(egui::RichText::new("Step").strong());
+                    ui.label("");
+                    ui.end_row();
+
+                    for tracker in trackers.iter_mut() {
+                        ui.label(format!("{} ({})", tracker.name, tracker.unit));
+
+                        let mut has_goal = tracker.daily_goal.is_some();
+                        ui.horizontal(|ui| {
+                            if ui.checkbox(&mut has_goal, "").changed() {
+                                tracker.daily_goal = if has_goal { Some(tracker.increment.max(1.0)) } else { None };
+                                changed = true;
+                            }
+                            if let Some(ref mut goal) = tracker.daily_goal {
+                                changed |= ui.add(egui::DragValue::new(goal)
+                                    .speed(tracker.increment.max(1.0))
+                                    .clamp_range(0.0..=1_000_000.0))
+                                    .changed();
+                            }
+                        });
+
+                        changed |= ui.add(egui::DragValue::new(&mut tracker.increment)
+                            .speed(1.0)
+                            .clamp_range(1.0..=100_000.0))
+                            .changed();
+
+                        if tracker.id == WATER_TRACKER_ID {
+                            ui.label("");
+                        } else if ui.button(egui::RichText::new("❌").color(styling::AppTheme::default().error_color)).clicked() {
+                            to_remove = Some(tracker.id.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            // Goal and step tweaks are saved as they are made
+            if changed {
+                db.trackers.insert(db.current_user.clone(), trackers);
+            }
+
+            if let Some(tracker_id) = to_remove {
+                let user_id = db.current_user.clone();
+                self.error_message = service::logging::remove_tracker(db, undo_manager, &user_id, &tracker_id).err();
+            }
+
+            ui.add_space(16.0);
+
+            // New tracker form
+            styling::section_header(ui, "Add Counter");
+
+            egui::Grid::new("new_tracker_grid")
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut self.new_name)
+                        .hint_text("e.g., Vegetable servings")
+                        .desired_width(250.0));
+                    ui.end_row();
+
+                    ui.label("Unit:");
+                    ui.add(egui::TextEdit::singleline(&mut self.new_unit)
+                        .hint_text("e.g., servings, steps")
+                        .desired_width(250.0));
+                    ui.end_row();
+
+                    ui.label("Daily goal (optional):");
+                    ui.add(egui::TextEdit::singleline(&mut self.new_goal)
+                        .hint_text("e.g., 5")
+                        .desired_width(100.0));
+                    ui.end_row();
+
+                    ui.label("Step per click:");
+                    ui.add(egui::TextEdit::singleline(&mut self.new_increment)
+                        .desired_width(100.0));
+                    ui.end_row();
+                });
+
+            if let Some(ref error) = self.error_message {
+                ui.add_space(8.0);
+                ui.colored_label(
+                    styling::AppTheme::default().error_color,
+                    egui::RichText::new(error).size(14.0).strong()
+                );
+            }
+
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if styling::warning_button(ui, "Back to Home").clicked() {
+                        self.error_message = None;
+                        *current_state = AppState::Home;
+                    }
+
+                    ui.add_space(10.0);
+
+                    if styling::success_button(ui, "Add Counter").clicked() {
+                        self.add_tracker(db, undo_manager);
+                    }
+                });
+            });
+        });
+        });
+    }
+
+    fn add_tracker(&mut self, db: &mut Database, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        match service::logging::add_tracker(
+            db,
+            undo_manager,
+            &user_id,
+            &self.new_name,
+            &self.new_unit,
+            &self.new_goal,
+            &self.new_increment
+        ) {
+            Ok(()) => {
+                self.error_message = None;
+                self.new_name.clear();
+                self.new_unit.clear();
+                self.new_goal.clear();
+                self.new_increment = "1".to_string();
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+}
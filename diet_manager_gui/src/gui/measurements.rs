@@ -0,0 +1,174 @@
+use eframe::egui;
+use crate::models::UserProfile;
+use crate::gui::styling;
+use crate::units::{self, Units};
+
+// Height, weight and age fields shared by registration and the profile screen.
+// What is typed is parsed in the chosen units and each field shows its own error.
+pub struct MeasurementsInput {
+    height: String, // Centimetres, or feet for imperial
+    inches: String,
+    weight: String,
+    age: String,
+    height_error: Option<String>,
+    weight_error: Option<String>,
+    age_error: Option<String>,
+}
+
+// Parsed values, always metric
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurements {
+    pub height_cm: f32,
+    pub weight_kg: f32,
+    pub age: u32,
+}
+
+impl MeasurementsInput {
+    pub fn new() -> Self {
+        Self {
+            height: String::new(),
+            inches: String::new(),
+            weight: String::new(),
+            age: String::new(),
+            height_error: None,
+            weight_error: None,
+            age_error: None,
+        }
+    }
+
+    pub fn from_profile(profile: &UserProfile) -> Self {
+        let mut input = Self::new();
+        input.fill(profile.height_cm, profile.weight_kg, profile.units);
+        input.age = profile.age.to_string();
+        input
+    }
+
+    fn fill(&mut self, height_cm: f32, weight_kg: f32, units: Units) {
+        match units {
+            Units::Metric => {
+                self.height = format!("{:.0}", height_cm);
+                self.inches.clear();
+            }
+            Units::Imperial => {
+                let (feet, inches) = units::cm_to_feet_inches(height_cm);
+                self.height = feet.to_string();
+                self.inches = inches.to_string();
+            }
+        }
+        self.weight = format!("{:.1}", units.weight_from_kg(weight_kg));
+    }
+
+    // Rewrite what has been typed so far when the units change. Fields that
+    // don't parse are cleared rather than reinterpreted.
+    pub fn convert(&mut self, from: Units, to: Units) {
+        if from == to {
+            return;
+        }
+        let height = units::parse_height(&self.height, &self.inches, from).ok();
+        let weight = units::parse_weight(&self.weight, from, "Weight").ok();
+        self.fill(height.unwrap_or_default(), weight.unwrap_or_default(), to);
+        if height.is_none() {
+            self.height.clear();
+            self.inches.clear();
+        }
+        if weight.is_none() {
+            self.weight.clear();
+        }
+        self.height_error = None;
+        self.weight_error = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, units: Units) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("📏").size(20.0));
+            ui.vertical(|ui| {
+                match units {
+                    Units::Metric => {
+                        ui.label("Height (cm):");
+                        ui.add(egui::TextEdit::singleline(&mut self.height)
+                            .hint_text("e.g. 175")
+                            .desired_width(300.0));
+                    }
+                    Units::Imperial => {
+                        ui.label("Height:");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.height).hint_text("5").desired_width(60.0));
+                            ui.label("ft");
+                            ui.add(egui::TextEdit::singleline(&mut self.inches).hint_text("9").desired_width(60.0));
+                            ui.label("in");
+                        });
+                    }
+                }
+                field_error(ui, &self.height_error);
+            });
+        });
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("🗓️").size(20.0));
+            ui.vertical(|ui| {
+                ui.label("Age:");
+                ui.add(egui::TextEdit::singleline(&mut self.age)
+                    .hint_text("Enter your age")
+                    .desired_width(300.0));
+                field_error(ui, &self.age_error);
+            });
+        });
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("⚖️").size(20.0));
+            ui.vertical(|ui| {
+                ui.label(format!("Weight ({}):", units.weight_unit()));
+                ui.add(egui::TextEdit::singleline(&mut self.weight)
+                    .hint_text(if units == Units::Imperial { "e.g. 160" } else { "e.g. 72.5" })
+                    .desired_width(300.0));
+                field_error(ui, &self.weight_error);
+            });
+        });
+    }
+
+    // The values if every field is valid, without touching the error messages
+    pub fn current(&self, units: Units) -> Option<Measurements> {
+        let (height, weight, age) = self.parse(units);
+        Some(Measurements { height_cm: height.ok()?, weight_kg: weight.ok()?, age: age.ok()? })
+    }
+
+    // The values, or None after setting a message on each field that is wrong
+    pub fn validate(&mut self, units: Units) -> Option<Measurements> {
+        let (height, weight, age) = self.parse(units);
+        self.height_error = height.as_ref().err().cloned();
+        self.weight_error = weight.as_ref().err().cloned();
+        self.age_error = age.as_ref().err().cloned();
+        Some(Measurements { height_cm: height.ok()?, weight_kg: weight.ok()?, age: age.ok()? })
+    }
+
+    fn parse(&self, units: Units) -> (Result<f32, String>, Result<f32, String>, Result<u32, String>) {
+        (
+            units::parse_height(&self.height, &self.inches, units),
+            units::parse_weight(&self.weight, units, "Weight"),
+            units::parse_age(&self.age),
+        )
+    }
+}
+
+fn field_error(ui: &mut egui::Ui, error: &Option<String>) {
+    if let Some(error) = error {
+        ui.colored_label(styling::AppTheme::default().error_color, egui::RichText::new(error).size(12.0));
+    }
+}
+
+// Metric/imperial choice; returns the previous units when it changed
+pub fn units_selector(ui: &mut egui::Ui, units: &mut Units) -> Option<Units> {
+    let previous = *units;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("📐").size(20.0));
+        ui.label("Units:");
+        for option in Units::ALL {
+            ui.selectable_value(units, option, option.label());
+        }
+    });
+    (previous != *units).then_some(previous)
+}
@@ -6,11 +6,21 @@ pub mod download_food_data_screen;
 mod view_daily_log_screen;
 mod login_screen; // Add this line
 mod register_screen; // Add this line
-pub mod undo_manager; // Add this line
 mod add_food_to_log_screen; // Add this line
 mod edit_food_log_screen;   // Add this line
 mod update_profile_screen;  // Add this line
+mod log_exercise_screen;
+mod manage_trackers_screen;
+mod export_screen;
+mod import_screen;
+mod shopping_list_screen;
+mod account_screen;
+mod two_factor_screen;
+mod unlock_screen;
+mod admin_screen;
 pub mod styling; // Add this line
+pub mod calendar;
+pub mod measurements;
 
 pub use home_screen::*;
 pub use add_basic_food_screen::*;
@@ -19,7 +29,15 @@ pub use download_food_data_screen::*;
 pub use view_daily_log_screen::*;
 pub use login_screen::*; // Add this line
 pub use register_screen::*; // Add this line
-// pub use undo_manager::*; // Add this line
 pub use add_food_to_log_screen::*; // Add this line
 pub use edit_food_log_screen::*;   // Add this line
 pub use update_profile_screen::*;  // Add this line
+pub use log_exercise_screen::*;
+pub use manage_trackers_screen::*;
+pub use export_screen::*;
+pub use import_screen::*;
+pub use shopping_list_screen::*;
+pub use account_screen::*;
+pub use two_factor_screen::*;
+pub use unlock_screen::*;
+pub use admin_screen::*;
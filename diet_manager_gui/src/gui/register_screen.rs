@@ -1,16 +1,18 @@
 use eframe::egui;
-use crate::models::{Database, User, UserProfile, Gender, ActivityLevel, CalorieCalculationMethod};
+use crate::models::{Database, UserProfile, Gender, ActivityLevel, CalorieCalculationMethod};
 use crate::app_state::AppState;
-use uuid::Uuid;
 use crate::gui::styling;
+use crate::gui::measurements::{self, MeasurementsInput};
+use crate::undo::UndoManager;
+use crate::service;
+use crate::units::Units;
 
 pub struct RegisterScreen {
     username: String,
     password: String,
     gender: Gender,
-    height_cm: String,
-    age: String,
-    weight_kg: String,
+    units: Units,
+    measurements: MeasurementsInput,
     activity_level: ActivityLevel,
     calorie_method: CalorieCalculationMethod,
     error_message: Option<String>,
@@ -22,16 +24,21 @@ impl RegisterScreen {
             username: String::new(),
             password: String::new(),
             gender: Gender::Male,
-            height_cm: String::new(),
-            age: String::new(),
-            weight_kg: String::new(),
+            units: Units::Metric,
+            measurements: MeasurementsInput::new(),
             activity_level: ActivityLevel::Moderate,
             calorie_method: CalorieCalculationMethod::HarrisBenedict,
             error_message: None,
         }
     }
 
-    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
         ui.vertical_centered(|ui| {
             ui.heading(egui::RichText::new("Create Account").size(28.0).strong());
             ui.add_space(4.0);
@@ -87,42 +94,13 @@ impl RegisterScreen {
 
             ui.add_space(8.0);
 
-            // Height field
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("📏").size(20.0));
-                ui.vertical(|ui| {
-                    ui.label("Height (cm):");
-                    ui.add(egui::TextEdit::singleline(&mut self.height_cm)
-                        .hint_text("Enter your height")
-                        .desired_width(300.0));
-                });
-            });
-
-            ui.add_space(8.0);
-
-            // Age field
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("🗓️").size(20.0));
-                ui.vertical(|ui| {
-                    ui.label("Age:");
-                    ui.add(egui::TextEdit::singleline(&mut self.age)
-                        .hint_text("Enter your age")
-                        .desired_width(300.0));
-                });
-            });
+            if let Some(previous) = measurements::units_selector(ui, &mut self.units) {
+                self.measurements.convert(previous, self.units);
+            }
 
             ui.add_space(8.0);
 
-            // Weight field
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("⚖️").size(20.0));
-                ui.vertical(|ui| {
-                    ui.label("Weight (kg):");
-                    ui.add(egui::TextEdit::singleline(&mut self.weight_kg)
-                        .hint_text("Enter your weight")
-                        .desired_width(300.0));
-                });
-            });
+            self.measurements.show(ui, self.units);
 
             ui.add_space(16.0);
 
@@ -175,59 +153,44 @@ impl RegisterScreen {
                     ui.add_space(10.0);
 
                     if styling::success_button(ui, "Register").clicked() {
-                        self.handle_registration(db, current_state);
+                        self.handle_registration(db, current_state, undo_manager);
                     }
                 });
             });
         });
     }
 
-    fn handle_registration(&mut self, db: &mut Database, current_state: &mut AppState) {
-        // Check for empty fields
-        if self.username.is_empty() || self.password.is_empty() {
-            self.error_message = Some("Username and password are required.".to_string());
-            return;
-        }
-
-        // Check for existing username
-        if db.users.contains_key(&self.username) {
-            self.error_message = Some("Username already exists.".to_string());
-            return;
-        }
-
-        // Parse numeric values
-        let height_cm = self.height_cm.parse().unwrap_or(0.0);
-        let age = self.age.parse().unwrap_or(0);
-        let weight_kg = self.weight_kg.parse().unwrap_or(0.0);
-
-        // Validate inputs
-        if height_cm <= 0.0 || age <= 0 || weight_kg <= 0.0 {
-            self.error_message = Some("Invalid height, age, or weight.".to_string());
-            return;
-        }
+    fn handle_registration(&mut self, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let measured = match self.measurements.validate(self.units) {
+            Some(measured) => measured,
+            None => {
+                self.error_message = Some("Please correct the highlighted fields".to_string());
+                return;
+            }
+        };
 
-        // Create user
-        let user_id = Uuid::new_v4().to_string();
         let profile = UserProfile {
             gender: self.gender.clone(),
-            height_cm,
-            age,
+            height_cm: measured.height_cm,
+            age: measured.age,
             calorie_method: self.calorie_method.clone(),
-            weight_kg,
+            weight_kg: measured.weight_kg,
             activity_level: self.activity_level.clone(),
+            weight_goal: None,
+            earn_back_exercise: true,
+            units: self.units,
         };
 
-        let user = User {
-            user_id: user_id.clone(),
-            username: self.username.clone(),
-            password: self.password.clone(),
-            profile,
-        };
-
-        // Insert new user and set as current user
-        db.users.insert(self.username.clone(), user);
-        db.current_user = user_id;
+        match service::auth::register(db, &self.username, &self.password, profile) {
+            Ok(_) => {
+                self.error_message = None;
+                *current_state = AppState::Home;
 
-        *current_state = AppState::Home;
+                // Start the new session's undo history from the fresh account
+                undo_manager.clear();
+                undo_manager.initialize(db.clone());
+            }
+            Err(e) => self.error_message = Some(e),
+        }
     }
-}
\ No newline at end of file
+}
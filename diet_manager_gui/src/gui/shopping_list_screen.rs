@@ -0,0 +1,168 @@
+use eframe::egui;
+use chrono::{ Duration, Local, NaiveDate };
+use std::path::Path;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::undo::UndoManager;
+use crate::service::shopping::{ self, ShoppingListFormat };
+use crate::gui::styling;
+
+pub struct ShoppingListScreen {
+    from_date: String,
+    to_date: String,
+    file_path: String,
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl ShoppingListScreen {
+    pub fn new() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            from_date: today.format("%Y-%m-%d").to_string(),
+            to_date: (today + Duration::days(6)).format("%Y-%m-%d").to_string(),
+            file_path: "shopping_list.txt".to_string(),
+            status_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState, undo_manager: &mut UndoManager) {
+        let theme = styling::AppTheme::default();
+
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Shopping List").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Everything needed for the meals you have planned");
+            ui.add_space(20.0);
+        });
+
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Dates");
+
+            ui.horizontal(|ui| {
+                ui.label("From:");
+                ui.add(egui::TextEdit::singleline(&mut self.from_date)
+                    .hint_text("YYYY-MM-DD")
+                    .desired_width(100.0));
+                ui.label("To:");
+                ui.add(egui::TextEdit::singleline(&mut self.to_date)
+                    .hint_text("YYYY-MM-DD")
+                    .desired_width(100.0));
+                if ui.button("Next 7 days").clicked() {
+                    let today = Local::now().date_naive();
+                    self.from_date = today.format("%Y-%m-%d").to_string();
+                    self.to_date = (today + Duration::days(6)).format("%Y-%m-%d").to_string();
+                }
+            });
+
+            ui.add_space(16.0);
+
+            let (from, to) = match self.date_range() {
+                Ok(range) => range,
+                Err(e) => {
+                    ui.colored_label(theme.error_color, e);
+                    return;
+                }
+            };
+
+            let user_id = db.current_user.clone();
+            let items = match shopping::shopping_list(db, &user_id, &from, &to) {
+                Ok(items) => items,
+                Err(e) => {
+                    ui.colored_label(theme.error_color, e);
+                    return;
+                }
+            };
+
+            styling::section_header(ui, "Items");
+
+            if items.is_empty() {
+                ui.label(egui::RichText::new("No meals planned for these dates. Plan food from Add Food to Log.")
+                    .italics());
+            }
+
+            let mut toggled = None;
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                let mut group = None;
+                for item in &items {
+                    if group != Some(&item.group) {
+                        ui.add_space(6.0);
+                        ui.label(egui::RichText::new(&item.group).strong().color(theme.primary_color));
+                        group = Some(&item.group);
+                    }
+
+                    let mut checked = item.checked;
+                    let text = format!("{}  ×{:.1} servings", item.name, item.servings);
+                    if ui.checkbox(&mut checked, text).changed() {
+                        toggled = Some((item.food_id.clone(), checked));
+                    }
+                }
+            });
+
+            if let Some((food_id, checked)) = toggled {
+                if let Err(e) = shopping::set_checked(db, undo_manager, &user_id, &food_id, checked) {
+                    self.status_message = Some((e, true));
+                }
+            }
+
+            if items.iter().any(|item| item.checked) {
+                ui.add_space(8.0);
+                if ui.button("Clear Ticks").clicked() {
+                    if let Err(e) = shopping::clear_checked(db, undo_manager, &user_id) {
+                        self.status_message = Some((e, true));
+                    }
+                }
+            }
+
+            ui.add_space(16.0);
+            styling::section_header(ui, "Save or Print");
+
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.add(egui::TextEdit::singleline(&mut self.file_path).desired_width(300.0));
+            });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if styling::primary_button(ui, "Save as Text").clicked() {
+                    self.status_message = Some(self.save(&items, &from, &to, ShoppingListFormat::Text));
+                }
+                if styling::primary_button(ui, "Save as CSV").clicked() {
+                    self.status_message = Some(self.save(&items, &from, &to, ShoppingListFormat::Csv));
+                }
+            });
+
+            if let Some((ref message, is_error)) = self.status_message {
+                ui.add_space(12.0);
+                let color = if is_error { theme.error_color } else { theme.success_color };
+                ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+            }
+        });
+
+        ui.add_space(16.0);
+
+        if styling::warning_button(ui, "Back to Home").clicked() {
+            self.status_message = None;
+            *current_state = AppState::Home;
+        }
+    }
+
+    fn date_range(&self) -> Result<(String, String), String> {
+        let from = NaiveDate::parse_from_str(self.from_date.trim(), "%Y-%m-%d")
+            .map_err(|_| "Start date must be in YYYY-MM-DD format".to_string())?;
+        let to = NaiveDate::parse_from_str(self.to_date.trim(), "%Y-%m-%d")
+            .map_err(|_| "End date must be in YYYY-MM-DD format".to_string())?;
+        Ok((from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()))
+    }
+
+    fn save(&mut self, items: &[shopping::ShoppingItem], from: &str, to: &str, format: ShoppingListFormat) -> (String, bool) {
+        if self.file_path.trim().is_empty() {
+            return ("Please enter a file name".to_string(), true);
+        }
+        self.file_path = Path::new(self.file_path.trim()).with_extension(format.extension()).to_string_lossy().to_string();
+
+        match shopping::export_shopping_list(items, from, to, format, Path::new(&self.file_path)) {
+            Ok(count) => (format!("Saved {} items to {}", count, self.file_path), false),
+            Err(e) => (format!("Save failed: {}", e), true),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+// Shared theme and widget helpers; not every screen uses every helper
+#![allow(dead_code)]
+
 use eframe::egui::{self, Color32, Rounding, Stroke, Vec2};
 
 pub struct AppTheme {
@@ -71,8 +74,7 @@ pub fn apply_theme(ctx: &egui::Context) {
 }
 
 pub fn heading_style(_ui: &mut egui::Ui) -> egui::TextStyle {
-    let heading = egui::TextStyle::Heading;
-    heading
+    egui::TextStyle::Heading
 }
 
 pub fn styled_button(ui: &mut egui::Ui, text: &str) -> egui::Response {
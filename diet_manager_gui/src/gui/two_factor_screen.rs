@@ -0,0 +1,230 @@
+use eframe::egui;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::service;
+
+pub struct TwoFactorScreen {
+    enrollment: Option<(String, String)>, // (secret, otpauth URI) while setting up
+    password: String,
+    code: String,
+    recovery_codes: Vec<String>, // Shown once, right after they are made
+    status_message: Option<(String, bool)>, // (message, is_error)
+}
+
+impl TwoFactorScreen {
+    pub fn new() -> Self {
+        Self {
+            enrollment: None,
+            password: String::new(),
+            code: String::new(),
+            recovery_codes: Vec::new(),
+            status_message: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
+        let theme = styling::AppTheme::default();
+        let user_id = db.current_user.clone();
+        let recovery_codes_left = match db.find_user_by_id(&user_id) {
+            Some(user) => user.two_factor.as_ref().map(|two_factor| two_factor.recovery_codes.len()),
+            None => {
+                *current_state = AppState::Login;
+                return;
+            }
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.heading(egui::RichText::new("Two-Factor Authentication").size(28.0).strong());
+            ui.add_space(4.0);
+            ui.label("Ask for a code from an authenticator app after your password");
+            ui.add_space(20.0);
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            styling::card_frame().show(ui, |ui| {
+                if !self.recovery_codes.is_empty() {
+                    self.render_recovery_codes(ui);
+                } else if let Some(left) = recovery_codes_left {
+                    self.render_enabled(ui, db, undo_manager, &user_id, left);
+                } else if self.enrollment.is_some() {
+                    self.render_enrollment(ui, db, undo_manager, &user_id);
+                } else {
+                    styling::section_header(ui, "Status: Off");
+                    ui.label("Anyone who knows your password can sign in as you. With two-factor authentication \
+                              they would also need the app on your phone, which works without a network connection.");
+                    ui.add_space(8.0);
+                    if styling::primary_button(ui, "Set Up").clicked() {
+                        self.enrollment = Some(service::auth::new_two_factor_secret(db, &user_id));
+                        self.status_message = None;
+                    }
+                }
+
+                if let Some((ref message, is_error)) = self.status_message {
+                    ui.add_space(12.0);
+                    let color = if is_error { theme.error_color } else { theme.success_color };
+                    ui.colored_label(color, egui::RichText::new(message).size(14.0).strong());
+                }
+            });
+
+            ui.add_space(16.0);
+
+            if styling::warning_button(ui, "Back to Account").clicked() {
+                *self = Self::new();
+                *current_state = AppState::Account;
+            }
+        });
+    }
+
+    fn render_enrollment(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager, user_id: &str) {
+        let (secret, uri) = match &self.enrollment {
+            Some(enrollment) => enrollment.clone(),
+            None => return,
+        };
+
+        styling::section_header(ui, "1. Add this account to your authenticator app");
+        ui.label("Enter the secret key by hand, or paste the link into an app that accepts otpauth:// links.");
+        ui.add_space(4.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Secret key:");
+            ui.label(egui::RichText::new(group_in_fours(&secret)).monospace().strong());
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|output| output.copied_text = secret.clone());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Link:");
+            ui.label(egui::RichText::new(&uri).monospace().size(11.0));
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|output| output.copied_text = uri.clone());
+            }
+        });
+        ui.label(egui::RichText::new("Time-based, 6 digits, every 30 seconds").size(12.0).italics());
+
+        ui.add_space(16.0);
+        styling::section_header(ui, "2. Confirm");
+
+        egui::Grid::new("two_factor_enroll_grid")
+            .spacing([10.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Code from the app:");
+                ui.add(egui::TextEdit::singleline(&mut self.code).hint_text("123456").desired_width(120.0));
+                ui.end_row();
+
+                ui.label("Current password:");
+                ui.add(egui::TextEdit::singleline(&mut self.password).password(true).desired_width(250.0));
+                ui.end_row();
+            });
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if styling::success_button(ui, "Turn On").clicked() {
+                match service::auth::enable_two_factor(db, undo_manager, user_id, &self.password, &secret, &self.code) {
+                    Ok(recovery_codes) => {
+                        self.enrollment = None;
+                        self.recovery_codes = recovery_codes;
+                        self.status_message = save(db, "Two-factor authentication is on");
+                    }
+                    Err(e) => self.status_message = Some((e, true)),
+                }
+                self.password.clear();
+                self.code.clear();
+            }
+            if styling::warning_button(ui, "Cancel").clicked() {
+                *self = Self::new();
+            }
+        });
+    }
+
+    fn render_enabled(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager, user_id: &str, recovery_codes_left: usize) {
+        styling::section_header(ui, "Status: On");
+        ui.label("You will be asked for a code from your authenticator app each time you log in.");
+        ui.label(format!("Unused recovery codes: {}", recovery_codes_left));
+        if recovery_codes_left <= 2 {
+            ui.colored_label(styling::AppTheme::default().warning_color, "You are running out of recovery codes; make new ones.");
+        }
+
+        ui.add_space(12.0);
+        ui.horizontal(|ui| {
+            ui.label("Current password:");
+            ui.add(egui::TextEdit::singleline(&mut self.password).password(true).desired_width(250.0));
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if styling::primary_button(ui, "New Codes").on_hover_text("Replace all recovery codes").clicked() {
+                match service::auth::regenerate_recovery_codes(db, undo_manager, user_id, &self.password) {
+                    Ok(recovery_codes) => {
+                        self.recovery_codes = recovery_codes;
+                        self.status_message = save(db, "New recovery codes made; the old ones no longer work");
+                    }
+                    Err(e) => self.status_message = Some((e, true)),
+                }
+                self.password.clear();
+            }
+            if styling::error_button(ui, "Turn Off").clicked() {
+                self.status_message = match service::auth::disable_two_factor(db, undo_manager, user_id, &self.password) {
+                    Ok(()) => save(db, "Two-factor authentication is off"),
+                    Err(e) => Some((e, true)),
+                };
+                self.password.clear();
+            }
+        });
+    }
+
+    fn render_recovery_codes(&mut self, ui: &mut egui::Ui) {
+        styling::section_header(ui, "Recovery Codes");
+        ui.label("Each code logs you in once if you lose your authenticator app. \
+                  Write them down or print them now; they won't be shown again.");
+        ui.add_space(8.0);
+
+        egui::Grid::new("recovery_codes_grid")
+            .spacing([24.0, 6.0])
+            .show(ui, |ui| {
+                for (i, code) in self.recovery_codes.iter().enumerate() {
+                    ui.label(egui::RichText::new(code).monospace().size(16.0));
+                    if i % 2 == 1 {
+                        ui.end_row();
+                    }
+                }
+            });
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if ui.button("Copy All").clicked() {
+                ui.output_mut(|output| output.copied_text = self.recovery_codes.join("\n"));
+            }
+            if styling::primary_button(ui, "Done").clicked() {
+                self.recovery_codes.clear();
+                self.status_message = None;
+            }
+        });
+    }
+}
+
+// Saved right away like the other account changes
+fn save(db: &Database, success: &str) -> Option<(String, bool)> {
+    Some(match crate::database::save_database(db) {
+        Ok(()) => (success.to_string(), false),
+        Err(e) => (format!("{}, but saving failed: {}", success, e), true),
+    })
+}
+
+// "JBSWY3DPEHPK3PXP" -> "JBSW Y3DP EHPK 3PXP"
+fn group_in_fours(secret: &str) -> String {
+    secret
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
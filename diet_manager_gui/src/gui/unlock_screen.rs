@@ -0,0 +1,76 @@
+use eframe::egui;
+use crate::models::Database;
+use crate::app_state::AppState;
+use crate::gui::styling;
+use crate::undo::UndoManager;
+use crate::config;
+use crate::database;
+
+// Shown on startup when the database file is encrypted
+pub struct UnlockScreen {
+    passphrase: String,
+    error_message: Option<String>,
+}
+
+impl UnlockScreen {
+    pub fn new() -> Self {
+        Self {
+            passphrase: String::new(),
+            error_message: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
+        let theme = styling::AppTheme::default();
+        let path = config::database_file();
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.0);
+            ui.heading(egui::RichText::new("Database Locked").size(28.0).strong());
+            ui.add_space(8.0);
+            ui.label(format!("{} is encrypted. Enter its passphrase to open it.", path.display()));
+            ui.add_space(30.0);
+
+            styling::card_frame().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("🔒").size(20.0));
+                    let response = ui.add(
+                        egui::TextEdit
+                            ::singleline(&mut self.passphrase)
+                            .password(true)
+                            .hint_text("Passphrase")
+                            .desired_width(250.0)
+                    );
+                    let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+
+                    if styling::primary_button(ui, "Unlock").clicked() || submitted {
+                        match database::unlock_database(&path, &self.passphrase) {
+                            Ok(()) => {
+                                *db = database::load_database();
+                                undo_manager.initialize(db.clone());
+                                *self = Self::new();
+                                *current_state = AppState::Login;
+                            }
+                            Err(e) => self.error_message = Some(e),
+                        }
+                        self.passphrase.clear();
+                    }
+                });
+
+                if let Some(error) = &self.error_message {
+                    ui.add_space(12.0);
+                    ui.colored_label(theme.error_color, egui::RichText::new(error).size(14.0).strong());
+                }
+
+                ui.add_space(12.0);
+                ui.label(egui::RichText::new("Use diet-manager-cli encryption to change the passphrase or turn encryption off.").size(12.0).italics());
+            });
+        });
+    }
+}
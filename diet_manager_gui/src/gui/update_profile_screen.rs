@@ -1,12 +1,17 @@
 use eframe::egui;
-use crate::models::{Database, Gender, ActivityLevel, CalorieCalculationMethod, User};
+use crate::models::{Database, Gender, ActivityLevel, CalorieCalculationMethod, User, WeightGoal, GoalPace, MAX_WEEKLY_RATE_KG};
 use crate::app_state::AppState;
 use crate::gui::styling;
+use crate::gui::measurements::{self, MeasurementsInput};
+use crate::undo::UndoManager;
+use crate::service;
+use crate::units::{MAX_WEIGHT_KG, MIN_WEIGHT_KG};
 
 pub struct UpdateProfileScreen {
     success_message: Option<String>,
     error_message: Option<String>,
     editing_user: Option<User>, // Store the user we're editing
+    measurements: MeasurementsInput,
     initialized: bool,         // Track if we've loaded the user
     should_return_home: bool,  // Flag to track navigation
 }
@@ -17,12 +22,19 @@ impl UpdateProfileScreen {
             success_message: None,
             error_message: None,
             editing_user: None,
+            measurements: MeasurementsInput::new(),
             initialized: false,
             should_return_home: false,
         }
     }
 
-    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+        undo_manager: &mut UndoManager
+    ) {
         // Check if we should return to home screen
         if self.should_return_home {
             self.initialized = false;
@@ -37,6 +49,7 @@ impl UpdateProfileScreen {
         // Initialize editing_user once when the screen is first shown
         if !self.initialized {
             if let Some(user) = db.users.values().find(|u| u.user_id == db.current_user) {
+                self.measurements = MeasurementsInput::from_profile(&user.profile);
                 self.editing_user = Some(user.clone());
             }
             self.initialized = true;
@@ -86,47 +99,14 @@ impl UpdateProfileScreen {
 
                     ui.add_space(8.0);
 
-                    // Height slider
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("📏").size(20.0));
-                        ui.vertical(|ui| {
-                            ui.label("Height (cm):");
-                            ui.add(egui::Slider::new(&mut user_clone.profile.height_cm, 100.0..=250.0)
-                                .text("cm")
-                                .clamp_to_range(true)
-                                .smart_aim(false)
-                                .fixed_decimals(1));
-                        });
-                    });
-
-                    ui.add_space(8.0);
-
-                    // Age slider
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("🗓️").size(20.0));
-                        ui.vertical(|ui| {
-                            ui.label("Age:");
-                            ui.add(egui::Slider::new(&mut user_clone.profile.age, 1..=120)
-                                .text("years")
-                                .clamp_to_range(true)
-                                .smart_aim(false));
-                        });
-                    });
+                    let units = &mut user_clone.profile.units;
+                    if let Some(previous) = measurements::units_selector(ui, units) {
+                        self.measurements.convert(previous, *units);
+                    }
 
                     ui.add_space(8.0);
 
-                    // Weight slider
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("⚖️").size(20.0));
-                        ui.vertical(|ui| {
-                            ui.label("Weight (kg):");
-                            ui.add(egui::Slider::new(&mut user_clone.profile.weight_kg, 30.0..=250.0)
-                                .text("kg")
-                                .clamp_to_range(true)
-                                .smart_aim(false)
-                                .fixed_decimals(1));
-                        });
-                    });
+                    self.measurements.show(ui, user_clone.profile.units);
 
                     ui.add_space(16.0);
 
@@ -158,16 +138,113 @@ impl UpdateProfileScreen {
                             "Mifflin-St Jeor Formula");
                     });
 
-                    // Calculate calorie target based on current settings
-                    let target_calories = user_clone.profile.calculate_target_calories();
+                    ui.add_space(16.0);
+
+                    // Weight goal section
+                    styling::section_header(ui, "Weight Goal");
+
+                    let mut has_goal = user_clone.profile.weight_goal.is_some();
+                    if ui.checkbox(&mut has_goal, "I want to lose or gain weight").changed() {
+                        user_clone.profile.weight_goal = if has_goal {
+                            Some(WeightGoal {
+                                target_weight_kg: user_clone.profile.weight_kg,
+                                pace: GoalPace::WeeklyRate(0.5),
+                            })
+                        } else {
+                            None
+                        };
+                    }
+
+                    // Goal sliders work in the chosen units; the profile stays metric
+                    let units = user_clone.profile.units;
+                    let unit = units.weight_unit();
+                    if let Some(ref mut goal) = user_clone.profile.weight_goal {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("🎯").size(20.0));
+                            ui.vertical(|ui| {
+                                ui.label(format!("Target weight ({}):", unit));
+                                let mut target = units.weight_from_kg(goal.target_weight_kg);
+                                let range = units.weight_from_kg(MIN_WEIGHT_KG)..=units.weight_from_kg(MAX_WEIGHT_KG);
+                                let slider = ui.add(egui::Slider::new(&mut target, range)
+                                    .text(unit)
+                                    .clamp_to_range(true)
+                                    .smart_aim(false)
+                                    .fixed_decimals(1));
+                                if slider.changed() {
+                                    goal.target_weight_kg = units.weight_to_kg(target);
+                                }
+                            });
+                        });
+
+                        ui.add_space(8.0);
+
+                        ui.horizontal(|ui| {
+                            let by_rate = matches!(goal.pace, GoalPace::WeeklyRate(_));
+                            if ui.radio(by_rate, "Weekly rate").clicked() && !by_rate {
+                                goal.pace = GoalPace::WeeklyRate(0.5);
+                            }
+                            if ui.radio(!by_rate, "Target date").clicked() && by_rate {
+                                let in_three_months = chrono::Local::now().date_naive() + chrono::Duration::days(90);
+                                goal.pace = GoalPace::TargetDate(in_three_months.format("%Y-%m-%d").to_string());
+                            }
+                        });
+
+                        match goal.pace {
+                            GoalPace::WeeklyRate(ref mut rate) => {
+                                let mut shown = units.weight_from_kg(*rate);
+                                let range = units.weight_from_kg(0.1)..=units.weight_from_kg(MAX_WEEKLY_RATE_KG);
+                                let slider = ui.add(egui::Slider::new(&mut shown, range)
+                                    .text(format!("{}/week", unit))
+                                    .clamp_to_range(true)
+                                    .smart_aim(false)
+                                    .fixed_decimals(2));
+                                if slider.changed() {
+                                    *rate = units.weight_to_kg(shown);
+                                }
+                            }
+                            GoalPace::TargetDate(ref mut date) => {
+                                ui.add(egui::TextEdit::singleline(date)
+                                    .hint_text("YYYY-MM-DD")
+                                    .desired_width(150.0));
+                            }
+                        }
+                    }
+
+                    ui.add_space(8.0);
+                    ui.checkbox(&mut user_clone.profile.earn_back_exercise,
+                        "Earn back exercise calories (add calories burned to my daily allowance)");
+
+                    // Calculate calorie target based on current settings, including
+                    // measurements being typed once they are valid
+                    let mut preview = user_clone.profile.clone();
+                    if let Some(measured) = self.measurements.current(preview.units) {
+                        preview.height_cm = measured.height_cm;
+                        preview.weight_kg = measured.weight_kg;
+                        preview.age = measured.age;
+                    }
+                    let target = preview.calorie_target_on(chrono::Local::now().date_naive());
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
                         ui.label(egui::RichText::new("Daily Calorie Target:").strong());
-                        ui.label(egui::RichText::new(format!("{:.0} kcal", target_calories))
+                        ui.label(egui::RichText::new(format!("{:.0} kcal", target.target))
                             .size(18.0)
                             .color(styling::AppTheme::default().accent_color)
                             .strong());
                     });
+                    if target.adjustment != 0.0 {
+                        ui.label(format!(
+                            "Maintenance {:.0} kcal {} {:.0} kcal/day for your goal",
+                            target.maintenance,
+                            if target.adjustment < 0.0 { "minus" } else { "plus" },
+                            target.adjustment.abs()
+                        ));
+                    }
+                    if target.floor_applied {
+                        ui.colored_label(
+                            styling::AppTheme::default().warning_color,
+                            format!("Raised to the {:.0} kcal safety minimum", target.target)
+                        );
+                    }
 
                     ui.add_space(16.0);
 
@@ -212,14 +289,24 @@ impl UpdateProfileScreen {
                 }
 
                 if save_clicked {
-                    // Update the actual user in the database
-                    if let Some(user) = db.users.get_mut(&user_clone.username) {
-                        user.profile = user_clone.profile.clone();
-                        self.success_message = Some("Profile updated successfully!".to_string());
-                        self.error_message = None;
-                    } else {
-                        self.error_message = Some("Failed to update profile".to_string());
-                        self.success_message = None;
+                    let result = match self.measurements.validate(user_clone.profile.units) {
+                        Some(measured) => {
+                            user_clone.profile.height_cm = measured.height_cm;
+                            user_clone.profile.weight_kg = measured.weight_kg;
+                            user_clone.profile.age = measured.age;
+                            service::auth::update_profile(db, undo_manager, &user_clone.user_id, user_clone.profile.clone())
+                        }
+                        None => Err("Please correct the highlighted fields".to_string()),
+                    };
+                    match result {
+                        Ok(()) => {
+                            self.success_message = Some("Profile updated successfully!".to_string());
+                            self.error_message = None;
+                        }
+                        Err(e) => {
+                            self.error_message = Some(e);
+                            self.success_message = None;
+                        }
                     }
                 }
             } else {
@@ -237,4 +324,4 @@ impl UpdateProfileScreen {
             }
         });
     }
-}
\ No newline at end of file
+}
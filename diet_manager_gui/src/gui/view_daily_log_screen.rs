@@ -1,18 +1,35 @@
 use eframe::egui;
 use chrono::{NaiveDate, Local, Duration};
-// use crate::models::Database;
-use crate::models::{Database, FoodLogEntry};
+use crate::models::{Database, Meal};
 use crate::app_state::AppState;
 use crate::gui::styling;
-use crate::gui::undo_manager::UndoManager;
+use crate::gui::calendar::Calendar;
+use crate::undo::UndoManager;
+use crate::service;
+
+// Longest run of days entries can be repeated over in one go
+const MAX_REPEAT_DAYS: u32 = 31;
+
 pub struct ViewDailyLogScreen {
     selected_date: NaiveDate,
+    calendar: Calendar,
+    copy_scope: Option<Meal>, // None copies the whole day
+    copy_target: String,
+    repeat_days: u32,
+    template_name: String,
+    copy_message: Option<(String, bool)>, // (message, is_error)
 }
 
 impl ViewDailyLogScreen {
     pub fn new() -> Self {
         Self {
             selected_date: Local::now().date_naive(),
+            calendar: Calendar::new("daily_log_calendar", true),
+            copy_scope: None,
+            copy_target: String::new(),
+            repeat_days: 1,
+            template_name: String::new(),
+            copy_message: None,
         }
     }
 
@@ -22,38 +39,18 @@ impl ViewDailyLogScreen {
             ui.add_space(4.0);
 
             // Date navigation
-            ui.horizontal(|ui| {
-                if styling::primary_button(ui, "◀ Previous").clicked() {
-                    self.selected_date -= Duration::days(1);
-                }
-
-                ui.label(egui::RichText::new(self.selected_date.format("%A, %B %d, %Y").to_string())
-                    .size(18.0)
-                    .strong());
-
-                let tomorrow = self.selected_date + Duration::days(1);
-                let today = Local::now().date_naive();
-                let button = styling::primary_button(ui, "Next ▶");
-                let is_future = tomorrow > today;
-
-                if button.clicked() && !is_future {
-                    self.selected_date += Duration::days(1);
-                }
-
-                if is_future {
-                    ui.label(egui::RichText::new("Cannot view future dates")
-                        .size(12.0)
-                        .color(styling::AppTheme::default().warning_color));
-                }
-            });
+            if self.calendar.show(ui, db, &mut self.selected_date) {
+                self.reset_copy_form();
+            }
 
             ui.add_space(20.0);
         });
 
         // Calculate nutrition data for the selected date
         let selected_date_str = self.selected_date.format("%Y-%m-%d").to_string();
-        let (total_calories, calories_goal, calories_remaining) =
-            self.calculate_daily_nutrition(db, &db.current_user, &selected_date_str);
+        let summary = service::reporting::daily_summary(db, &db.current_user, &selected_date_str);
+        let (total_calories, calories_burned, calories_goal, calories_remaining) =
+            (summary.consumed, summary.burned, summary.target, summary.remaining);
 
         // Nutrition Summary Card
         styling::card_frame().show(ui, |ui| {
@@ -68,6 +65,13 @@ impl ViewDailyLogScreen {
 
                 ui.add_space(40.0);
 
+                ui.vertical(|ui| {
+                    ui.label(egui::RichText::new("Burned").size(14.0));
+                    ui.label(egui::RichText::new(format!("{:.0} kcal", calories_burned)).size(24.0));
+                });
+
+                ui.add_space(40.0);
+
                 ui.vertical(|ui| {
                     ui.label(egui::RichText::new("Goal").size(14.0));
                     ui.label(egui::RichText::new(format!("{:.0} kcal", calories_goal)).size(24.0));
@@ -93,14 +97,30 @@ impl ViewDailyLogScreen {
             ui.add_space(12.0);
 
             // Progress bar showing percentage of daily goal consumed
-            if calories_goal > 0.0 {
-                let progress = (total_calories / calories_goal).min(1.0);
+            if summary.allowance > 0.0 {
+                let progress = summary.progress().min(1.0);
                 let progress_text = format!("{:.1}% of daily goal", progress * 100.0);
                 let progress_bar = egui::ProgressBar::new(progress)
                     .text(progress_text)
                     .animate(true);
                 ui.add(progress_bar);
             }
+
+            // Projection once the planned food is eaten too
+            if summary.planned > 0.0 {
+                let projected = summary.projected_remaining();
+                let theme = styling::AppTheme::default();
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label(format!("Planned: {:.0} kcal", summary.planned));
+                    ui.add_space(20.0);
+                    if projected >= 0.0 {
+                        ui.colored_label(theme.success_color, format!("Projected: {:.0} kcal remaining", projected));
+                    } else {
+                        ui.colored_label(theme.error_color, format!("Projected: {:.0} kcal over", -projected));
+                    }
+                });
+            }
         });
 
         ui.add_space(16.0);
@@ -109,15 +129,10 @@ impl ViewDailyLogScreen {
         styling::card_frame().show(ui, |ui| {
             styling::section_header(ui, "Food Entries");
 
-            // Collect entries first to avoid borrowing issues
-            let entries: Vec<FoodLogEntry> = db.food_logs
-                .get(&db.current_user)
-                .map_or(Vec::new(), |entries| {
-                    entries.iter()
-                        .filter(|entry| entry.date == selected_date_str)
-                        .cloned()
-                        .collect()
-                });
+            let user_id = db.current_user.clone();
+            let entries = service::logging::entries_for_day(db, &user_id, &selected_date_str);
+            let mut to_remove = None;
+            let mut to_mark_eaten = None;
 
             if entries.is_empty() {
                 ui.vertical_centered(|ui| {
@@ -153,10 +168,7 @@ impl ViewDailyLogScreen {
                         ui.separator();
 
                         for (i, entry) in entries.iter().enumerate() {
-                            let food_name = db.basic_foods.get(&entry.food_id)
-                                .map(|f| f.name.clone())
-                                .or_else(|| db.composite_foods.get(&entry.food_id).map(|f| f.name.clone()))
-                                .unwrap_or_else(|| entry.food_id.clone());
+                            let food_name = db.food_name(&entry.food_id);
 
                             let calories = db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings;
 
@@ -164,40 +176,16 @@ impl ViewDailyLogScreen {
                                 ui.label(format!("{}", i + 1));
                                 ui.add_space(20.0);
                                 ui.label(food_name);
+                                ui.label(egui::RichText::new(entry.meal.label()).weak());
+                                if entry.planned {
+                                    ui.label(egui::RichText::new("Planned").italics().color(styling::AppTheme::default().primary_color));
+                                }
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                     if ui.button(egui::RichText::new("❌").color(styling::AppTheme::default().error_color)).clicked() {
-                                        // Get food name for better description
-                                        let food_name = db.basic_foods.get(&entry.food_id)
-                                            .map(|f| f.name.clone())
-                                            .or_else(|| db.composite_foods.get(&entry.food_id).map(|f| f.name.clone()))
-                                            .unwrap_or_else(|| entry.food_id.clone());
-                                        println!("Attempting to delete food entry: {}", food_name);
-                                        // Record state BEFORE deletion
-                                        let db_before_delete = db.clone();
-                                        
-                                        // Perform the deletion
-                                        let mut deleted = false;
-                                        if let Some(entries) = db.food_logs.get_mut(&db.current_user) {
-                                            if let Some(pos) = entries.iter().position(|e|
-                                                e.date == entry.date &&
-                                                e.food_id == entry.food_id &&
-                                                e.servings == entry.servings &&
-                                                e.user_id == entry.user_id
-                                            ) {
-                                                println!("Found entry at position {}, removing...", pos);
-                                                entries.remove(pos);
-                                                deleted = true;
-                                            }
-                                        }
-                                        
-                                        // Only record the action if deletion was successful
-                                        if deleted {
-                                            undo_manager.record_action(db_before_delete, &format!("Removed {} from food log", food_name));
-                                             println!("Successfully recorded undo action");
-                                        }
-                                        else {
-                                        println!("Failed to find entry to delete");
+                                        to_remove = Some(entry.id.clone());
                                     }
+                                    if entry.planned && ui.button("✔ Eaten").on_hover_text("Log this planned food as eaten").clicked() {
+                                        to_mark_eaten = Some(entry.id.clone());
                                     }
                                     ui.add_space(10.0);
                                     ui.label(format!("{:.0} kcal", calories));
@@ -212,6 +200,18 @@ impl ViewDailyLogScreen {
                         }
                     });
 
+                if let Some(entry_id) = to_remove {
+                    if let Err(e) = service::logging::remove_entry(db, undo_manager, &user_id, &entry_id) {
+                        eprintln!("Failed to delete food entry: {}", e);
+                    }
+                }
+
+                if let Some(entry_id) = to_mark_eaten {
+                    if let Err(e) = service::logging::mark_eaten(db, undo_manager, &user_id, &entry_id) {
+                        eprintln!("Failed to mark food as eaten: {}", e);
+                    }
+                }
+
                 ui.add_space(16.0);
                 ui.horizontal(|ui| {
                     if styling::primary_button(ui, "Add More Food").clicked() {
@@ -223,6 +223,103 @@ impl ViewDailyLogScreen {
 
         ui.add_space(16.0);
 
+        self.render_copy_card(ui, db, undo_manager);
+
+        ui.add_space(16.0);
+
+        // Exercise Entries Card
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Exercise");
+
+            let weight_kg = db
+                .find_user_by_id(&db.current_user)
+                .map_or(0.0, |user| user.profile.weight_kg);
+            let exercise_entries: Vec<(String, f32, f32)> = db.exercise_logs
+                .get(&db.current_user)
+                .map_or(Vec::new(), |entries| {
+                    entries.iter()
+                        .filter(|entry| entry.date == selected_date_str)
+                        .map(|entry| match db.exercises.get(&entry.exercise_id) {
+                            Some(exercise) => (
+                                exercise.name.clone(),
+                                entry.duration_minutes,
+                                exercise.calories_burned(weight_kg, entry.duration_minutes),
+                            ),
+                            None => (entry.exercise_id.clone(), entry.duration_minutes, 0.0),
+                        })
+                        .collect()
+                });
+
+            if exercise_entries.is_empty() {
+                ui.label(egui::RichText::new("No exercise logged for this date").italics());
+            } else {
+                for (name, minutes, burned) in exercise_entries {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(format!("{:.0} kcal", burned));
+                            ui.add_space(20.0);
+                            ui.label(format!("{:.0} min", minutes));
+                        });
+                    });
+                }
+            }
+
+            ui.add_space(8.0);
+            if styling::primary_button(ui, "Log Exercise").clicked() {
+                *current_state = AppState::LogExercise;
+            }
+        });
+
+        ui.add_space(16.0);
+
+        // Daily Trackers Card with the week leading up to the selected date
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Daily Trackers");
+
+            let trackers = db.trackers.get(&db.current_user).cloned().unwrap_or_default();
+            if trackers.is_empty() {
+                ui.label(egui::RichText::new("No trackers set up").italics());
+            } else {
+                let week: Vec<NaiveDate> = (0..7)
+                    .rev()
+                    .map(|offset| self.selected_date - Duration::days(offset))
+                    .collect();
+
+                egui::Grid::new("tracker_history_grid")
+                    .striped(true)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Tracker").strong());
+                        for day in &week {
+                            ui.label(egui::RichText::new(day.format("%a %d").to_string()).strong());
+                        }
+                        ui.end_row();
+
+                        for tracker in &trackers {
+                            ui.label(format!("{} ({})", tracker.name, tracker.unit));
+                            for day in &week {
+                                let value = db.tracker_value(
+                                    &db.current_user,
+                                    &tracker.id,
+                                    &day.format("%Y-%m-%d").to_string()
+                                );
+                                let met_goal = tracker.daily_goal.is_some_and(|goal| value >= goal);
+                                let color = if met_goal {
+                                    styling::AppTheme::default().success_color
+                                } else {
+                                    styling::AppTheme::default().text_color
+                                };
+                                ui.colored_label(color, format!("{:.0}", value));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+
+        ui.add_space(16.0);
+
         // Navigation buttons
         ui.horizontal(|ui| {
             if styling::warning_button(ui, "Back to Home").clicked() {
@@ -231,30 +328,95 @@ impl ViewDailyLogScreen {
         });
     }
 
-    fn calculate_daily_nutrition(
-        &self,
-        db: &Database,
-        user_id: &str,
-        date: &str
-    ) -> (f32, f32, f32) {
-        // Calculate total calories consumed
-        let total_calories = db.food_logs
-            .get(user_id)
-            .map_or(0.0, |entries| {
-                entries.iter()
-                    .filter(|entry| entry.date == date)
-                    .map(|entry| db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
-                    .sum()
+    // Copy the day (or one meal) to other dates, or save it as a named template
+    fn render_copy_card(&mut self, ui: &mut egui::Ui, db: &mut Database, undo_manager: &mut UndoManager) {
+        let user_id = db.current_user.clone();
+        let selected_date_str = self.selected_date.format("%Y-%m-%d").to_string();
+        if service::logging::entries_for_day(db, &user_id, &selected_date_str).is_empty() {
+            return;
+        }
+
+        if self.copy_target.is_empty() {
+            self.copy_target = (self.selected_date + Duration::days(1)).format("%Y-%m-%d").to_string();
+        }
+
+        styling::card_frame().show(ui, |ui| {
+            styling::section_header(ui, "Copy & Templates");
+
+            ui.horizontal(|ui| {
+                ui.label("What:");
+                egui::ComboBox::from_id_source("copy_scope")
+                    .selected_text(self.copy_scope.map_or("Whole day", |meal| meal.label()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.copy_scope, None, "Whole day");
+                        for meal in Meal::ALL {
+                            ui.selectable_value(&mut self.copy_scope, Some(meal), meal.label());
+                        }
+                    });
             });
 
-        // Get daily calorie goal from user profile
-        let calories_goal = db.users.values()
-            .find(|u| u.user_id == user_id)
-            .map(|user| user.profile.calculate_target_calories())
-            .unwrap_or(0.0);
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.label("Copy to:");
+                ui.add(egui::TextEdit::singleline(&mut self.copy_target).hint_text("YYYY-MM-DD").desired_width(100.0));
+                ui.label("for");
+                ui.add(egui::DragValue::new(&mut self.repeat_days).clamp_range(1..=MAX_REPEAT_DAYS).suffix(" day(s)"));
+
+                if styling::primary_button(ui, "Copy").clicked() {
+                    self.copy_message = Some(match self.copy_dates() {
+                        Ok(dates) => match service::logging::copy_entries(
+                            db, undo_manager, &user_id, &selected_date_str, &dates, self.copy_scope
+                        ) {
+                            Ok(count) => (format!("Copied {} entries", count), false),
+                            Err(e) => (e, true),
+                        },
+                        Err(e) => (e, true),
+                    });
+                }
+            });
 
-        let calories_remaining = calories_goal - total_calories;
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.label("Save as template:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_name)
+                    .hint_text("e.g. Standard workday lunch")
+                    .desired_width(200.0));
+
+                if styling::success_button(ui, "Save Template").clicked() {
+                    self.copy_message = Some(match service::templates::save_template(
+                        db, undo_manager, &user_id, &self.template_name, &selected_date_str, self.copy_scope
+                    ) {
+                        Ok(count) => {
+                            let message = format!("Saved '{}' with {} foods", self.template_name.trim(), count);
+                            self.template_name.clear();
+                            (message, false)
+                        }
+                        Err(e) => (e, true),
+                    });
+                }
+            });
+
+            if let Some((message, is_error)) = &self.copy_message {
+                ui.add_space(8.0);
+                let theme = styling::AppTheme::default();
+                ui.colored_label(if *is_error { theme.error_color } else { theme.success_color }, message);
+            }
+        });
+    }
+
+    // The copy target defaults to the day after whichever date is shown
+    fn reset_copy_form(&mut self) {
+        self.copy_target.clear();
+        self.copy_message = None;
+    }
+
+    // The target date and the days after it, one per repeat
+    fn copy_dates(&self) -> Result<Vec<String>, String> {
+        let start = NaiveDate::parse_from_str(self.copy_target.trim(), "%Y-%m-%d")
+            .map_err(|_| "Please enter the date to copy to as YYYY-MM-DD".to_string())?;
 
-        (total_calories, calories_goal, calories_remaining)
+        Ok((0..self.repeat_days)
+            .map(|offset| (start + Duration::days(offset as i64)).format("%Y-%m-%d").to_string())
+            .collect())
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::Parser;
 use eframe::egui;
 use crate::models::Database;
-use crate::database::{load_database, save_database};
+use crate::database::{load_database, read_database, save_database, DATABASE_CHANGED};
 use crate::gui::{
     LoginScreen,
     RegisterScreen,
@@ -12,13 +15,23 @@ use crate::gui::{
     EditFoodLogScreen,
     UpdateProfileScreen,
     DownloadFoodDataScreen,
+    LogExerciseScreen,
+    ManageTrackersScreen,
+    ExportScreen,
+    ImportScreen,
+    ShoppingListScreen,
+    AccountScreen,
+    TwoFactorScreen,
+    UnlockScreen,
+    AdminScreen,
 };
 use crate::app_state::AppState;
-use crate::gui::undo_manager::UndoManager;
+use crate::undo::UndoManager;
 use crate::gui::styling;
+use crate::config::DataPaths;
+
+use diet_manager_core::{config, models, database, export, import, undo, service, units};
 
-mod models;
-mod database;
 mod app_state;
 mod gui;
 
@@ -36,14 +49,27 @@ struct DietManagerApp {
     update_profile_screen: UpdateProfileScreen,
     undo_manager: UndoManager,
     download_food_data_screen: DownloadFoodDataScreen,
+    log_exercise_screen: LogExerciseScreen,
+    manage_trackers_screen: ManageTrackersScreen,
+    export_screen: ExportScreen,
+    import_screen: ImportScreen,
+    shopping_list_screen: ShoppingListScreen,
+    account_screen: AccountScreen,
+    two_factor_screen: TwoFactorScreen,
+    unlock_screen: UnlockScreen,
+    admin_screen: AdminScreen,
+    saved_version: u64, // undo_manager.version() when the database was last saved
+    notice: Option<String>, // Shown in the top panel after the file changed under us
 }
 
 impl Default for DietManagerApp {
     fn default() -> Self {
-        let db = load_database();
+        // An encrypted file is only loaded once the unlock screen has its passphrase
+        let locked = database::is_locked(&config::database_file());
+        let db = if locked { Database::default() } else { load_database() };
         Self {
             db: db.clone(),
-            current_state: AppState::Login,
+            current_state: if locked { AppState::Unlock } else { AppState::Login },
             login_screen: LoginScreen::new(),
             register_screen: RegisterScreen::new(),
             home_screen: HomeScreen::default(),
@@ -55,45 +81,114 @@ impl Default for DietManagerApp {
             update_profile_screen: UpdateProfileScreen::new(),
             undo_manager: UndoManager::new(100),
             download_food_data_screen: DownloadFoodDataScreen::default(),
+            log_exercise_screen: LogExerciseScreen::new(),
+            manage_trackers_screen: ManageTrackersScreen::new(),
+            export_screen: ExportScreen::new(),
+            import_screen: ImportScreen::new(),
+            shopping_list_screen: ShoppingListScreen::new(),
+            account_screen: AccountScreen::new(),
+            two_factor_screen: TwoFactorScreen::new(),
+            unlock_screen: UnlockScreen::new(),
+            admin_screen: AdminScreen::new(),
+            saved_version: 0,
+            notice: None,
         }
     }
 }
 
 impl DietManagerApp {
-    pub fn record_action(&mut self, description: &str) {
-        println!("[DEBUG] Recording action: {}", description);
-        self.undo_manager.record_action(self.db.clone(), description);
-        if let Err(e) = save_database(&self.db) {
-            eprintln!("Failed to save database after '{}': {}", description, e);
+    // Save the open database, then start over on another file: everything is
+    // reloaded and the undo history starts fresh so it can't restore the old file.
+    fn switch_database(&mut self, path: PathBuf) {
+        if let Err(e) = self.save() {
+            self.login_screen.set_database_message(format!("Failed to save the current database: {}", e), true);
+            return;
         }
+
+        let paths = config::data_paths();
+        let _ = paths.remember_database(&config::database_file());
+        config::set_database_file(path.clone());
+        let _ = paths.remember_database(&path);
+
+        *self = Self::default();
+        self.login_screen.set_database_message(format!("Opened {}", path.display()), false);
     }
 
-    pub fn on_login(&mut self) {
-        println!("[DEBUG] User logged in, initializing undo manager");
-        self.initialize_undo_manager();
+    // Write the database, unless the server or CLI saved the file since it was
+    // read; then their version is loaded instead and this change is dropped.
+    fn save(&mut self) -> std::io::Result<()> {
+        let result = save_database(&self.db);
+        match &result {
+            Ok(()) => self.saved_version = self.undo_manager.version(),
+            Err(e) if e.to_string() == DATABASE_CHANGED => {
+                self.reload("Another program changed the database, so your last change was not saved. Its changes were loaded instead.");
+            }
+            Err(_) => {}
+        }
+        result
     }
 
-    pub fn initialize_undo_manager(&mut self) {
-        println!("[DEBUG] Initializing undo manager with current db state");
+    // Load the file again, staying logged in if the account is still usable.
+    // The undo history starts fresh so it can't bring back the replaced data.
+    fn reload(&mut self, notice: &str) {
+        let mut db = match read_database(&config::database_file()) {
+            Ok(db) => db,
+            Err(e) => {
+                self.notice = Some(format!("Failed to reload the database: {}", e));
+                return;
+            }
+        };
+        let logged_in = db.find_user_by_id(&self.db.current_user).is_some_and(|user| !user.disabled);
+        if logged_in {
+            db.current_user = self.db.current_user.clone();
+        } else if !self.db.current_user.is_empty() {
+            db.current_user.clear();
+            self.current_state = AppState::Login;
+        }
+        self.db = db;
         self.undo_manager.initialize(self.db.clone());
+        self.saved_version = self.undo_manager.version();
+        self.notice = Some(notice.to_string());
     }
 }
 
 impl eframe::App for DietManagerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         styling::apply_theme(ctx);
-        
+
+        // Nothing is read from a database that was never unlocked
+        let unlocked = !matches!(self.current_state, AppState::Unlock);
+        if unlocked && database::changed_on_disk(&config::database_file()) {
+            self.reload("The database was changed by another program and has been reloaded.");
+        }
+        // Keep checking the file while the window is idle
+        ctx.request_repaint_after(Duration::from_secs(1));
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Diet Manager");
+                if let Some(profile) = &config::data_paths().profile {
+                    ui.label(format!("Profile: {}", profile));
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if !self.db.current_user.is_empty() {
                         if let Some(user) = self.db.users.values().find(|u| u.user_id == self.db.current_user) {
-                            ui.label(format!("Logged in as: {}", user.username));
+                            match user.role {
+                                models::Role::Member => ui.label(format!("Logged in as: {}", user.username)),
+                                role => ui.label(format!("Logged in as: {} ({})", user.username, role.label().to_lowercase())),
+                            };
                         }
                     }
                 });
             });
+            if let Some(notice) = self.notice.clone() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::RED, notice);
+                    if ui.small_button("Dismiss").clicked() {
+                        self.notice = None;
+                    }
+                });
+            }
             ui.add_space(8.0);
         });
 
@@ -104,7 +199,7 @@ impl eframe::App for DietManagerApp {
                         ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
                     ),
                     AppState::Register => self.register_screen.render(
-                        ui, &mut self.db, &mut self.current_state
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
                     ),
                     AppState::Home => self.home_screen.render(
                         ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
@@ -128,25 +223,92 @@ impl eframe::App for DietManagerApp {
                         ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
                     ),
                     AppState::UpdateProfile => self.update_profile_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::LogExercise => self.log_exercise_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::ManageTrackers => self.manage_trackers_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::Export => self.export_screen.render(
                         ui, &mut self.db, &mut self.current_state
                     ),
+                    AppState::Import => self.import_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::ShoppingList => self.shopping_list_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::Account => self.account_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::TwoFactor => self.two_factor_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::Unlock => self.unlock_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
+                    AppState::Admin => self.admin_screen.render(
+                        ui, &mut self.db, &mut self.current_state, &mut self.undo_manager
+                    ),
                 }
             });
         });
+
+        // Save every change right away, so the server and CLI see it and it
+        // can't overwrite theirs later
+        if unlocked && self.undo_manager.version() != self.saved_version {
+            if let Err(e) = self.save() {
+                if e.to_string() != DATABASE_CHANGED {
+                    self.notice = Some(format!("Failed to save database: {}", e));
+                }
+            }
+        }
+
+        if let Some(path) = self.login_screen.take_database_switch() {
+            self.switch_database(path);
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        if let Err(e) = save_database(&self.db) {
+        // Nothing was loaded from a database that was never unlocked
+        if matches!(self.current_state, AppState::Unlock) {
+            return;
+        }
+        if let Err(e) = self.save() {
             eprintln!("Failed to save database: {}", e);
         }
     }
 }
 
+#[derive(Parser)]
+#[command(name = "diet_manager", about = "Diet Manager desktop app")]
+struct Args {
+    /// Directory holding database.json (overrides DIET_MANAGER_DATA_DIR)
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+
+    /// Use a separate per-profile database (overrides DIET_MANAGER_PROFILE)
+    #[arg(long)]
+    profile: Option<String>,
+}
+
 fn main() {
+    let args = Args::parse();
+    let configured = DataPaths::resolve(args.data_dir, args.profile)
+        .and_then(|paths| config::init(paths).map_err(|e| format!("Could not prepare data directory: {}", e)));
+    if let Err(error) = configured {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+
     let options = eframe::NativeOptions::default();
-    eframe::run_native(
+    if let Err(e) = eframe::run_native(
         "Diet Manager",
         options,
         Box::new(|_cc| Box::new(DietManagerApp::default()))
-    );
+    ) {
+        eprintln!("Failed to start Diet Manager: {}", e);
+    }
 }
\ No newline at end of file